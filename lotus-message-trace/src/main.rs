@@ -0,0 +1,249 @@
+//! Inspect and filter message traces dumped by scripts.
+//!
+//! Reads JSON Lines written by `lotus_shared::message::trace`, either raw dumps or log files
+//! containing them, merges the traces of multiple scripts by game time and prints the matching
+//! entries.
+
+use std::{
+    io::{self, Read},
+    path::PathBuf,
+};
+
+use anyhow::Context;
+use clap::{Parser, ValueEnum};
+use lotus_shared::message::{
+    trace::{TraceDirection, TraceEntry, TraceFilter},
+    Coupling, MessageSource, MessageTarget,
+};
+
+#[derive(Parser)]
+#[command(version, about)]
+struct Args {
+    /// The traces to read, e.g. one per vehicle. Reads from stdin if none are given.
+    files: Vec<PathBuf>,
+    /// Only show messages with the given namespace.
+    #[arg(long)]
+    namespace: Option<String>,
+    /// Only show messages with the given identifier.
+    #[arg(long)]
+    identifier: Option<String>,
+    /// Only show messages on the given bus.
+    #[arg(long)]
+    bus: Option<String>,
+    /// Only show sent or received messages.
+    #[arg(long, value_enum)]
+    direction: Option<Direction>,
+    /// Only show messages received from or sent across the given coupling.
+    #[arg(long, value_enum)]
+    coupling: Option<CouplingArg>,
+    /// Only show messages from this tick on.
+    #[arg(long)]
+    from_tick: Option<u64>,
+    /// Only show messages up to this tick.
+    #[arg(long)]
+    to_tick: Option<u64>,
+    /// Only show messages whose payload contains the given text.
+    #[arg(long)]
+    contains: Option<String>,
+    /// Print the matching entries as JSON Lines instead of a table.
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Direction {
+    Sent,
+    Received,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum CouplingArg {
+    Front,
+    Rear,
+}
+
+impl Args {
+    fn filter(&self) -> TraceFilter {
+        TraceFilter {
+            namespace: self.namespace.clone(),
+            identifier: self.identifier.clone(),
+            bus: self.bus.clone(),
+            direction: self.direction.map(|direction| match direction {
+                Direction::Sent => TraceDirection::Sent,
+                Direction::Received => TraceDirection::Received,
+            }),
+            coupling: self.coupling.map(|coupling| match coupling {
+                CouplingArg::Front => Coupling::Front,
+                CouplingArg::Rear => Coupling::Rear,
+            }),
+            from_tick: self.from_tick,
+            to_tick: self.to_tick,
+        }
+    }
+}
+
+/// A trace entry together with the name of the trace it was read from.
+struct LabeledEntry {
+    label: String,
+    entry: TraceEntry,
+}
+
+fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    let mut entries = Vec::new();
+    if args.files.is_empty() {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .context("failed to read stdin")?;
+        parse(&input, "stdin", &mut entries);
+    } else {
+        for file in &args.files {
+            let input = std::fs::read_to_string(file)
+                .with_context(|| format!("failed to read {}", file.display()))?;
+            let label = file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            parse(&input, &label, &mut entries);
+        }
+    }
+
+    // Ticks are counted per script, so only the game time lines up the traces of several
+    // scripts. Stable, so entries of the same time keep the order they were recorded in.
+    if entries.iter().all(|e| e.entry.game_time_micros.is_some()) {
+        entries.sort_by_key(|e| e.entry.game_time_micros);
+    } else {
+        if args.files.len() > 1 {
+            eprintln!("some entries have no game time, merging the traces by tick");
+        }
+        entries.sort_by_key(|e| e.entry.tick);
+    }
+
+    let filter = args.filter();
+    let label_width = entries.iter().map(|e| e.label.len()).max().unwrap_or(0);
+
+    for LabeledEntry { label, entry } in entries.iter().filter(|e| {
+        filter.matches(&e.entry)
+            && args
+                .contains
+                .as_ref()
+                .is_none_or(|text| e.entry.payload.to_string().contains(text.as_str()))
+    }) {
+        if args.json {
+            println!("{}", serde_json::to_string(entry)?);
+        } else {
+            println!(
+                "{:>8}  {:>12}  {label:label_width$}  {}  {}  {}",
+                entry.tick,
+                entry.game_time_micros.map(clock).unwrap_or_default(),
+                match entry.direction {
+                    TraceDirection::Sent => "->",
+                    TraceDirection::Received => "<-",
+                },
+                message_name(entry),
+                route(entry),
+            );
+            println!("{:>8}  {:>12}  {}", "", "", entry.payload);
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses all trace entries in the input, ignoring lines that aren't trace entries,
+/// e.g. other log lines. Log prefixes in front of the JSON object are skipped.
+fn parse(input: &str, label: &str, entries: &mut Vec<LabeledEntry>) {
+    let mut skipped = 0;
+
+    for line in input.lines() {
+        let Some(start) = line.find('{') else {
+            continue;
+        };
+
+        match serde_json::from_str::<TraceEntry>(&line[start..]) {
+            Ok(entry) => entries.push(LabeledEntry {
+                label: label.to_string(),
+                entry,
+            }),
+            Err(_) => skipped += 1,
+        }
+    }
+
+    if skipped > 0 {
+        eprintln!("{label}: skipped {skipped} lines that are not trace entries");
+    }
+}
+
+/// Formats the time of day of a game time, e.g. `08:15:02.250`.
+fn clock(unix_micros: i64) -> String {
+    let millis = unix_micros.div_euclid(1_000).rem_euclid(86_400_000);
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        millis / 3_600_000,
+        millis / 60_000 % 60,
+        millis / 1_000 % 60,
+        millis % 1_000
+    )
+}
+
+fn message_name(entry: &TraceEntry) -> String {
+    let meta = &entry.meta;
+    match &meta.bus {
+        Some(bus) => format!("{}:{}@{bus}", meta.namespace, meta.identifier),
+        None => format!("{}:{}", meta.namespace, meta.identifier),
+    }
+}
+
+fn route(entry: &TraceEntry) -> String {
+    match entry.direction {
+        TraceDirection::Sent => {
+            let targets = entry.targets.iter().map(target).collect::<Vec<_>>();
+            match entry.schedule {
+                Some(schedule) => format!("to {} ({schedule:?})", targets.join(", ")),
+                None => format!("to {}", targets.join(", ")),
+            }
+        }
+        TraceDirection::Received => entry
+            .source
+            .as_ref()
+            .map(source)
+            .unwrap_or_else(|| "from unknown".to_string()),
+    }
+}
+
+fn target(target: &MessageTarget) -> String {
+    match target {
+        MessageTarget::Myself => "self".to_string(),
+        MessageTarget::ChildByIndex(index) => format!("child {index}"),
+        MessageTarget::Cockpit(index) => format!("cockpit {index}"),
+        MessageTarget::Broadcast {
+            across_couplings,
+            include_self,
+        } => format!(
+            "broadcast (across couplings: {across_couplings}, include self: {include_self})"
+        ),
+        MessageTarget::AcrossCoupling { coupling, cascade } => {
+            format!("{coupling:?} coupling (cascade: {cascade})")
+        }
+        MessageTarget::Parent => "parent".to_string(),
+    }
+}
+
+fn source(source: &MessageSource) -> String {
+    let mut parts = vec![format!("from {:?}", source.sender)];
+
+    match source.vehicle_offset() {
+        0 => {}
+        offset => parts.push(format!("vehicle {offset:+}")),
+    }
+    if let Some(index) = source.module_slot_index {
+        parts.push(format!("module slot {index}"));
+    }
+    if let Some(index) = source.module_slot_cockpit_index {
+        parts.push(format!("cockpit {index}"));
+    }
+
+    parts.join(", ")
+}
@@ -47,6 +47,9 @@ pub mod log {
     #[link(wasm_import_module = "log")]
     extern "C" {
         pub fn write(level: i32, message: u64);
+        /// Writes a serialized `LogRecord` with its target, fields and spans. Engines without
+        /// this import only support [write].
+        pub fn record(record: u64);
     }
 }
 
@@ -55,6 +58,11 @@ pub mod messages {
     extern "C" {
         pub fn take() -> u64;
         pub fn send(target: u64, message: u64);
+        /// Returns the ID of the scheduled message.
+        pub fn send_scheduled(target: u64, message: u64, schedule: u64) -> u64;
+        /// Returns: 1 if the message was still pending.
+        pub fn cancel_scheduled(id: u64) -> u32;
+        pub fn is_scheduled(id: u64) -> u32;
     }
 }
 
@@ -63,7 +71,14 @@ pub mod textures {
     extern "C" {
         pub fn create(options: u64) -> u32;
         pub fn add_action(texture: u32, options: u64);
+        /// Adds a list of actions at once.
+        pub fn add_actions(texture: u32, actions: u64);
         pub fn get_pixel(texture: u32, x: u32, y: u32) -> u32;
+        /// Reads the pixels of a rectangle, after applying all pending actions.
+        /// Returns: 0 if the pending actions can't be applied yet, e.g. because assets are still
+        /// streaming.
+        /// Returns: otherwise a packed pointer to the raw RGBA8 pixels, row by row.
+        pub fn read_region(texture: u32, region: u64) -> u64;
         pub fn apply_to(texture: u32, name: u64);
         pub fn flush_actions(texture: u32) -> u32;
         pub fn dispose(texture: u32);
@@ -88,6 +103,16 @@ pub mod var {
     }
 }
 
+pub mod recording {
+    #[link(wasm_import_module = "recording")]
+    extern "C" {
+        /// Writes an input recording to the recordings folder of the engine.
+        pub fn save(name: u64, content: u64);
+        /// Returns: 0 if there is no recording with the name.
+        pub fn load(name: u64) -> u64;
+    }
+}
+
 pub mod rand {
     #[link(wasm_import_module = "rand")]
     extern "C" {
@@ -111,6 +136,10 @@ pub mod action {
     extern "C" {
         pub fn register(action: u64);
         pub fn state(action: u64) -> u64;
+        pub fn axis(action: u64) -> u64;
+        /// Returns: 0 if the action is not registered.
+        pub fn bindings(action: u64) -> u64;
+        pub fn rebind(action: u64, request: u64);
     }
 }
 
@@ -155,6 +184,8 @@ pub mod vehicle {
         pub fn open_bus(coupling: u32, bus: u64);
         pub fn close_bus(coupling: u32, bus: u64);
         pub fn is_bus_open(coupling: u32, bus: u64) -> u32;
+        pub fn open_bus_with_metadata(coupling: u32, bus: u64, metadata: u64);
+        pub fn buses(coupling: u32) -> u64;
         pub fn rail_quality(bogie: u32, axle: u32) -> u32;
         pub fn surface_type(bogie: u32, axle: u32) -> u32;
         pub fn inverse_radius(bogie: u32, axle: u32) -> f32;
@@ -246,6 +277,18 @@ impl FfiObject {
         }
     }
 
+    /// Wraps data that is already serialized as msgpack.
+    pub fn from_bytes(data: Box<[u8]>) -> Self {
+        Self {
+            data: FfiObjectData::Boxed(data),
+        }
+    }
+
+    /// Returns the serialized data.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
     pub fn deserialize<T: DeserializeOwned>(&self) -> T {
         rmp_serde::from_slice(self.data.as_slice()).expect("Failed to deserialize value")
     }
@@ -261,6 +304,26 @@ impl FfiObject {
         u64::from_be_bytes(packed)
     }
 
+    /// Returns the packed pointer and hands ownership of the data to the receiver,
+    /// which has to free it with [deallocate].
+    pub fn packed_forget(self) -> u64 {
+        let data = self.data.as_slice();
+        let layout = std::alloc::Layout::from_size_align(data.len(), 8).unwrap();
+
+        let forgotten = unsafe {
+            let ptr = std::alloc::alloc(layout);
+            std::ptr::copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+
+            Self {
+                data: FfiObjectData::Raw(ptr, data.len()),
+            }
+        };
+
+        let packed = forgotten.packed();
+        std::mem::forget(forgotten);
+        packed
+    }
+
     pub fn from_packed(packed: u64) -> Self {
         let packed = packed.to_be_bytes();
         let ptr = u32::from_be_bytes(packed[..4].try_into().unwrap());
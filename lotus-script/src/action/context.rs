@@ -0,0 +1,432 @@
+//! Input contexts that enable and block actions at runtime.
+//!
+//! Actions are put into contexts when registering them, see
+//! [ActionsBuilder::context](super::ActionsBuilder::context). An action in a context only
+//! reports its state and sends [ActionEvent]s while the context is active. Active contexts
+//! block actions of lower priority, so e.g. a text entry mode of a driver display can use the
+//! same keys as the cockpit without operating it. An action that is held while it gets
+//! blocked keeps its state until it is released, so its release is never lost.
+//!
+//! # Example
+//! ```no_run
+//! # use lotus_script::{action::{self, context::*, ActionsBuilder}, input::KeyCode};
+//! let actions = ActionsBuilder::new()
+//!     .push("horn", KeyCode::KeyH)
+//!     .context("ibis_entry")
+//!     .push("ibis_digit_1", KeyCode::Digit1)
+//!     .push("ibis_confirm", KeyCode::Enter)
+//!     .build();
+//!
+//! // When the driver starts entering a route number, make the keys of the cockpit inactive.
+//! push_context(InputContext::new("ibis_entry").with_blocking(Blocking::All));
+//! assert!(action::state("horn").kind.is_released());
+//!
+//! // And when done:
+//! remove_context("ibis_entry");
+//! ```
+
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use lotus_shared::{
+    input::{ActionState, ActionStateKind, KeyChord},
+    message::Message,
+};
+
+use super::{ActionEvent, BindingsChanged, RegisterAction};
+
+/// Which actions of lower priority an active context blocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Blocking {
+    /// Don't block other actions.
+    None,
+    /// Block actions that share a binding with an action of this context.
+    #[default]
+    Bindings,
+    /// Block all actions of lower priority, e.g. for a modal text entry.
+    All,
+}
+
+/// An input context that can be pushed onto the context stack.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InputContext {
+    pub name: String,
+    /// Contexts with a higher priority block contexts with a lower one. Contexts with the same
+    /// priority block the ones pushed before them.
+    pub priority: i32,
+    pub blocking: Blocking,
+}
+
+impl InputContext {
+    /// Creates a context with priority `0` that blocks actions with the same bindings.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            priority: 0,
+            blocking: Blocking::default(),
+        }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn with_blocking(mut self, blocking: Blocking) -> Self {
+        self.blocking = blocking;
+        self
+    }
+}
+
+struct ActionInfo {
+    context: Option<String>,
+    bindings: Vec<KeyChord>,
+}
+
+#[derive(Default)]
+struct Contexts {
+    actions: HashMap<String, ActionInfo>,
+    stack: Vec<InputContext>,
+    /// Actions pressed while enabled, as seen by [state](super::state) and by [ActionEvent]s.
+    held_states: HashSet<String>,
+    held_events: HashSet<String>,
+}
+
+thread_local! {
+    static CONTEXTS: RefCell<Contexts> = RefCell::default();
+}
+
+/// Remembers the contexts and bindings of the registered actions.
+pub(super) fn register(actions: &[RegisterAction]) {
+    CONTEXTS.with_borrow_mut(|contexts| {
+        for action in actions {
+            contexts.actions.insert(
+                action.id.clone(),
+                ActionInfo {
+                    context: action.context.clone(),
+                    bindings: action.default_bindings.clone(),
+                },
+            );
+        }
+    });
+}
+
+/// Activates a context. If it is already active, it is replaced and moved to the top of
+/// the stack.
+pub fn push_context(context: InputContext) {
+    CONTEXTS.with_borrow_mut(|contexts| {
+        contexts.stack.retain(|c| c.name != context.name);
+        contexts.stack.push(context);
+    });
+}
+
+/// Deactivates the most recently pushed context and returns it.
+pub fn pop_context() -> Option<InputContext> {
+    CONTEXTS.with_borrow_mut(|contexts| contexts.stack.pop())
+}
+
+/// Deactivates the context with the given name. Returns `false` if it wasn't active.
+pub fn remove_context(name: &str) -> bool {
+    CONTEXTS.with_borrow_mut(|contexts| {
+        let len = contexts.stack.len();
+        contexts.stack.retain(|c| c.name != name);
+        contexts.stack.len() != len
+    })
+}
+
+/// Deactivates all contexts.
+pub fn clear_contexts() {
+    CONTEXTS.with_borrow_mut(|contexts| contexts.stack.clear());
+}
+
+/// Returns `true` if the context with the given name is active.
+pub fn is_context_active(name: &str) -> bool {
+    CONTEXTS.with_borrow(|contexts| contexts.stack.iter().any(|c| c.name == name))
+}
+
+/// Returns the active contexts, starting with the one pushed first.
+pub fn active_contexts() -> Vec<InputContext> {
+    CONTEXTS.with_borrow(|contexts| contexts.stack.clone())
+}
+
+/// Returns `true` if the action is in an active context and not blocked by a context of higher
+/// priority. Actions that weren't registered by this script are always enabled.
+pub fn is_action_enabled(action: &str) -> bool {
+    CONTEXTS.with_borrow(|contexts| contexts.is_enabled(action))
+}
+
+/// Returns the state of an action if it is enabled or was pressed before it got blocked,
+/// otherwise [ActionStateKind::None].
+pub(super) fn filter_state(action: &str, state: ActionState) -> ActionState {
+    CONTEXTS.with_borrow_mut(|contexts| {
+        let enabled = contexts.is_enabled(action);
+        if let_through(&mut contexts.held_states, enabled, action, state.kind) {
+            state
+        } else {
+            ActionState {
+                kind: ActionStateKind::None,
+                cockpit_index: None,
+                uv: None,
+            }
+        }
+    })
+}
+
+/// Applies [BindingsChanged] messages and drops the [ActionEvent]s of actions that are not
+/// enabled, except for releases of actions that were pressed before.
+pub(crate) fn filter_events(messages: Vec<Message>) -> Vec<Message> {
+    CONTEXTS.with_borrow_mut(|contexts| contexts.filter_events(messages))
+}
+
+/// Decides whether the state of an action is let through, tracking the held actions.
+fn let_through(
+    held: &mut HashSet<String>,
+    enabled: bool,
+    action: &str,
+    kind: ActionStateKind,
+) -> bool {
+    let pressed = matches!(
+        kind,
+        ActionStateKind::JustPressed | ActionStateKind::Pressed
+    );
+
+    if enabled {
+        if pressed {
+            held.insert(action.to_string());
+        } else {
+            held.remove(action);
+        }
+        return true;
+    }
+
+    match kind {
+        // A new press of a blocked action.
+        ActionStateKind::JustPressed => {
+            held.remove(action);
+            false
+        }
+        ActionStateKind::Pressed => held.contains(action),
+        ActionStateKind::JustReleased | ActionStateKind::None => held.remove(action),
+    }
+}
+
+impl Contexts {
+    fn filter_events(&mut self, messages: Vec<Message>) -> Vec<Message> {
+        for message in &messages {
+            if let Ok(changed) = message.value::<BindingsChanged>() {
+                self.set_bindings(&changed);
+            }
+        }
+
+        messages
+            .into_iter()
+            .filter(|message| match message.value::<ActionEvent>() {
+                Ok(event) => {
+                    let enabled = self.is_enabled(&event.name);
+                    let_through(
+                        &mut self.held_events,
+                        enabled,
+                        &event.name,
+                        event.state.kind,
+                    )
+                }
+                Err(_) => true,
+            })
+            .collect()
+    }
+
+    /// Uses the bindings the user chose to decide which actions [Blocking::Bindings] blocks.
+    fn set_bindings(&mut self, changed: &BindingsChanged) {
+        if let Some(info) = self.actions.get_mut(&changed.action) {
+            info.bindings.clone_from(&changed.bindings.keys);
+        }
+    }
+
+    /// Returns the rank of an active context, actions without a context having the lowest rank.
+    fn rank(&self, context: Option<&str>) -> Option<(i32, isize)> {
+        let Some(context) = context else {
+            return Some((i32::MIN, -1));
+        };
+
+        self.stack
+            .iter()
+            .enumerate()
+            .find(|(_, c)| c.name == context)
+            .map(|(i, c)| (c.priority, i as isize))
+    }
+
+    fn is_enabled(&self, action: &str) -> bool {
+        let Some(info) = self.actions.get(action) else {
+            return true;
+        };
+        let Some(rank) = self.rank(info.context.as_deref()) else {
+            return false;
+        };
+
+        self.stack
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| (c.priority, *i as isize) > rank)
+            .all(|(_, c)| match c.blocking {
+                Blocking::None => true,
+                Blocking::All => false,
+                Blocking::Bindings => !self.actions.values().any(|other| {
+                    other.context.as_deref() == Some(c.name.as_str())
+                        && other.bindings.iter().any(|b| info.bindings.contains(b))
+                }),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lotus_shared::input::KeyCode;
+
+    use super::{super::ActionBindings, *};
+
+    fn contexts(actions: &[RegisterAction]) -> Contexts {
+        let mut contexts = Contexts::default();
+        for action in actions {
+            contexts.actions.insert(
+                action.id.clone(),
+                ActionInfo {
+                    context: action.context.clone(),
+                    bindings: action.default_bindings.clone(),
+                },
+            );
+        }
+        contexts
+    }
+
+    fn actions() -> Vec<RegisterAction> {
+        super::super::ActionsBuilder::new()
+            .push("horn", KeyCode::KeyH)
+            .push("bell", KeyCode::KeyB)
+            .context("ibis")
+            .push("ibis_h", KeyCode::KeyH)
+            .context("menu")
+            .push("menu_confirm", KeyCode::Enter)
+            .build()
+    }
+
+    #[test]
+    fn test_inactive_context() {
+        let contexts = contexts(&actions());
+
+        assert!(contexts.is_enabled("horn"));
+        assert!(!contexts.is_enabled("ibis_h"));
+        assert!(contexts.is_enabled("unknown"));
+    }
+
+    #[test]
+    fn test_blocking() {
+        let mut contexts = contexts(&actions());
+
+        contexts.stack.push(InputContext::new("ibis"));
+        assert!(contexts.is_enabled("ibis_h"));
+        assert!(!contexts.is_enabled("horn"));
+        assert!(contexts.is_enabled("bell"));
+
+        contexts.stack[0].blocking = Blocking::None;
+        assert!(contexts.is_enabled("horn"));
+
+        contexts.stack[0].blocking = Blocking::All;
+        assert!(!contexts.is_enabled("bell"));
+    }
+
+    #[test]
+    fn test_changed_bindings() {
+        let mut contexts = contexts(&actions());
+        contexts.stack.push(InputContext::new("ibis"));
+
+        contexts.set_bindings(&BindingsChanged {
+            action: "ibis_h".to_string(),
+            bindings: ActionBindings {
+                keys: vec![KeyCode::KeyB.into()],
+                axes: Vec::new(),
+            },
+        });
+        assert!(contexts.is_enabled("horn"));
+        assert!(!contexts.is_enabled("bell"));
+    }
+
+    #[test]
+    fn test_ranking() {
+        let mut contexts = contexts(&actions());
+
+        // The menu was pushed last, so it blocks the ibis context with the same priority.
+        contexts.stack.push(InputContext::new("ibis"));
+        contexts
+            .stack
+            .push(InputContext::new("menu").with_blocking(Blocking::All));
+        assert!(contexts.is_enabled("menu_confirm"));
+        assert!(!contexts.is_enabled("ibis_h"));
+
+        // A higher priority wins over the order.
+        contexts.stack[0].priority = 1;
+        assert!(contexts.is_enabled("ibis_h"));
+        assert!(contexts.is_enabled("menu_confirm"));
+        assert!(!contexts.is_enabled("horn"));
+
+        contexts.stack[0].blocking = Blocking::All;
+        assert!(!contexts.is_enabled("menu_confirm"));
+    }
+
+    fn event(name: &str, kind: ActionStateKind) -> Message {
+        Message::new(&ActionEvent {
+            name: name.to_string(),
+            state: ActionState {
+                kind,
+                cockpit_index: Some(0),
+                uv: None,
+            },
+        })
+    }
+
+    #[test]
+    fn test_release_after_blocking() {
+        let mut contexts = contexts(&actions());
+
+        let pressed = contexts.filter_events(vec![event("horn", ActionStateKind::JustPressed)]);
+        assert_eq!(pressed.len(), 1);
+
+        // The ibis context blocks the horn while it is held, its release still arrives.
+        contexts.stack.push(InputContext::new("ibis"));
+        let released = contexts.filter_events(vec![
+            event("horn", ActionStateKind::JustReleased),
+            event("bell", ActionStateKind::JustReleased),
+        ]);
+        assert_eq!(released.len(), 2);
+        assert_eq!(
+            released[0].value::<ActionEvent>().unwrap().state.kind,
+            ActionStateKind::JustReleased
+        );
+
+        // New presses are blocked, and so are their releases.
+        let blocked = contexts.filter_events(vec![
+            event("horn", ActionStateKind::JustPressed),
+            event("horn", ActionStateKind::JustReleased),
+        ]);
+        assert!(blocked.is_empty());
+    }
+
+    #[test]
+    fn test_held_state_after_blocking() {
+        let mut contexts = contexts(&actions());
+        let let_state = |contexts: &mut Contexts, kind| {
+            let enabled = contexts.is_enabled("horn");
+            let_through(&mut contexts.held_states, enabled, "horn", kind)
+        };
+
+        assert!(let_state(&mut contexts, ActionStateKind::JustPressed));
+        contexts.stack.push(InputContext::new("ibis"));
+        assert!(let_state(&mut contexts, ActionStateKind::Pressed));
+        assert!(let_state(&mut contexts, ActionStateKind::JustReleased));
+        assert!(!let_state(&mut contexts, ActionStateKind::None));
+
+        assert!(!let_state(&mut contexts, ActionStateKind::JustPressed));
+        assert!(!let_state(&mut contexts, ActionStateKind::Pressed));
+    }
+}
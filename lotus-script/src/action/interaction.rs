@@ -0,0 +1,587 @@
+//! Helpers for clicking and dragging cockpit controls, based on the [uv](ActionState::uv)
+//! coordinate the engine reports for an action.
+//!
+//! Every helper has an `update` method taking the state of an action, and a `poll` method that
+//! fetches it itself. [Hotspots] and [Drag] take the [ActionState] with its uv coordinate,
+//! [DoubleClick] also the elapsed time, and [HoldRepeat] only the [ActionStateKind] and the
+//! elapsed time.
+//!
+//! # Example
+//! ```no_run
+//! # use lotus_script::action::interaction::*;
+//! let mut panel = Hotspots::new()
+//!     .with("door_open", UvRect::new((0.1, 0.1), (0.2, 0.2)))
+//!     .with("door_close", UvRect::new((0.3, 0.1), (0.4, 0.2)));
+//! let mut master = Drag::new(DragAxis::Vertical, -1.0..=1.0)
+//!     .with_sensitivity(-4.0)
+//!     .with_detents([0.0], 0.05);
+//!
+//! // In `tick`:
+//! if let Some(HotspotEvent::Clicked(name)) = panel.poll("cockpit_click") {
+//!     // ...
+//! }
+//! if let Some(value) = master.poll("master_controller") {
+//!     // ...
+//! }
+//! ```
+
+use std::ops::RangeInclusive;
+
+use lotus_shared::{
+    input::{ActionState, ActionStateKind},
+    math::Vec2,
+};
+
+/// A rectangle in uv space, `(0, 0)` being the top left corner of the texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl UvRect {
+    pub fn new(min: impl Into<Vec2>, max: impl Into<Vec2>) -> Self {
+        Self {
+            min: min.into(),
+            max: max.into(),
+        }
+    }
+
+    pub fn contains(&self, uv: Vec2) -> bool {
+        uv.cmpge(self.min).all() && uv.cmplt(self.max).all()
+    }
+}
+
+/// An interaction with a [Hotspots] region.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotspotEvent {
+    /// The action was pressed over the hotspot.
+    Pressed(String),
+    /// The action was released after pressing the hotspot, wherever the cursor is.
+    Released(String),
+    /// The action was pressed and released over the same hotspot.
+    Clicked(String),
+}
+
+/// Named uv regions, e.g. the buttons of a panel that is a single mesh.
+#[derive(Debug, Clone, Default)]
+pub struct Hotspots {
+    regions: Vec<(String, UvRect)>,
+    cockpit_index: Option<u8>,
+    pressed: Option<String>,
+}
+
+impl Hotspots {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a hotspot. Hotspots added earlier win where they overlap.
+    pub fn with(mut self, name: impl Into<String>, rect: UvRect) -> Self {
+        self.insert(name, rect);
+        self
+    }
+
+    /// Only reacts to presses routed to the given cockpit. A release from another cockpit
+    /// still ends a press, without clicking.
+    pub fn with_cockpit_index(mut self, cockpit_index: u8) -> Self {
+        self.cockpit_index = Some(cockpit_index);
+        self
+    }
+
+    /// Adds or moves a hotspot.
+    pub fn insert(&mut self, name: impl Into<String>, rect: UvRect) {
+        let name = name.into();
+        match self.regions.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, r)) => *r = rect,
+            None => self.regions.push((name, rect)),
+        }
+    }
+
+    /// Returns the hotspot at the given uv coordinate.
+    pub fn hit(&self, uv: Vec2) -> Option<&str> {
+        self.regions
+            .iter()
+            .find(|(_, rect)| rect.contains(uv))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Returns the hotspot that is currently pressed.
+    pub fn pressed(&self) -> Option<&str> {
+        self.pressed.as_deref()
+    }
+
+    /// Routes the state of an action to the hotspots.
+    pub fn update(&mut self, state: &ActionState) -> Option<HotspotEvent> {
+        let other_cockpit =
+            self.cockpit_index.is_some() && state.cockpit_index != self.cockpit_index;
+        let hit = state
+            .uv
+            .filter(|_| !other_cockpit)
+            .and_then(|uv| self.hit(uv))
+            .map(str::to_string);
+
+        match state.kind {
+            ActionStateKind::JustPressed if other_cockpit => None,
+            ActionStateKind::JustPressed => {
+                self.pressed = hit.clone();
+                hit.map(HotspotEvent::Pressed)
+            }
+            ActionStateKind::JustReleased => {
+                let pressed = self.pressed.take()?;
+                if hit.as_ref() == Some(&pressed) {
+                    Some(HotspotEvent::Clicked(pressed))
+                } else {
+                    Some(HotspotEvent::Released(pressed))
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Fetches the state of the given action and routes it to the hotspots.
+    pub fn poll(&mut self, action: &str) -> Option<HotspotEvent> {
+        self.update(&super::state(action))
+    }
+}
+
+/// How a [Drag] turns cursor movement into a value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DragAxis {
+    /// Moving right increases the value, e.g. a horizontal lever.
+    Horizontal,
+    /// Moving down increases the value, e.g. a lever. Use a negative sensitivity to increase
+    /// the value when moving up.
+    Vertical,
+    /// Moving clockwise around the center increases the value by the angle in radians,
+    /// e.g. a rotary switch.
+    Rotary { center: Vec2 },
+}
+
+/// Drags a value like the position of a lever or rotary switch.
+#[derive(Debug, Clone)]
+pub struct Drag {
+    axis: DragAxis,
+    range: RangeInclusive<f32>,
+    sensitivity: f32,
+    detents: Vec<f32>,
+    detent_width: f32,
+    raw: f32,
+    value: f32,
+    last_uv: Option<Vec2>,
+}
+
+impl Drag {
+    /// Creates a drag starting at the start of the range, changing the value by the length of
+    /// the range when moving over the whole texture.
+    pub fn new(axis: DragAxis, range: RangeInclusive<f32>) -> Self {
+        Self {
+            axis,
+            sensitivity: range.end() - range.start(),
+            raw: *range.start(),
+            value: *range.start(),
+            range,
+            detents: Vec::new(),
+            detent_width: 0.0,
+            last_uv: None,
+        }
+    }
+
+    /// Sets how much the value changes per uv unit or, for [DragAxis::Rotary], per radian.
+    pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.sensitivity = sensitivity;
+        self
+    }
+
+    /// Adds detents the value snaps to when it is closer than `width`. With a `width` of
+    /// [f32::INFINITY], the value always snaps to the closest detent, like the positions of a
+    /// rotary switch.
+    pub fn with_detents(mut self, detents: impl IntoIterator<Item = f32>, width: f32) -> Self {
+        self.detents = detents.into_iter().collect();
+        self.detent_width = width;
+        self.value = self.snap(self.raw);
+        self
+    }
+
+    /// Sets the value, e.g. when the control is moved by the script.
+    pub fn set_value(&mut self, value: f32) {
+        self.raw = value.clamp(*self.range.start(), *self.range.end());
+        self.value = self.snap(self.raw);
+    }
+
+    pub fn value(&self) -> f32 {
+        self.value
+    }
+
+    /// Returns `true` while the control is being dragged.
+    pub fn is_dragging(&self) -> bool {
+        self.last_uv.is_some()
+    }
+
+    fn snap(&self, value: f32) -> f32 {
+        self.detents
+            .iter()
+            .map(|d| (*d, (d - value).abs()))
+            .filter(|(_, distance)| *distance <= self.detent_width)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(value, |(d, _)| d)
+    }
+
+    fn delta(&self, from: Vec2, to: Vec2) -> f32 {
+        match self.axis {
+            DragAxis::Horizontal => to.x - from.x,
+            DragAxis::Vertical => to.y - from.y,
+            DragAxis::Rotary { center } => {
+                let (from, to) = (from - center, to - center);
+                if from == Vec2::ZERO || to == Vec2::ZERO {
+                    0.0
+                } else {
+                    from.angle_to(to)
+                }
+            }
+        }
+    }
+
+    /// Routes the state of an action to the control. Returns the new value if it changed.
+    pub fn update(&mut self, state: &ActionState) -> Option<f32> {
+        match state.kind {
+            ActionStateKind::JustPressed => {
+                self.last_uv = state.uv;
+                None
+            }
+            ActionStateKind::Pressed => {
+                let (last, uv) = (self.last_uv?, state.uv?);
+                self.last_uv = Some(uv);
+
+                let raw = self.raw + self.delta(last, uv) * self.sensitivity;
+                self.raw = raw.clamp(*self.range.start(), *self.range.end());
+
+                let value = self.snap(self.raw);
+                (value != self.value).then(|| {
+                    self.value = value;
+                    value
+                })
+            }
+            ActionStateKind::JustReleased | ActionStateKind::None => {
+                // Stay in the detent instead of between it and the cursor.
+                self.raw = self.value;
+                self.last_uv = None;
+                None
+            }
+        }
+    }
+
+    /// Fetches the state of the given action and routes it to the control.
+    pub fn poll(&mut self, action: &str) -> Option<f32> {
+        self.update(&super::state(action))
+    }
+}
+
+/// Repeats an action while it is held, like a key on a keyboard, e.g. to scroll through a list.
+#[derive(Debug, Clone)]
+pub struct HoldRepeat {
+    delay: f32,
+    interval: f32,
+    held: Option<f32>,
+}
+
+impl HoldRepeat {
+    /// Repeats after holding for `delay` seconds, then every `interval` seconds.
+    pub fn new(delay: f32, interval: f32) -> Self {
+        Self {
+            delay,
+            interval,
+            held: None,
+        }
+    }
+
+    /// Advances by `delta` seconds and returns how often the action triggers in this tick,
+    /// including the initial press.
+    pub fn update(&mut self, kind: ActionStateKind, delta: f32) -> u32 {
+        if !kind.is_pressed() {
+            self.held = None;
+            return 0;
+        }
+
+        let Some(held) = self.held else {
+            self.held = Some(0.0);
+            return 1;
+        };
+
+        let now = held + delta;
+        self.held = Some(now);
+
+        let repeats = |t: f32| {
+            if t < self.delay || self.interval <= 0.0 {
+                0
+            } else {
+                ((t - self.delay) / self.interval) as u32 + 1
+            }
+        };
+        repeats(now) - repeats(held)
+    }
+
+    /// Fetches the state of the given action and returns how often it triggers in this tick.
+    pub fn poll(&mut self, action: &str) -> u32 {
+        self.update(super::state(action).kind, crate::time::delta())
+    }
+}
+
+/// A click detected by [DoubleClick].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Click {
+    Single,
+    Double,
+}
+
+/// Detects double clicks, e.g. to reset a trip counter.
+#[derive(Debug, Clone)]
+pub struct DoubleClick {
+    max_interval: f32,
+    max_distance: f32,
+    last: Option<(f32, Option<Vec2>)>,
+}
+
+impl DoubleClick {
+    /// Detects two presses within `max_interval` seconds as a double click.
+    pub fn new(max_interval: f32) -> Self {
+        Self {
+            max_interval,
+            max_distance: f32::INFINITY,
+            last: None,
+        }
+    }
+
+    /// Only detects a double click if the presses are at most this far apart in uv space.
+    pub fn with_max_distance(mut self, max_distance: f32) -> Self {
+        self.max_distance = max_distance;
+        self
+    }
+
+    /// Advances by `delta` seconds and returns the click if the action was just pressed.
+    /// The first press is always reported as [Click::Single].
+    pub fn update(&mut self, state: &ActionState, delta: f32) -> Option<Click> {
+        if let Some((elapsed, _)) = &mut self.last {
+            *elapsed += delta;
+        }
+
+        if !state.kind.is_just_pressed() {
+            return None;
+        }
+
+        let is_double = self.last.is_some_and(|(elapsed, uv)| {
+            let near = match (uv, state.uv) {
+                (Some(a), Some(b)) => a.distance(b) <= self.max_distance,
+                _ => true,
+            };
+            elapsed <= self.max_interval && near
+        });
+
+        if is_double {
+            self.last = None;
+            Some(Click::Double)
+        } else {
+            self.last = Some((0.0, state.uv));
+            Some(Click::Single)
+        }
+    }
+
+    /// Fetches the state of the given action and returns the click if it was just pressed.
+    pub fn poll(&mut self, action: &str) -> Option<Click> {
+        self.update(&super::state(action), crate::time::delta())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(kind: ActionStateKind, uv: Option<(f32, f32)>) -> ActionState {
+        ActionState {
+            kind,
+            cockpit_index: None,
+            uv: uv.map(Vec2::from),
+        }
+    }
+
+    fn panel() -> Hotspots {
+        Hotspots::new()
+            .with("open", UvRect::new((0.0, 0.0), (0.5, 0.5)))
+            .with("close", UvRect::new((0.25, 0.0), (1.0, 0.5)))
+    }
+
+    #[test]
+    fn test_hotspots_click() {
+        let mut panel = panel();
+
+        // The hotspot added first wins where they overlap.
+        assert_eq!(panel.hit(Vec2::new(0.3, 0.1)), Some("open"));
+        assert_eq!(
+            panel.update(&state(ActionStateKind::JustPressed, Some((0.3, 0.1)))),
+            Some(HotspotEvent::Pressed("open".to_string()))
+        );
+        assert_eq!(panel.pressed(), Some("open"));
+        assert_eq!(
+            panel.update(&state(ActionStateKind::Pressed, Some((0.3, 0.1)))),
+            None
+        );
+        assert_eq!(
+            panel.update(&state(ActionStateKind::JustReleased, Some((0.1, 0.1)))),
+            Some(HotspotEvent::Clicked("open".to_string()))
+        );
+        assert_eq!(panel.pressed(), None);
+
+        panel.update(&state(ActionStateKind::JustPressed, Some((0.1, 0.1))));
+        assert_eq!(
+            panel.update(&state(ActionStateKind::JustReleased, Some((0.9, 0.1)))),
+            Some(HotspotEvent::Released("open".to_string()))
+        );
+
+        assert_eq!(
+            panel.update(&state(ActionStateKind::JustPressed, Some((0.5, 0.9)))),
+            None
+        );
+        assert_eq!(
+            panel.update(&state(ActionStateKind::JustReleased, Some((0.1, 0.1)))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hotspots_cockpit() {
+        let mut panel = panel().with_cockpit_index(1);
+        let in_cockpit = |kind, cockpit_index| ActionState {
+            cockpit_index,
+            ..state(kind, Some((0.1, 0.1)))
+        };
+
+        assert_eq!(
+            panel.update(&in_cockpit(ActionStateKind::JustPressed, Some(0))),
+            None
+        );
+        assert!(panel
+            .update(&in_cockpit(ActionStateKind::JustPressed, Some(1)))
+            .is_some());
+
+        // A release reported for another cockpit still ends the press.
+        assert_eq!(
+            panel.update(&in_cockpit(ActionStateKind::JustReleased, None)),
+            Some(HotspotEvent::Released("open".to_string()))
+        );
+        assert_eq!(panel.pressed(), None);
+    }
+
+    #[test]
+    fn test_drag() {
+        let mut drag = Drag::new(DragAxis::Vertical, -1.0..=1.0).with_sensitivity(-4.0);
+        assert_eq!(drag.value(), -1.0);
+
+        drag.update(&state(ActionStateKind::JustPressed, Some((0.5, 0.5))));
+        assert!(drag.is_dragging());
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.5, 0.25)))),
+            Some(0.0)
+        );
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.5, 0.25)))),
+            None
+        );
+        // Clamped to the range.
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.5, 0.0)))),
+            Some(1.0)
+        );
+
+        drag.update(&state(ActionStateKind::JustReleased, None));
+        assert!(!drag.is_dragging());
+        // Not pressed on the control, so moving doesn't change it.
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.5, 1.0)))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_drag_detents() {
+        let mut drag = Drag::new(DragAxis::Horizontal, 0.0..=1.0).with_detents([0.5], 0.1);
+
+        drag.update(&state(ActionStateKind::JustPressed, Some((0.0, 0.0))));
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.45, 0.0)))),
+            Some(0.5)
+        );
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.55, 0.0)))),
+            None
+        );
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.7, 0.0)))),
+            Some(0.7)
+        );
+
+        // Released in the detent, the next drag starts from it.
+        drag.update(&state(ActionStateKind::Pressed, Some((0.45, 0.0))));
+        drag.update(&state(ActionStateKind::JustReleased, None));
+        drag.update(&state(ActionStateKind::JustPressed, Some((0.0, 0.0))));
+        let value = drag.update(&state(ActionStateKind::Pressed, Some((0.2, 0.0))));
+        assert!((value.unwrap() - 0.7).abs() < 1e-6);
+
+        drag.set_value(0.58);
+        assert_eq!(drag.value(), 0.5);
+        drag.set_value(2.0);
+        assert_eq!(drag.value(), 1.0);
+    }
+
+    #[test]
+    fn test_drag_rotary() {
+        let center = Vec2::new(0.5, 0.5);
+        let mut drag = Drag::new(DragAxis::Rotary { center }, 0.0..=4.0)
+            .with_sensitivity(1.0)
+            .with_detents([0.0, 1.0, 2.0, 3.0], f32::INFINITY);
+
+        drag.update(&state(ActionStateKind::JustPressed, Some((1.0, 0.5))));
+        // A quarter turn clockwise, y pointing down.
+        let value = drag.update(&state(ActionStateKind::Pressed, Some((0.5, 1.0))));
+        assert_eq!(value, Some(2.0));
+
+        // The center has no angle.
+        assert_eq!(
+            drag.update(&state(ActionStateKind::Pressed, Some((0.5, 0.5)))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_hold_repeat() {
+        let mut repeat = HoldRepeat::new(0.5, 0.1);
+
+        assert_eq!(repeat.update(ActionStateKind::JustPressed, 0.016), 1);
+        assert_eq!(repeat.update(ActionStateKind::Pressed, 0.4), 0);
+        assert_eq!(repeat.update(ActionStateKind::Pressed, 0.1), 1);
+        assert_eq!(repeat.update(ActionStateKind::Pressed, 0.25), 2);
+        assert_eq!(repeat.update(ActionStateKind::JustReleased, 0.1), 0);
+        assert_eq!(repeat.update(ActionStateKind::Pressed, 0.1), 1);
+
+        let mut no_interval = HoldRepeat::new(0.5, 0.0);
+        no_interval.update(ActionStateKind::JustPressed, 0.0);
+        assert_eq!(no_interval.update(ActionStateKind::Pressed, 1.0), 0);
+    }
+
+    #[test]
+    fn test_double_click() {
+        let mut click = DoubleClick::new(0.3).with_max_distance(0.1);
+        let press = |uv| state(ActionStateKind::JustPressed, Some(uv));
+        let idle = state(ActionStateKind::None, None);
+
+        assert_eq!(click.update(&press((0.5, 0.5)), 0.0), Some(Click::Single));
+        assert_eq!(click.update(&idle, 0.1), None);
+        assert_eq!(click.update(&press((0.52, 0.5)), 0.1), Some(Click::Double));
+        // A third click starts over.
+        assert_eq!(click.update(&press((0.5, 0.5)), 0.1), Some(Click::Single));
+
+        // Too slow.
+        assert_eq!(click.update(&press((0.5, 0.5)), 0.4), Some(Click::Single));
+        // Too far apart.
+        assert_eq!(click.update(&press((0.9, 0.5)), 0.1), Some(Click::Single));
+    }
+}
@@ -0,0 +1,74 @@
+use lotus_script_sys::FfiObject;
+pub use lotus_shared::action::*;
+use lotus_shared::input::{ActionState, AxisState};
+
+pub mod context;
+pub mod interaction;
+
+/// Get the current state of an action. If the action is not registered, it will return `ActionState::None`.
+///
+/// If the action is not enabled by the active input contexts, it will also return
+/// `ActionState::None`, unless it was held before it got blocked, see [context].
+pub fn state(action: &str) -> ActionState {
+    context::filter_state(action, state_unfiltered(action))
+}
+
+/// Get the current state of an action, ignoring the active input contexts.
+pub fn state_unfiltered(action: &str) -> ActionState {
+    crate::input::recording::action_state(action, || {
+        let action = FfiObject::new(&action);
+        let state = unsafe { lotus_script_sys::action::state(action.packed()) };
+
+        FfiObject::from_packed(state).deserialize()
+    })
+}
+
+/// Get the current value of an axis action, with its dead zone, inversion and curve applied.
+/// If the action is not registered or not an axis action, it will return `0.0`.
+pub fn axis(action: &str) -> f32 {
+    axis_state(action).value
+}
+
+/// Get the current state of an axis action, including the cockpit the input is routed to.
+/// Axis actions that are not enabled by the active input contexts have the value `0.0`.
+pub fn axis_state(action: &str) -> AxisState {
+    if !context::is_action_enabled(action) {
+        return AxisState::default();
+    }
+
+    crate::input::recording::axis_state(action, || {
+        let action = FfiObject::new(&action);
+        let state = unsafe { lotus_script_sys::action::axis(action.packed()) };
+
+        FfiObject::from_packed(state).deserialize()
+    })
+}
+
+/// Get the effective bindings of an action, e.g. to show them on a help texture.
+/// Returns `None` if the action is not registered.
+pub fn bindings(action: &str) -> Option<ActionBindings> {
+    let action = FfiObject::new(&action);
+    let bindings = unsafe { lotus_script_sys::action::bindings(action.packed()) };
+
+    (bindings != 0).then(|| FfiObject::from_packed(bindings).deserialize())
+}
+
+/// Request a change of the bindings of an action, e.g. from an in-vehicle menu.
+/// The change is applied asynchronously and reported with a [BindingsChanged] message.
+pub fn rebind(action: &str, request: Rebind) {
+    let action = FfiObject::new(&action);
+    let request = FfiObject::new(&request);
+    unsafe { lotus_script_sys::action::rebind(action.packed(), request.packed()) };
+}
+
+#[doc(hidden)]
+pub fn register_many(actions: &[RegisterAction]) {
+    context::register(actions);
+
+    for action in actions {
+        let action = FfiObject::new(&action);
+        unsafe {
+            lotus_script_sys::action::register(action.packed());
+        }
+    }
+}
@@ -1,5 +1,16 @@
+//! Legacy engine events.
+//!
+//! All events are also available as built-in message types in [crate::message] and are received
+//! through [Script::on_message](crate::Script::on_message). Use [Event::into_message] to convert them.
+
 use serde::{Deserialize, Serialize};
 
+use crate::message::{self, Message, TriggerKind};
+pub use crate::message::{
+    BroadcastEvent, BroadcastValue, ButtonEvent, FloatInputEvent, ReceiveMessageEvent,
+    ReceiveMessageKind, ReceiveMessageValue, VehicleEnteredEvent,
+};
+
 #[derive(Debug, Serialize, Deserialize)]
 pub enum Event {
     EnterTrigger(TriggerEvent),
@@ -12,67 +23,48 @@ pub enum Event {
     Empty,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum BroadcastValue {
-    Single(f32),
-    String(String),
-    Integer(i32),
-    Serializeable(serde_json::Value),
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ReceiveMessageValue {
-    Single(f32),
-    String(String),
-    Integer(i32),
-}
+impl Event {
+    /// Converts the event into the corresponding built-in message. Returns `None` for [Event::Empty].
+    pub fn into_message(self) -> Option<Message> {
+        let message = match self {
+            Self::EnterTrigger(e) => Message::new(&e.with_kind(TriggerKind::Enter)),
+            Self::LeaveTrigger(e) => Message::new(&e.with_kind(TriggerKind::Leave)),
+            Self::Button(e) => Message::new(&e),
+            Self::FloatInput(e) => Message::new(&e),
+            Self::Broadcast(e) => Message::new(&e),
+            Self::VehicleEntered(e) => Message::new(&e),
+            Self::ReceiveMessage(e) => Message::new(&e),
+            Self::Empty => return None,
+        };
 
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ReceiveMessageKind {
-    Parent,
-    Child,
+        Some(message)
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ReceiveMessageEvent {
-    pub slot_index: i32,
-    pub id: String,
-    pub kind: ReceiveMessageKind,
-    pub value: ReceiveMessageValue,
+impl From<Event> for Option<Message> {
+    fn from(event: Event) -> Self {
+        event.into_message()
+    }
 }
 
+/// A trigger event without its kind, which is given by the [Event] variant.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TriggerEvent {
     pub id: String,
     pub sensor_index: i32,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ButtonEvent {
-    pub id: String,
-    pub value: bool,
-    pub cockpit_index: u8,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct FloatInputEvent {
-    pub id: String,
-    pub value: f32,
-    pub cockpit_index: u8,
+impl TriggerEvent {
+    /// Converts the event into a [message::TriggerEvent] of the given kind.
+    pub fn with_kind(self, kind: TriggerKind) -> message::TriggerEvent {
+        message::TriggerEvent {
+            id: self.id,
+            sensor_index: self.sensor_index,
+            kind,
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct BroadcastEvent {
-    pub bus_id: String,
-    pub id: String,
-    pub value: BroadcastValue,
-}
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VehicleEnteredEvent {
-    pub id: String,
-    /// Current speed in meters per second
-    pub speed_mps: f32,
-}
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReceiveMessageFromChildEvent {
     pub slot_index: i32,
@@ -80,9 +72,128 @@ pub struct ReceiveMessageFromChildEvent {
     pub value: i32,
 }
 
+impl From<ReceiveMessageFromChildEvent> for ReceiveMessageEvent {
+    fn from(event: ReceiveMessageFromChildEvent) -> Self {
+        Self {
+            slot_index: event.slot_index,
+            id: event.id,
+            kind: ReceiveMessageKind::Child,
+            value: ReceiveMessageValue::Integer(event.value),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReceiveMessageFromParentEvent {
     pub index_of_class: i32,
     pub id: String,
     pub value: i32,
 }
+
+impl From<ReceiveMessageFromParentEvent> for ReceiveMessageEvent {
+    fn from(event: ReceiveMessageFromParentEvent) -> Self {
+        Self {
+            slot_index: event.index_of_class,
+            id: event.id,
+            kind: ReceiveMessageKind::Parent,
+            value: ReceiveMessageValue::Integer(event.value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::{MessageType, ReceiveMessageValue};
+
+    fn trigger() -> TriggerEvent {
+        TriggerEvent {
+            id: "door_sensor".into(),
+            sensor_index: 2,
+        }
+    }
+
+    fn convert<T: MessageType>(event: Event) -> T {
+        let message = event.into_message().unwrap();
+        assert_eq!(message.meta(), &T::MESSAGE_META);
+        message.value().unwrap()
+    }
+
+    #[test]
+    fn test_trigger_events() {
+        let enter = convert::<message::TriggerEvent>(Event::EnterTrigger(trigger()));
+        assert_eq!(enter.id, "door_sensor");
+        assert_eq!(enter.sensor_index, 2);
+        assert_eq!(enter.kind, TriggerKind::Enter);
+
+        let leave = convert::<message::TriggerEvent>(Event::LeaveTrigger(trigger()));
+        assert_eq!(leave.sensor_index, 2);
+        assert!(leave.is_leave());
+    }
+
+    #[test]
+    fn test_input_events() {
+        let button = convert::<ButtonEvent>(Event::Button(ButtonEvent {
+            id: "horn".into(),
+            value: true,
+            cockpit_index: 1,
+        }));
+        assert_eq!(button.id, "horn");
+        assert!(button.value);
+        assert_eq!(button.cockpit_index, 1);
+
+        let input = convert::<FloatInputEvent>(Event::FloatInput(FloatInputEvent {
+            id: "throttle".into(),
+            value: 0.75,
+            cockpit_index: 0,
+        }));
+        assert_eq!(input.id, "throttle");
+        assert_eq!(input.value, 0.75);
+    }
+
+    #[test]
+    fn test_vehicle_events() {
+        let broadcast = convert::<BroadcastEvent>(Event::Broadcast(BroadcastEvent {
+            bus_id: "ibis".into(),
+            id: "line".into(),
+            value: BroadcastValue::Integer(5),
+        }));
+        assert_eq!(broadcast.bus_id, "ibis");
+        assert!(matches!(broadcast.value, BroadcastValue::Integer(5)));
+
+        let entered = convert::<VehicleEnteredEvent>(Event::VehicleEntered(VehicleEnteredEvent {
+            id: "player".into(),
+            speed_mps: 3.5,
+        }));
+        assert_eq!(entered.speed_mps, 3.5);
+
+        assert!(Event::Empty.into_message().is_none());
+        assert!(Option::<Message>::from(Event::Empty).is_none());
+    }
+
+    #[test]
+    fn test_receive_message_events() {
+        let child = ReceiveMessageEvent::from(ReceiveMessageFromChildEvent {
+            slot_index: 3,
+            id: "pantograph".into(),
+            value: 1,
+        });
+        let child = convert::<ReceiveMessageEvent>(Event::ReceiveMessage(child));
+        assert_eq!(child.slot_index, 3);
+        assert_eq!(child.id, "pantograph");
+        assert_eq!(child.kind, ReceiveMessageKind::Child);
+        assert!(matches!(child.value, ReceiveMessageValue::Integer(1)));
+
+        let parent = ReceiveMessageEvent::from(ReceiveMessageFromParentEvent {
+            index_of_class: 4,
+            id: "lights".into(),
+            value: -2,
+        });
+        let parent = Option::<Message>::from(Event::ReceiveMessage(parent)).unwrap();
+        assert_eq!(parent.meta(), &ReceiveMessageEvent::MESSAGE_META);
+        let parent = parent.value::<ReceiveMessageEvent>().unwrap();
+        assert_eq!(parent.slot_index, 4);
+        assert_eq!(parent.kind, ReceiveMessageKind::Parent);
+        assert!(matches!(parent.value, ReceiveMessageValue::Integer(-2)));
+    }
+}
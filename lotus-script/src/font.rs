@@ -1,6 +1,12 @@
 use lotus_script_sys::FfiObject;
-use lotus_shared::content::ContentId;
 pub use lotus_shared::font::*;
+use lotus_shared::{content::ContentId, math::Rectangle};
+
+use crate::graphics::{
+    textures::{AlphaMode, Texture},
+    Color,
+};
+use layout::{TextLayout, TextLayoutOptions};
 
 /// A bitmap font that can be used to render text.
 pub struct BitmapFont {
@@ -27,11 +33,44 @@ impl BitmapFont {
         }
     }
 
+    #[cfg(test)]
+    pub(crate) fn from_properties(content_id: ContentId, properties: BitmapFontProperties) -> Self {
+        Self {
+            content_id,
+            properties,
+        }
+    }
+
+    /// Get the content id of this font.
+    pub fn content_id(&self) -> ContentId {
+        self.content_id
+    }
+
     /// Get the properties of this font.
     pub fn properties(&self) -> &BitmapFontProperties {
         &self.properties
     }
 
+    /// Lay out the text in the rectangle. This is measured locally, without calling the engine.
+    pub fn layout(&self, text: &str, rect: Rectangle, options: &TextLayoutOptions) -> TextLayout {
+        self.properties.layout(text, rect, options)
+    }
+
+    /// Lay out the text in the rectangle and draw it on the texture, clipped to the rectangle.
+    pub fn draw_text_in(
+        &self,
+        texture: &mut Texture,
+        text: &str,
+        rect: Rectangle,
+        options: &TextLayoutOptions,
+        full_color: Option<Color>,
+        alpha_mode: AlphaMode,
+    ) -> TextLayout {
+        let layout = self.layout(text, rect, options);
+        texture.draw_text_layout(self.content_id, &layout, full_color, alpha_mode);
+        layout
+    }
+
     /// Get the width of the text in pixels.
     pub fn text_len(&self, text: &str, letter_spacing: i32) -> u32 {
         let font = FfiObject::new(&self.content_id);
@@ -0,0 +1,298 @@
+use lotus_shared::{
+    graphics::textures::{DrawPixel, FillMode, ShapeOptions, TextureAction},
+    math::{UVec2, Vec2},
+};
+
+use super::{BitMatrix, DisplayStyle, Failures};
+use crate::graphics::{textures::Texture, Color};
+
+/// The technology of a [DotMatrixDisplay].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DotKind {
+    /// Round LEDs that change immediately.
+    Led,
+    /// Square flip-dots that change column by column. Flip-dots don't glow.
+    FlipDot,
+}
+
+/// A board of LEDs or flip-dots, e.g. a destination display.
+///
+/// # Example
+/// ```no_run
+/// # use lotus_script::graphics::{displays::*, textures::Texture, Color};
+/// # fn example(texture: &mut Texture) {
+/// let mut board = DotMatrixDisplay::new(DotKind::FlipDot, (112, 16), (0.0, 0.0), 4.0)
+///     .with_style(DisplayStyle::new(Color::YELLOW).with_off(Color::rgb(20, 20, 20)));
+///
+/// board.matrix_mut().blit(&BitMatrix::from_rows(&["#.#", ".#.", "#.#"]), 2, 2);
+///
+/// // In `tick`:
+/// board.tick();
+/// board.draw(texture);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct DotMatrixDisplay {
+    kind: DotKind,
+    origin: Vec2,
+    pitch: f32,
+    dot_size: f32,
+    style: DisplayStyle,
+    flip_speed: f32,
+    failures: Failures,
+    target: BitMatrix,
+    shown: BitMatrix,
+    flip_column: u32,
+    flip_progress: f32,
+}
+
+impl DotMatrixDisplay {
+    /// Creates a display of `size` dots with its top left corner at `origin`. `pitch` is the
+    /// distance between the centers of neighboring dots in pixels.
+    pub fn new(kind: DotKind, size: impl Into<UVec2>, origin: impl Into<Vec2>, pitch: f32) -> Self {
+        let size = size.into();
+
+        Self {
+            kind,
+            origin: origin.into(),
+            pitch,
+            dot_size: pitch * 0.8,
+            style: DisplayStyle::new(Color::rgb(255, 160, 0)),
+            flip_speed: 60.0,
+            failures: Failures::default(),
+            target: BitMatrix::new(size.x, size.y),
+            shown: BitMatrix::new(size.x, size.y),
+            flip_column: 0,
+            flip_progress: 0.0,
+        }
+    }
+
+    pub fn with_style(mut self, style: DisplayStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Sets the diameter of the dots in pixels. Defaults to 80% of the pitch.
+    pub fn with_dot_size(mut self, dot_size: f32) -> Self {
+        self.dot_size = dot_size;
+        self
+    }
+
+    /// Sets how many columns of a flip-dot display change per second. Defaults to 60.
+    pub fn with_flip_speed(mut self, columns_per_second: f32) -> Self {
+        self.flip_speed = columns_per_second;
+        self
+    }
+
+    pub fn kind(&self) -> DotKind {
+        self.kind
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.target.size()
+    }
+
+    pub fn set_style(&mut self, style: DisplayStyle) {
+        self.style = style;
+    }
+
+    /// Returns the defects of the display. The dot at `(x, y)` has the index `y * width + x`.
+    pub fn failures(&self) -> &Failures {
+        &self.failures
+    }
+
+    /// Returns the defects of the display for modification. See [DotMatrixDisplay::failures].
+    pub fn failures_mut(&mut self) -> &mut Failures {
+        &mut self.failures
+    }
+
+    /// Randomly marks dots as defect. See [Failures::randomize].
+    pub fn randomize_failures(&mut self, rate: f64, stuck_on: f64) {
+        let size = self.size();
+        self.failures
+            .randomize(size.x as usize * size.y as usize, rate, stuck_on);
+    }
+
+    /// Returns the content the display shows or changes to.
+    pub fn matrix(&self) -> &BitMatrix {
+        &self.target
+    }
+
+    /// Returns the content the display shows or changes to for modification.
+    pub fn matrix_mut(&mut self) -> &mut BitMatrix {
+        &mut self.target
+    }
+
+    /// Returns the content that is currently visible. Differs from [DotMatrixDisplay::matrix]
+    /// while a flip-dot display is changing.
+    pub fn shown(&self) -> &BitMatrix {
+        &self.shown
+    }
+
+    /// Replaces the content of the display. The matrix is cut off or padded to the size of
+    /// the display.
+    pub fn set_matrix(&mut self, matrix: &BitMatrix) {
+        self.target.fill(false);
+        self.target.blit(matrix, 0, 0);
+    }
+
+    /// Sets a single dot.
+    pub fn set(&mut self, x: u32, y: u32, on: bool) {
+        self.target.set(x, y, on);
+    }
+
+    /// Returns `true` while a flip-dot display is changing.
+    pub fn is_flipping(&self) -> bool {
+        self.kind == DotKind::FlipDot && self.shown != self.target
+    }
+
+    /// Advances the display by [time::delta](crate::time::delta).
+    pub fn tick(&mut self) {
+        self.update(crate::time::delta());
+    }
+
+    /// Advances the display by the given number of seconds.
+    pub fn update(&mut self, delta: f32) {
+        if self.kind == DotKind::Led || self.flip_speed <= 0.0 {
+            self.shown.clone_from(&self.target);
+            return;
+        }
+
+        if self.shown == self.target {
+            self.flip_column = 0;
+            self.flip_progress = 0.0;
+            return;
+        }
+
+        self.flip_progress += delta * self.flip_speed;
+
+        while self.flip_progress >= 1.0 && self.shown != self.target {
+            self.flip_progress -= 1.0;
+
+            let x = self.flip_column;
+            for y in 0..self.target.height() {
+                self.shown.set(x, y, self.target.get(x, y));
+            }
+
+            self.flip_column = (x + 1) % self.target.width().max(1);
+        }
+    }
+
+    /// Draws the visible content onto the texture, submitting all dots at once.
+    pub fn draw(&self, texture: &mut Texture) {
+        let actions = self.actions(texture.shape_options());
+        texture.add_actions(actions);
+    }
+
+    /// Returns the actions that draw the visible content, see [DotMatrixDisplay::draw].
+    pub fn actions(&self, options: ShapeOptions) -> Vec<TextureAction> {
+        let dots = self
+            .shown
+            .iter()
+            .enumerate()
+            .map(|(index, (pos, on))| (pos, self.failures.apply(index, on)))
+            .collect::<Vec<_>>();
+
+        if self.dot_size <= 1.0 {
+            let pixels = dots
+                .iter()
+                .filter_map(|(pos, on)| {
+                    let color = if *on {
+                        Some(self.style.on)
+                    } else {
+                        self.style.off
+                    }?;
+                    Some(DrawPixel::from((self.center(*pos).as_uvec2(), color)))
+                })
+                .collect::<Box<[_]>>();
+            return vec![TextureAction::DrawPixels(pixels)];
+        }
+
+        let mut actions = Vec::new();
+
+        if let Some(off) = self.style.off {
+            for (pos, _) in dots.iter().filter(|(_, on)| !on) {
+                actions.push(self.dot(*pos, 0.0, off, options));
+            }
+        }
+
+        if let (DotKind::Led, Some(glow)) = (self.kind, self.style.glow) {
+            for (pos, _) in dots.iter().filter(|(_, on)| *on) {
+                actions.push(self.dot(*pos, glow.radius, glow.color, options));
+            }
+        }
+
+        for (pos, _) in dots.iter().filter(|(_, on)| *on) {
+            actions.push(self.dot(*pos, 0.0, self.style.on, options));
+        }
+
+        actions
+    }
+
+    fn center(&self, pos: UVec2) -> Vec2 {
+        self.origin + (pos.as_vec2() + 0.5) * self.pitch
+    }
+
+    fn dot(&self, pos: UVec2, grow: f32, color: Color, options: ShapeOptions) -> TextureAction {
+        let center = self.center(pos);
+        let radius = self.dot_size / 2.0 + grow;
+
+        match self.kind {
+            DotKind::Led => TextureAction::DrawCircle {
+                center,
+                radius,
+                fill: FillMode::Fill,
+                color,
+                options,
+            },
+            DotKind::FlipDot => TextureAction::DrawRect {
+                start: (center - radius).round().max(Vec2::ZERO).as_uvec2(),
+                end: (center + radius).round().max(Vec2::ZERO).as_uvec2(),
+                color,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::displays::Failure;
+
+    #[test]
+    fn test_flip_dots_change_column_by_column() {
+        let mut board =
+            DotMatrixDisplay::new(DotKind::FlipDot, (3, 2), (0.0, 0.0), 4.0).with_flip_speed(10.0);
+        board.matrix_mut().fill(true);
+
+        board.update(0.25);
+        assert!(board.is_flipping());
+        assert_eq!(board.shown(), &BitMatrix::from_rows(&["##.", "##."]));
+
+        board.update(0.1);
+        assert!(!board.is_flipping());
+        assert_eq!(board.shown(), board.matrix());
+
+        let mut led = DotMatrixDisplay::new(DotKind::Led, (3, 2), (0.0, 0.0), 4.0);
+        led.set(1, 1, true);
+        led.update(0.0);
+        assert_eq!(led.shown(), led.matrix());
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut board = DotMatrixDisplay::new(DotKind::Led, (4, 2), (0.0, 0.0), 4.0)
+            .with_style(DisplayStyle::new(Color::YELLOW).with_glow(1.0, 64));
+        board.set(0, 0, true);
+        board.failures_mut().set(7, Some(Failure::StuckOn));
+        board.update(0.0);
+
+        // Two lit dots with glow, unlit dots aren't drawn.
+        let actions = board.actions(ShapeOptions::default());
+        assert_eq!(actions.len(), 4);
+
+        let board = board.with_dot_size(1.0);
+        let actions = board.actions(ShapeOptions::default());
+        assert!(matches!(&actions[..], [TextureAction::DrawPixels(pixels)] if pixels.len() == 2));
+    }
+}
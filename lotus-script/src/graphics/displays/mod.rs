@@ -0,0 +1,255 @@
+//! Emulators for segment, LED matrix and flip-dot displays.
+//!
+//! The displays take logical input, like a string for a [SegmentDisplay] or a [BitMatrix] for a
+//! [DotMatrixDisplay], and draw it onto a [Texture](super::textures::Texture) with configurable
+//! geometry, colors, glow and failing segments or dots.
+
+use std::collections::HashMap;
+
+use lotus_shared::math::UVec2;
+
+use super::Color;
+
+mod matrix;
+mod segment;
+
+pub use matrix::*;
+pub use segment::*;
+
+/// Glow around lit segments or dots.
+#[derive(Debug, Clone, Copy)]
+pub struct Glow {
+    /// The color of the glow, usually the on color with a low alpha.
+    pub color: Color,
+    /// How far the glow extends beyond the segment or dot in pixels.
+    pub radius: f32,
+}
+
+/// The colors of a display.
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayStyle {
+    /// The color of lit segments or dots.
+    pub on: Color,
+    /// The color of unlit segments or dots. `None` to not draw them.
+    pub off: Option<Color>,
+    pub glow: Option<Glow>,
+}
+
+impl DisplayStyle {
+    /// A style with the given on color that doesn't draw unlit segments or dots.
+    pub fn new(on: Color) -> Self {
+        Self {
+            on,
+            off: None,
+            glow: None,
+        }
+    }
+
+    pub fn with_off(mut self, off: Color) -> Self {
+        self.off = Some(off);
+        self
+    }
+
+    /// Adds a glow in the on color with the given alpha.
+    pub fn with_glow(mut self, radius: f32, alpha: u8) -> Self {
+        let Color { r, g, b, .. } = self.on;
+        self.glow = Some(Glow {
+            color: Color::rgba(r, g, b, alpha),
+            radius,
+        });
+        self
+    }
+}
+
+/// A simulated defect of a segment or dot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Failure {
+    /// The segment or dot never lights up.
+    StuckOff,
+    /// The segment or dot is always lit.
+    StuckOn,
+}
+
+/// The defects of a display, by segment or dot index.
+#[derive(Debug, Clone, Default)]
+pub struct Failures(HashMap<usize, Failure>);
+
+impl Failures {
+    /// Sets or removes the defect of the given segment or dot.
+    pub fn set(&mut self, index: usize, failure: Option<Failure>) {
+        match failure {
+            Some(failure) => self.0.insert(index, failure),
+            None => self.0.remove(&index),
+        };
+    }
+
+    /// Returns the defect of the given segment or dot.
+    pub fn get(&self, index: usize) -> Option<Failure> {
+        self.0.get(&index).copied()
+    }
+
+    /// Removes all defects.
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Randomly marks segments or dots out of `count` as defect with the given probability,
+    /// using [crate::rand]. `stuck_on` is the share of defects that are stuck on.
+    pub fn randomize(&mut self, count: usize, rate: f64, stuck_on: f64) {
+        for index in 0..count {
+            if crate::rand::gen_f64() < rate {
+                let failure = if crate::rand::gen_f64() < stuck_on {
+                    Failure::StuckOn
+                } else {
+                    Failure::StuckOff
+                };
+                self.0.insert(index, failure);
+            }
+        }
+    }
+
+    /// Returns whether the segment or dot is lit, taking its defect into account.
+    pub fn apply(&self, index: usize, on: bool) -> bool {
+        match self.get(index) {
+            Some(Failure::StuckOff) => false,
+            Some(Failure::StuckOn) => true,
+            None => on,
+        }
+    }
+}
+
+/// A grid of on/off values, e.g. the content of an LED board.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitMatrix {
+    width: u32,
+    height: u32,
+    bits: Vec<bool>,
+}
+
+impl BitMatrix {
+    /// Creates a matrix with all bits off.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            bits: vec![false; width as usize * height as usize],
+        }
+    }
+
+    /// Creates a matrix from rows of text, where every character other than ` ` and `.` is on.
+    /// The matrix is as wide as the longest row.
+    pub fn from_rows(rows: &[&str]) -> Self {
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let mut matrix = Self::new(width as u32, rows.len() as u32);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                matrix.set(x as u32, y as u32, c != ' ' && c != '.');
+            }
+        }
+
+        matrix
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn size(&self) -> UVec2 {
+        UVec2::new(self.width, self.height)
+    }
+
+    fn index(&self, x: u32, y: u32) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| (y * self.width + x) as usize)
+    }
+
+    /// Returns the bit at the given position, `false` if it is out of bounds.
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        self.index(x, y).is_some_and(|index| self.bits[index])
+    }
+
+    /// Sets the bit at the given position. Does nothing if it is out of bounds.
+    pub fn set(&mut self, x: u32, y: u32, on: bool) {
+        if let Some(index) = self.index(x, y) {
+            self.bits[index] = on;
+        }
+    }
+
+    /// Sets all bits.
+    pub fn fill(&mut self, on: bool) {
+        self.bits.fill(on);
+    }
+
+    /// Copies the other matrix into this one with its top left corner at the given position.
+    /// Bits outside of this matrix are ignored.
+    pub fn blit(&mut self, other: &BitMatrix, x: i32, y: i32) {
+        for oy in 0..other.height {
+            for ox in 0..other.width {
+                let (tx, ty) = (x + ox as i32, y + oy as i32);
+                if tx >= 0 && ty >= 0 {
+                    self.set(tx as u32, ty as u32, other.get(ox, oy));
+                }
+            }
+        }
+    }
+
+    /// Returns an iterator over all positions and bits, row by row.
+    pub fn iter(&self) -> impl Iterator<Item = (UVec2, bool)> + '_ {
+        self.bits.iter().enumerate().map(|(index, on)| {
+            let index = index as u32;
+            (UVec2::new(index % self.width, index / self.width), *on)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_matrix() {
+        let glyph = BitMatrix::from_rows(&["#.#", ".#", "#.#"]);
+        assert_eq!(glyph.size(), UVec2::new(3, 3));
+        assert!(glyph.get(0, 0) && !glyph.get(1, 0) && glyph.get(1, 1));
+        assert!(!glyph.get(2, 1));
+        assert!(!glyph.get(3, 0));
+
+        let mut board = BitMatrix::new(4, 2);
+        board.blit(&glyph, 2, -1);
+        assert_eq!(
+            board
+                .iter()
+                .filter(|(_, on)| *on)
+                .map(|(pos, _)| pos)
+                .collect::<Vec<_>>(),
+            [UVec2::new(3, 0), UVec2::new(2, 1)]
+        );
+
+        board.set(10, 10, true);
+        board.fill(true);
+        assert!(board.iter().all(|(_, on)| on));
+    }
+
+    #[test]
+    fn test_failures() {
+        let mut failures = Failures::default();
+        failures.set(1, Some(Failure::StuckOff));
+        failures.set(2, Some(Failure::StuckOn));
+
+        assert!(failures.apply(0, true));
+        assert!(!failures.apply(1, true));
+        assert!(failures.apply(2, false));
+
+        failures.set(2, None);
+        assert!(!failures.apply(2, false));
+        assert_eq!(failures.get(1), Some(Failure::StuckOff));
+    }
+}
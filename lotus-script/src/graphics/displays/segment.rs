@@ -0,0 +1,582 @@
+use std::collections::HashMap;
+
+use lotus_shared::{
+    graphics::textures::{FillMode, ShapeOptions, TextureAction},
+    math::Vec2,
+};
+
+use super::{DisplayStyle, Failures};
+use crate::graphics::{textures::Texture, Color};
+
+/// The segment layout of a digit.
+///
+/// The segments of a digit are stored as a bit mask, in the order listed for each kind.
+/// The decimal point is always the last segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SegmentKind {
+    /// `A B C D E F G DP`, starting at the top and going clockwise, `G` being the middle bar.
+    Seven,
+    /// `A B C D E F G1 G2 H J K L M N DP`. `G1` and `G2` are the left and right middle bars,
+    /// `H J K` the upper left diagonal, upper vertical and upper right diagonal and `L M N` the
+    /// lower left diagonal, lower vertical and lower right diagonal.
+    Fourteen,
+    /// `A1 A2 B C D1 D2 E F G1 G2 H J K L M N DP`, like [SegmentKind::Fourteen] with split top and
+    /// bottom bars.
+    Sixteen,
+}
+
+// Segments as lines in a unit square, (0, 0) being the top left corner.
+const TL: Vec2 = Vec2::new(0.0, 0.0);
+const TM: Vec2 = Vec2::new(0.5, 0.0);
+const TR: Vec2 = Vec2::new(1.0, 0.0);
+const ML: Vec2 = Vec2::new(0.0, 0.5);
+const MM: Vec2 = Vec2::new(0.5, 0.5);
+const MR: Vec2 = Vec2::new(1.0, 0.5);
+const BL: Vec2 = Vec2::new(0.0, 1.0);
+const BM: Vec2 = Vec2::new(0.5, 1.0);
+const BR: Vec2 = Vec2::new(1.0, 1.0);
+
+const SEVEN: &[(Vec2, Vec2)] = &[
+    (TL, TR),
+    (TR, MR),
+    (MR, BR),
+    (BL, BR),
+    (ML, BL),
+    (TL, ML),
+    (ML, MR),
+];
+
+const FOURTEEN: &[(Vec2, Vec2)] = &[
+    (TL, TR),
+    (TR, MR),
+    (MR, BR),
+    (BL, BR),
+    (ML, BL),
+    (TL, ML),
+    (ML, MM),
+    (MM, MR),
+    (TL, MM),
+    (TM, MM),
+    (TR, MM),
+    (BL, MM),
+    (MM, BM),
+    (BR, MM),
+];
+
+const SIXTEEN: &[(Vec2, Vec2)] = &[
+    (TL, TM),
+    (TM, TR),
+    (TR, MR),
+    (MR, BR),
+    (BL, BM),
+    (BM, BR),
+    (ML, BL),
+    (TL, ML),
+    (ML, MM),
+    (MM, MR),
+    (TL, MM),
+    (TM, MM),
+    (TR, MM),
+    (BL, MM),
+    (MM, BM),
+    (BR, MM),
+];
+
+impl SegmentKind {
+    fn lines(self) -> &'static [(Vec2, Vec2)] {
+        match self {
+            Self::Seven => SEVEN,
+            Self::Fourteen => FOURTEEN,
+            Self::Sixteen => SIXTEEN,
+        }
+    }
+
+    /// Returns the number of segments of a digit, including the decimal point.
+    pub fn segment_count(self) -> usize {
+        self.lines().len() + 1
+    }
+
+    /// Returns the bit of the decimal point.
+    pub fn decimal_point(self) -> u32 {
+        1 << self.lines().len()
+    }
+
+    /// Returns the built-in segments of a character, `None` if it can't be displayed.
+    /// Letters are shown in whatever case the segments allow.
+    pub fn glyph(self, c: char) -> Option<u32> {
+        match self {
+            Self::Seven => glyph_seven(c)
+                .or_else(|| glyph_seven(c.to_ascii_uppercase()))
+                .or_else(|| glyph_seven(c.to_ascii_lowercase())),
+            Self::Fourteen => glyph_fourteen(c.to_ascii_uppercase()),
+            Self::Sixteen => glyph_fourteen(c.to_ascii_uppercase()).map(|mask| {
+                // Split A and D, shift everything else to make room for A2 and D2.
+                let a = if mask & 1 != 0 { 0b11 } else { 0 };
+                let bc = (mask & 0b110) << 1;
+                let d = if mask & (1 << 3) != 0 { 0b11 << 4 } else { 0 };
+                let rest = (mask & !0b1111) << 2;
+                a | bc | d | rest
+            }),
+        }
+    }
+}
+
+fn glyph_seven(c: char) -> Option<u32> {
+    Some(match c {
+        '0' | 'O' => 0x3f,
+        '1' => 0x06,
+        '2' => 0x5b,
+        '3' => 0x4f,
+        '4' => 0x66,
+        '5' | 'S' => 0x6d,
+        '6' => 0x7d,
+        '7' => 0x07,
+        '8' => 0x7f,
+        '9' => 0x6f,
+        'A' => 0x77,
+        'b' => 0x7c,
+        'C' | '[' => 0x39,
+        'c' => 0x58,
+        'd' => 0x5e,
+        'E' => 0x79,
+        'F' => 0x71,
+        'G' => 0x3d,
+        'H' => 0x76,
+        'h' => 0x74,
+        'I' => 0x30,
+        'J' => 0x1e,
+        'L' => 0x38,
+        'n' => 0x54,
+        'o' => 0x5c,
+        'P' => 0x73,
+        'q' => 0x67,
+        'r' => 0x50,
+        't' => 0x78,
+        'U' => 0x3e,
+        'u' => 0x1c,
+        'y' => 0x6e,
+        ']' => 0x0f,
+        '-' => 0x40,
+        '_' => 0x08,
+        '=' => 0x48,
+        '"' => 0x22,
+        '\'' => 0x20,
+        '°' => 0x63,
+        ' ' => 0,
+        _ => return None,
+    })
+}
+
+fn glyph_fourteen(c: char) -> Option<u32> {
+    const A: u32 = 1 << 0;
+    const B: u32 = 1 << 1;
+    const C: u32 = 1 << 2;
+    const D: u32 = 1 << 3;
+    const E: u32 = 1 << 4;
+    const F: u32 = 1 << 5;
+    const G1: u32 = 1 << 6;
+    const G2: u32 = 1 << 7;
+    const H: u32 = 1 << 8;
+    const J: u32 = 1 << 9;
+    const K: u32 = 1 << 10;
+    const L: u32 = 1 << 11;
+    const M: u32 = 1 << 12;
+    const N: u32 = 1 << 13;
+
+    Some(match c {
+        '0' => A | B | C | D | E | F | K | L,
+        '1' => B | C | K,
+        '2' => A | B | D | E | G1 | G2,
+        '3' => A | B | C | D | G2,
+        '4' => B | C | F | G1 | G2,
+        '5' => A | C | D | F | G1 | G2,
+        '6' => A | C | D | E | F | G1 | G2,
+        '7' => A | B | C,
+        '8' => A | B | C | D | E | F | G1 | G2,
+        '9' => A | B | C | D | F | G1 | G2,
+        'A' => A | B | C | E | F | G1 | G2,
+        'B' => A | B | C | D | G2 | J | M,
+        'C' | '[' => A | D | E | F,
+        'D' => A | B | C | D | J | M,
+        'E' => A | D | E | F | G1,
+        'F' => A | E | F | G1,
+        'G' => A | C | D | E | F | G2,
+        'H' => B | C | E | F | G1 | G2,
+        'I' => A | D | J | M,
+        'J' => B | C | D | E,
+        'K' => E | F | G1 | K | N,
+        'L' => D | E | F,
+        'M' => B | C | E | F | H | K,
+        'N' => B | C | E | F | H | N,
+        'O' => A | B | C | D | E | F,
+        'P' => A | B | E | F | G1 | G2,
+        'Q' => A | B | C | D | E | F | N,
+        'R' => A | B | E | F | G1 | G2 | N,
+        'S' => A | C | D | F | G1 | G2,
+        'T' => A | J | M,
+        'U' => B | C | D | E | F,
+        'V' => E | F | K | L,
+        'W' => B | C | E | F | L | N,
+        'X' => H | K | L | N,
+        'Y' => H | K | M,
+        'Z' => A | D | K | L,
+        ']' => A | B | C | D,
+        '-' => G1 | G2,
+        '+' => G1 | G2 | J | M,
+        '*' => G1 | G2 | H | J | K | L | M | N,
+        '/' => K | L,
+        '\\' => H | N,
+        '<' | '(' => K | N,
+        '>' | ')' => H | L,
+        '_' => D,
+        '=' => D | G1 | G2,
+        '"' => F | J,
+        '\'' => J,
+        '°' => A | B | F | G1 | G2,
+        ' ' => 0,
+        _ => return None,
+    })
+}
+
+/// The size and shape of the digits of a [SegmentDisplay], in pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentGeometry {
+    /// The size of a digit, without the decimal point.
+    pub digit_size: Vec2,
+    /// The width of the segments.
+    pub thickness: f32,
+    /// The horizontal space between digits. The decimal point is drawn in this space.
+    pub digit_spacing: f32,
+    /// How far the top of a digit is shifted to the right, relative to its height.
+    /// `0.1` is a typical italic slant.
+    pub slant: f32,
+    /// The space between neighboring segments.
+    pub gap: f32,
+}
+
+impl SegmentGeometry {
+    /// Creates a geometry with proportions typical for LED and LCD digits.
+    pub fn new(digit_size: impl Into<Vec2>) -> Self {
+        let digit_size = digit_size.into();
+        let thickness = (digit_size.x * 0.15).max(1.0);
+
+        Self {
+            digit_size,
+            thickness,
+            digit_spacing: thickness * 2.0,
+            slant: 0.0,
+            gap: thickness * 0.2,
+        }
+    }
+
+    pub fn with_thickness(mut self, thickness: f32) -> Self {
+        self.thickness = thickness;
+        self
+    }
+
+    pub fn with_digit_spacing(mut self, digit_spacing: f32) -> Self {
+        self.digit_spacing = digit_spacing;
+        self
+    }
+
+    pub fn with_slant(mut self, slant: f32) -> Self {
+        self.slant = slant;
+        self
+    }
+
+    pub fn with_gap(mut self, gap: f32) -> Self {
+        self.gap = gap;
+        self
+    }
+
+    /// Converts a point in the unit square of a digit to a pixel.
+    fn point(&self, digit_origin: Vec2, p: Vec2) -> Vec2 {
+        let inset = self.thickness / 2.0;
+        let inner = (self.digit_size - self.thickness).max(Vec2::ZERO);
+        let pos = Vec2::splat(inset) + p * inner;
+
+        digit_origin + Vec2::new(pos.x + self.slant * (inner.y - pos.y + inset), pos.y)
+    }
+}
+
+/// A row of seven, fourteen or sixteen segment digits, e.g. of a clock or a destination display.
+///
+/// # Example
+/// ```no_run
+/// # use lotus_script::graphics::{displays::*, textures::Texture, Color};
+/// # fn example(texture: &mut Texture) {
+/// let mut clock = SegmentDisplay::new(SegmentKind::Seven, 4, (4.0, 4.0), SegmentGeometry::new((20.0, 36.0)))
+///     .with_style(DisplayStyle::new(Color::RED).with_off(Color::rgb(40, 0, 0)).with_glow(2.0, 64));
+///
+/// clock.set_text("12.45");
+/// clock.draw(texture);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SegmentDisplay {
+    kind: SegmentKind,
+    origin: Vec2,
+    geometry: SegmentGeometry,
+    style: DisplayStyle,
+    glyphs: HashMap<char, u32>,
+    failures: Failures,
+    masks: Vec<u32>,
+}
+
+impl SegmentDisplay {
+    /// Creates a display with the given number of digits, its top left corner at `origin`.
+    pub fn new(
+        kind: SegmentKind,
+        digits: usize,
+        origin: impl Into<Vec2>,
+        geometry: SegmentGeometry,
+    ) -> Self {
+        Self {
+            kind,
+            origin: origin.into(),
+            geometry,
+            style: DisplayStyle::new(Color::RED),
+            glyphs: HashMap::new(),
+            failures: Failures::default(),
+            masks: vec![0; digits],
+        }
+    }
+
+    pub fn with_style(mut self, style: DisplayStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Overrides the segments of a character, or adds one that has no built-in segments.
+    pub fn with_glyph(mut self, c: char, mask: u32) -> Self {
+        self.glyphs.insert(c, mask);
+        self
+    }
+
+    pub fn kind(&self) -> SegmentKind {
+        self.kind
+    }
+
+    pub fn digits(&self) -> usize {
+        self.masks.len()
+    }
+
+    pub fn set_style(&mut self, style: DisplayStyle) {
+        self.style = style;
+    }
+
+    pub fn set_geometry(&mut self, geometry: SegmentGeometry) {
+        self.geometry = geometry;
+    }
+
+    /// Returns the defects of the display. Segment `s` of digit `d` has the index
+    /// `d * kind.segment_count() + s`.
+    pub fn failures(&self) -> &Failures {
+        &self.failures
+    }
+
+    /// Returns the defects of the display for modification. See [SegmentDisplay::failures].
+    pub fn failures_mut(&mut self) -> &mut Failures {
+        &mut self.failures
+    }
+
+    /// Randomly marks segments as defect. See [Failures::randomize].
+    pub fn randomize_failures(&mut self, rate: f64, stuck_on: f64) {
+        let count = self.digits() * self.kind.segment_count();
+        self.failures.randomize(count, rate, stuck_on);
+    }
+
+    /// Returns the segments of a character, `None` if it can't be displayed.
+    pub fn glyph(&self, c: char) -> Option<u32> {
+        self.glyphs.get(&c).copied().or_else(|| self.kind.glyph(c))
+    }
+
+    /// Shows the text starting at the first digit. A `.` lights the decimal point of the
+    /// previous digit instead of taking a digit of its own. Characters that can't be displayed
+    /// are left blank and text that doesn't fit is cut off.
+    pub fn set_text(&mut self, text: &str) {
+        let dp = self.kind.decimal_point();
+        let mut masks = Vec::with_capacity(self.masks.len());
+
+        for c in text.chars() {
+            match (c, masks.last_mut()) {
+                ('.' | ',', Some(last)) if *last & dp == 0 => *last |= dp,
+                ('.' | ',', _) => masks.push(dp),
+                _ => masks.push(self.glyph(c).unwrap_or(0)),
+            }
+        }
+
+        masks.resize(self.masks.len(), 0);
+        self.masks = masks;
+    }
+
+    /// Sets the segments of a digit directly. Does nothing if the digit doesn't exist.
+    pub fn set_raw(&mut self, digit: usize, mask: u32) {
+        if let Some(m) = self.masks.get_mut(digit) {
+            *m = mask;
+        }
+    }
+
+    /// Returns the segments of all digits.
+    pub fn masks(&self) -> &[u32] {
+        &self.masks
+    }
+
+    /// Draws the display onto the texture, submitting all segments at once.
+    pub fn draw(&self, texture: &mut Texture) {
+        let actions = self.actions(texture.shape_options());
+        texture.add_actions(actions);
+    }
+
+    /// Returns the actions that draw the display, see [SegmentDisplay::draw].
+    pub fn actions(&self, options: ShapeOptions) -> Vec<TextureAction> {
+        let segment_count = self.kind.segment_count();
+        let mut lit = Vec::with_capacity(self.masks.len() * segment_count);
+
+        for (digit, mask) in self.masks.iter().enumerate() {
+            for segment in 0..segment_count {
+                let index = digit * segment_count + segment;
+                let on = mask & (1 << segment) != 0;
+                lit.push((digit, segment, self.failures.apply(index, on)));
+            }
+        }
+
+        let mut actions = Vec::new();
+
+        if let Some(off) = self.style.off {
+            for (digit, segment, _) in lit.iter().filter(|(_, _, on)| !on) {
+                actions.push(self.segment(*digit, *segment, 0.0, off, options));
+            }
+        }
+
+        if let Some(glow) = self.style.glow {
+            for (digit, segment, _) in lit.iter().filter(|(_, _, on)| *on) {
+                actions.push(self.segment(*digit, *segment, glow.radius, glow.color, options));
+            }
+        }
+
+        for (digit, segment, _) in lit.iter().filter(|(_, _, on)| *on) {
+            actions.push(self.segment(*digit, *segment, 0.0, self.style.on, options));
+        }
+
+        actions
+    }
+
+    fn segment(
+        &self,
+        digit: usize,
+        segment: usize,
+        grow: f32,
+        color: Color,
+        options: ShapeOptions,
+    ) -> TextureAction {
+        let g = &self.geometry;
+        let digit_origin =
+            self.origin + Vec2::new(digit as f32 * (g.digit_size.x + g.digit_spacing), 0.0);
+
+        let Some((start, end)) = self.kind.lines().get(segment) else {
+            // The decimal point, in the spacing right of the digit.
+            let center = digit_origin
+                + Vec2::new(
+                    g.digit_size.x + g.thickness * 0.75,
+                    g.digit_size.y - g.thickness / 2.0,
+                );
+            return TextureAction::DrawCircle {
+                center,
+                radius: g.thickness / 2.0 + grow,
+                fill: FillMode::Fill,
+                color,
+                options,
+            };
+        };
+
+        let start = g.point(digit_origin, *start);
+        let end = g.point(digit_origin, *end);
+        let gap = (end - start).normalize_or_zero() * (g.gap - grow).max(0.0);
+
+        TextureAction::DrawLine {
+            start: start + gap,
+            end: end - gap,
+            width: g.thickness + grow * 2.0,
+            color,
+            options,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphics::displays::Failure;
+
+    fn display(kind: SegmentKind, digits: usize) -> SegmentDisplay {
+        SegmentDisplay::new(kind, digits, (0.0, 0.0), SegmentGeometry::new((10.0, 20.0)))
+    }
+
+    #[test]
+    fn test_glyphs() {
+        assert_eq!(SegmentKind::Seven.segment_count(), 8);
+        assert_eq!(SegmentKind::Fourteen.segment_count(), 15);
+        assert_eq!(SegmentKind::Sixteen.segment_count(), 17);
+
+        assert_eq!(SegmentKind::Seven.glyph('8'), Some(0x7f));
+        // Letters fall back to the other case.
+        assert_eq!(SegmentKind::Seven.glyph('B'), SegmentKind::Seven.glyph('b'));
+        assert_eq!(SegmentKind::Seven.glyph('X'), None);
+
+        // The split top and bottom bars are both lit.
+        assert_eq!(SegmentKind::Sixteen.glyph('7'), Some(0b1111));
+        assert_eq!(
+            SegmentKind::Sixteen.glyph('-'),
+            SegmentKind::Fourteen.glyph('-').map(|mask| mask << 2)
+        );
+
+        for kind in [
+            SegmentKind::Seven,
+            SegmentKind::Fourteen,
+            SegmentKind::Sixteen,
+        ] {
+            for c in "0123456789 -".chars() {
+                let mask = kind.glyph(c).unwrap();
+                assert!(mask < kind.decimal_point(), "{kind:?} {c:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_set_text() {
+        let mut clock = display(SegmentKind::Seven, 4);
+        let dp = SegmentKind::Seven.decimal_point();
+
+        clock.set_text("12.45");
+        assert_eq!(clock.masks(), [0x06, 0x5b | dp, 0x66, 0x6d]);
+
+        clock.set_text("..1X23456");
+        assert_eq!(clock.masks(), [dp, dp, 0x06, 0]);
+
+        clock.set_text("7");
+        assert_eq!(clock.masks(), [0x07, 0, 0, 0]);
+
+        let clock = display(SegmentKind::Seven, 1).with_glyph('X', 0x76);
+        assert_eq!(clock.glyph('X'), Some(0x76));
+    }
+
+    #[test]
+    fn test_actions() {
+        let mut clock = display(SegmentKind::Seven, 2).with_style(
+            DisplayStyle::new(Color::RED)
+                .with_off(Color::BLACK)
+                .with_glow(2.0, 64),
+        );
+        clock.set_text("1.");
+        clock.failures_mut().set(1, Some(Failure::StuckOff));
+
+        let actions = clock.actions(ShapeOptions::default());
+        // 16 segments, of which C and the decimal point are lit and glow.
+        assert_eq!(actions.len(), 16 + 2);
+        assert!(matches!(
+            actions.last(),
+            Some(TextureAction::DrawCircle { color, .. }) if *color == Color::RED
+        ));
+    }
+}
@@ -0,0 +1,465 @@
+//! Animated text for destination signs and interior displays.
+//!
+//! [AnimatedText] cycles through pages, scrolls text that doesn't fit as a marquee, blinks and
+//! animates page changes. Call [AnimatedText::tick] once per tick and [AnimatedText::draw] whenever
+//! the display is redrawn.
+//!
+//! # Example
+//! ```no_run
+//! # use lotus_script::{font::BitmapFont, graphics::{effects::*, textures::Texture}, math::{Rectangle, UVec2}};
+//! # fn example(texture: &mut Texture, font: &BitmapFont, text: &mut AnimatedText, pages: [String; 2]) {
+//! // E.g. `PisStation::interieur_display`. Empty pages are skipped.
+//! text.set_pages(pages);
+//! text.tick();
+//!
+//! texture.clear(lotus_script::graphics::Color::BLACK);
+//! text.draw(texture, font, Rectangle::new(UVec2::new(0, 0), UVec2::new(128, 16)));
+//! # }
+//! ```
+
+use std::cell::Cell;
+
+use lotus_shared::{
+    content::ContentId,
+    font::layout::{TextLayout, TextLayoutOptions},
+    math::{IVec2, Rectangle, UVec2},
+};
+
+use super::{
+    textures::{AlphaMode, Texture},
+    Color, TextHorizontalAlignment,
+};
+use crate::font::BitmapFont;
+
+/// Scrolls text from right to left.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Marquee {
+    /// The scroll speed in pixels per second.
+    pub speed: f32,
+    /// The gap in pixels between the end of the text and its next repetition.
+    pub gap: u32,
+    /// Whether to scroll text that fits into the rectangle, too.
+    pub always: bool,
+}
+
+impl Marquee {
+    /// Scrolls text that doesn't fit with the given speed in pixels per second.
+    pub fn new(speed: f32) -> Self {
+        Self {
+            speed,
+            gap: 16,
+            always: false,
+        }
+    }
+}
+
+/// Switches the text on and off.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Blink {
+    /// How long the text is shown in seconds.
+    pub on: f32,
+    /// How long the text is hidden in seconds.
+    pub off: f32,
+}
+
+/// How to change from one page to the next.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum PageTransition {
+    /// Switch immediately.
+    #[default]
+    Cut,
+    /// Replace the page column by column from left to right over the given number of seconds,
+    /// like a flip-dot display.
+    Wipe(f32),
+    /// Scroll the old page up and the new page in from below over the given number of seconds.
+    ScrollUp(f32),
+}
+
+impl PageTransition {
+    fn duration(&self) -> f32 {
+        match self {
+            Self::Cut => 0.0,
+            Self::Wipe(duration) | Self::ScrollUp(duration) => *duration,
+        }
+    }
+}
+
+/// Text cycling through pages with optional marquee scrolling, blinking and page transitions.
+#[derive(Debug, Clone)]
+pub struct AnimatedText {
+    pages: Vec<String>,
+    /// How long each page is shown in seconds, including the transition.
+    pub page_duration: f32,
+    pub transition: PageTransition,
+    pub marquee: Option<Marquee>,
+    pub blink: Option<Blink>,
+    /// The layout of each page. Text is drawn left aligned while it scrolls.
+    pub layout: TextLayoutOptions,
+    /// The color to draw the text with, `None` to use the colors of the font.
+    pub color: Option<Color>,
+    pub alpha_mode: AlphaMode,
+    page: usize,
+    previous_page: Option<usize>,
+    page_time: f32,
+    /// The time for blinking, wrapped to the blink period.
+    time: f32,
+    /// How far the marquee scrolled on the current page, in pixels.
+    scroll: f32,
+    /// The distance after which the marquee of the current page repeats, known after drawing it.
+    scroll_period: Cell<Option<f32>>,
+}
+
+impl Default for AnimatedText {
+    fn default() -> Self {
+        Self {
+            pages: vec![String::new()],
+            page_duration: 3.0,
+            transition: PageTransition::default(),
+            marquee: None,
+            blink: None,
+            layout: TextLayoutOptions::default(),
+            color: None,
+            alpha_mode: AlphaMode::default(),
+            page: 0,
+            previous_page: None,
+            page_time: 0.0,
+            time: 0.0,
+            scroll: 0.0,
+            scroll_period: Cell::new(None),
+        }
+    }
+}
+
+impl AnimatedText {
+    /// Creates animated text with the given pages.
+    pub fn new(pages: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let mut text = Self::default();
+        text.set_pages(pages);
+        text
+    }
+
+    /// Sets how long each page is shown in seconds.
+    pub fn with_page_duration(mut self, seconds: f32) -> Self {
+        self.page_duration = seconds;
+        self
+    }
+
+    pub fn with_transition(mut self, transition: PageTransition) -> Self {
+        self.transition = transition;
+        self
+    }
+
+    pub fn with_marquee(mut self, marquee: Marquee) -> Self {
+        self.marquee = Some(marquee);
+        self
+    }
+
+    pub fn with_blink(mut self, on: f32, off: f32) -> Self {
+        self.blink = Some(Blink { on, off });
+        self
+    }
+
+    pub fn with_layout(mut self, layout: TextLayoutOptions) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+        self.alpha_mode = alpha_mode;
+        self
+    }
+
+    /// Returns the pages.
+    pub fn pages(&self) -> &[String] {
+        &self.pages
+    }
+
+    /// Sets the pages, skipping empty ones. Restarts the animation if the pages changed.
+    pub fn set_pages(&mut self, pages: impl IntoIterator<Item = impl Into<String>>) {
+        let mut pages = pages
+            .into_iter()
+            .map(Into::into)
+            .filter(|page: &String| !page.is_empty())
+            .collect::<Vec<_>>();
+
+        if pages.is_empty() {
+            pages.push(String::new());
+        }
+
+        if pages != self.pages {
+            self.pages = pages;
+            self.restart();
+        }
+    }
+
+    /// Restarts the animation at the first page.
+    pub fn restart(&mut self) {
+        self.page = 0;
+        self.previous_page = None;
+        self.page_time = 0.0;
+        self.time = 0.0;
+        self.restart_marquee();
+    }
+
+    fn restart_marquee(&mut self) {
+        self.scroll = 0.0;
+        self.scroll_period.set(None);
+    }
+
+    /// Returns the index of the current page.
+    pub fn page(&self) -> usize {
+        self.page
+    }
+
+    /// Returns the text of the current page.
+    pub fn current_page(&self) -> &str {
+        &self.pages[self.page]
+    }
+
+    /// Returns `false` while the text is hidden by blinking.
+    pub fn is_visible(&self) -> bool {
+        self.blink.is_none_or(|Blink { on, off }| {
+            let period = on + off;
+            period <= 0.0 || self.time.rem_euclid(period) < on
+        })
+    }
+
+    /// Advances the animation by [time::delta](crate::time::delta).
+    pub fn tick(&mut self) {
+        self.update(crate::time::delta());
+    }
+
+    /// Advances the animation by the given number of seconds.
+    pub fn update(&mut self, delta: f32) {
+        self.time += delta;
+        if let Some(Blink { on, off }) = self.blink {
+            if on + off > 0.0 {
+                self.time = self.time.rem_euclid(on + off);
+            }
+        }
+
+        if let Some(marquee) = self.marquee {
+            self.scroll += delta * marquee.speed;
+            if let Some(period) = self.scroll_period.get() {
+                self.scroll = self.scroll.rem_euclid(period);
+            }
+        }
+
+        self.page_time += delta;
+        if self.pages.len() > 1 && self.page_duration > 0.0 {
+            while self.page_time >= self.page_duration {
+                self.page_time -= self.page_duration;
+                self.previous_page = Some(self.page);
+                self.page = (self.page + 1) % self.pages.len();
+                self.restart_marquee();
+            }
+        } else {
+            // The page doesn't change, only the first transition needs the time.
+            self.page_time = self
+                .page_time
+                .min(self.page_duration.max(self.transition.duration()));
+        }
+    }
+
+    /// Draws the current state into the rectangle of the texture. The text is clipped to the rectangle.
+    pub fn draw(&self, texture: &mut Texture, font: &BitmapFont, rect: Rectangle) {
+        if !self.is_visible() {
+            return;
+        }
+
+        let duration = self.transition.duration();
+        let previous = self
+            .previous_page
+            .filter(|_| self.page_time < duration)
+            .map(|page| (page, self.page_time / duration));
+
+        let Some((previous, progress)) = previous else {
+            self.draw_page(texture, font, self.page, rect, IVec2::ZERO, rect);
+            return;
+        };
+
+        match self.transition {
+            PageTransition::Cut => {}
+            PageTransition::Wipe(_) => {
+                let split = rect.start().x + (rect.width() as f32 * progress) as u32;
+                let new = Rectangle::new(rect.start(), UVec2::new(split, rect.end().y));
+                let old = Rectangle::new(UVec2::new(split, rect.start().y), rect.end());
+
+                self.draw_page(texture, font, self.page, rect, IVec2::ZERO, new);
+                self.draw_page(texture, font, previous, rect, IVec2::ZERO, old);
+            }
+            PageTransition::ScrollUp(_) => {
+                let offset = (rect.height() as f32 * progress) as i32;
+                let height = rect.height() as i32;
+
+                self.draw_page(texture, font, previous, rect, IVec2::new(0, -offset), rect);
+                self.draw_page(
+                    texture,
+                    font,
+                    self.page,
+                    rect,
+                    IVec2::new(0, height - offset),
+                    rect,
+                );
+            }
+        }
+    }
+
+    fn draw_page(
+        &self,
+        texture: &mut Texture,
+        font: &BitmapFont,
+        page: usize,
+        rect: Rectangle,
+        offset: IVec2,
+        clip: Rectangle,
+    ) {
+        if clip.width() == 0 || clip.height() == 0 {
+            return;
+        }
+
+        let text = &self.pages[page];
+        let layout = font.layout(text, rect, &self.layout);
+
+        let marquee = self
+            .marquee
+            .filter(|marquee| marquee.always || layout.width() > rect.width());
+
+        let Some(marquee) = marquee else {
+            self.draw_layout(texture, font.content_id(), &layout, offset, clip);
+            return;
+        };
+
+        let mut options = self.layout.clone();
+        options.horizontal_alignment = TextHorizontalAlignment::Left;
+        let layout = font.layout(text, rect, &options);
+
+        // The text enters at the right edge and repeats after the gap.
+        let period = (layout.width() + marquee.gap).max(1) as f32;
+        if page == self.page {
+            self.scroll_period.set(Some(period));
+        }
+        let scrolled = self.scroll.rem_euclid(period);
+        let mut x = rect.width() as f32 - scrolled;
+
+        while x + layout.width() as f32 >= 0.0 {
+            self.draw_layout(
+                texture,
+                font.content_id(),
+                &layout,
+                offset + IVec2::new(x as i32, 0),
+                clip,
+            );
+            x -= period;
+        }
+    }
+
+    fn draw_layout(
+        &self,
+        texture: &mut Texture,
+        font: ContentId,
+        layout: &TextLayout,
+        offset: IVec2,
+        clip: Rectangle,
+    ) {
+        for line in &layout.lines {
+            texture.draw_text(
+                font,
+                line.text.as_str(),
+                line.top_left + offset,
+                layout.letter_spacing,
+                self.color,
+                self.alpha_mode,
+                clip,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_cycling() {
+        let mut text =
+            AnimatedText::new(["Line 5", "", "Hauptbahnhof", "Via Markt"]).with_page_duration(2.0);
+        assert_eq!(text.pages().len(), 3);
+
+        text.update(1.9);
+        assert_eq!(text.current_page(), "Line 5");
+        text.update(0.1);
+        assert_eq!(text.current_page(), "Hauptbahnhof");
+        assert_eq!(text.previous_page, Some(0));
+
+        // Several pages in one update wrap around.
+        text.update(4.5);
+        assert_eq!(text.page(), 0);
+
+        // Setting the same pages keeps the animation going, new ones restart it.
+        text.set_pages(["Line 5", "Hauptbahnhof", "Via Markt"]);
+        assert_eq!(text.page(), 0);
+        text.update(2.0);
+        text.set_pages(["Line 5", "Hauptbahnhof", "Via Markt"]);
+        assert_eq!(text.page(), 1);
+        text.set_pages(["Not in service"]);
+        assert_eq!(text.page(), 0);
+        assert_eq!(text.previous_page, None);
+    }
+
+    #[test]
+    fn test_blink() {
+        let mut text = AnimatedText::new(["Emergency stop"]).with_blink(0.5, 0.25);
+        assert!(text.is_visible());
+
+        text.update(0.6);
+        assert!(!text.is_visible());
+        text.update(0.2);
+        assert!(text.is_visible());
+
+        // The blink time stays within the period.
+        for _ in 0..10_000 {
+            text.update(0.75);
+        }
+        assert!(text.time < 0.75);
+        assert!(text.is_visible());
+    }
+
+    #[test]
+    fn test_marquee() {
+        let mut text = AnimatedText::new(["A long destination"]).with_marquee(Marquee::new(10.0));
+
+        text.update(1.0);
+        assert_eq!(text.scroll, 10.0);
+
+        // Once the period is known from drawing, the scroll position wraps around.
+        text.scroll_period.set(Some(16.0));
+        text.update(1.0);
+        assert_eq!(text.scroll, 4.0);
+        for _ in 0..10_000 {
+            text.update(1.0);
+        }
+        assert!(text.scroll < 16.0);
+        assert!(text.page_time <= text.page_duration);
+    }
+
+    #[test]
+    fn test_marquee_restarts_with_page() {
+        let mut text = AnimatedText::new(["Hauptbahnhof", "Via Markt"])
+            .with_page_duration(3.0)
+            .with_marquee(Marquee::new(10.0));
+        text.scroll_period.set(Some(100.0));
+
+        text.update(2.0);
+        assert_eq!(text.scroll, 20.0);
+        text.update(1.5);
+        assert_eq!(text.page(), 1);
+        assert_eq!(text.scroll, 0.0);
+        assert_eq!(text.scroll_period.get(), None);
+    }
+}
@@ -0,0 +1,770 @@
+#[cfg(feature = "internal")]
+use lotus_script_sys::FfiObject;
+
+pub use lotus_shared::graphics::*;
+
+pub mod displays;
+pub mod effects;
+
+pub mod textures {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use lotus_script_sys::FfiObject;
+    use lotus_shared::{
+        content::ContentId,
+        font::layout::TextLayout,
+        graphics::{atlas::SpriteAtlas, batch::ActionBatch, Color},
+        math::{IVec2, Rectangle, UVec2, Vec2},
+    };
+
+    pub use lotus_shared::graphics::textures::*;
+
+    /// A texture that can be manipulated and displayed on script texture slots.
+    #[derive(Debug)]
+    pub struct Texture {
+        handle: TextureHandle,
+        size: UVec2,
+        shape_options: ShapeOptions,
+    }
+
+    thread_local! {
+        static BATCHES: RefCell<HashMap<u32, ActionBatch>> = RefCell::new(HashMap::new());
+    }
+
+    /// Submits the collected actions of all textures in batching mode.
+    /// This is called automatically after every tick.
+    pub fn submit_batches() {
+        BATCHES.with_borrow_mut(|batches| {
+            for (id, batch) in batches.iter_mut() {
+                submit_batch(*id, batch);
+            }
+        });
+    }
+
+    fn submit_batch(id: u32, batch: &mut ActionBatch) -> bool {
+        match batch.take_changed() {
+            Some(actions) => {
+                let actions = FfiObject::from_bytes(actions);
+                unsafe { lotus_script_sys::textures::add_actions(id, actions.packed()) };
+                true
+            }
+            None => false,
+        }
+    }
+
+    impl Texture {
+        /// Create a new texture.
+        #[must_use]
+        pub fn create<'a>(options: impl Into<TextureCreationOptions<'a>>) -> Self {
+            let options = options.into();
+            let size = UVec2::new(options.width, options.height);
+            let options = FfiObject::new(&options);
+
+            let handle = unsafe { lotus_script_sys::textures::create(options.packed()) };
+
+            Self {
+                handle: TextureHandle::new(handle),
+                size,
+                shape_options: ShapeOptions::default(),
+            }
+        }
+
+        /// Create a new texture with the given RGBA8 pixels, row by row starting at the top left.
+        ///
+        /// # Panics
+        /// Panics if `data` does not contain exactly `width * height * 4` bytes.
+        #[must_use]
+        pub fn from_rgba(width: u32, height: u32, data: &[u8]) -> Self {
+            Self::create(TextureCreationOptions::from_rgba(width, height, data))
+        }
+
+        /// Get the size of the texture in pixels.
+        pub fn size(&self) -> UVec2 {
+            self.size
+        }
+
+        /// Add an action to the texture. You may want to call the helper methods
+        /// instead of this.
+        pub fn add_action(&mut self, action: TextureAction) {
+            let Some(action) =
+                BATCHES.with_borrow_mut(|batches| match batches.get_mut(&self.handle.id()) {
+                    Some(batch) => {
+                        batch.push(action);
+                        None
+                    }
+                    None => Some(action),
+                })
+            else {
+                return;
+            };
+
+            let action = FfiObject::new(&action);
+
+            unsafe { lotus_script_sys::textures::add_action(self.handle.id(), action.packed()) }
+        }
+
+        /// Add several actions to the texture in one call, e.g. everything a display emulator
+        /// draws in a frame.
+        pub fn add_actions(&mut self, actions: impl IntoIterator<Item = TextureAction>) {
+            let actions =
+                BATCHES.with_borrow_mut(|batches| match batches.get_mut(&self.handle.id()) {
+                    Some(batch) => {
+                        actions.into_iter().for_each(|action| batch.push(action));
+                        Vec::new()
+                    }
+                    None => actions.into_iter().collect(),
+                });
+
+            if actions.is_empty() {
+                return;
+            }
+
+            let actions = FfiObject::new(&actions);
+            unsafe { lotus_script_sys::textures::add_actions(self.handle.id(), actions.packed()) }
+        }
+
+        /// Enable or disable batching mode.
+        ///
+        /// In batching mode, actions are collected locally and submitted as a single list after
+        /// the tick, or when calling [Texture::submit]. Actions that are completely painted over
+        /// by a later clear, opaque rectangle or region write are dropped. If the actions of a
+        /// tick repaint the whole texture, e.g. starting with [Texture::clear], and are identical
+        /// to the last submitted ones, nothing is submitted. Ticks that draw on top of the
+        /// previous content are always submitted.
+        pub fn set_batching(&mut self, enabled: bool) {
+            let id = self.handle.id();
+
+            BATCHES.with_borrow_mut(|batches| {
+                if enabled {
+                    batches
+                        .entry(id)
+                        .or_insert_with(|| ActionBatch::new(Some(self.size)));
+                } else if let Some(mut batch) = batches.remove(&id) {
+                    submit_batch(id, &mut batch);
+                }
+            });
+        }
+
+        fn submit_pending(&self) {
+            let id = self.handle.id();
+
+            BATCHES.with_borrow_mut(|batches| {
+                if let Some(batch) = batches.get_mut(&id) {
+                    submit_batch(id, batch);
+                }
+            });
+        }
+
+        /// Returns `true` if the texture is in batching mode. See [Texture::set_batching].
+        pub fn is_batching(&self) -> bool {
+            BATCHES.with_borrow(|batches| batches.contains_key(&self.handle.id()))
+        }
+
+        /// Submit the actions collected in batching mode now instead of after the tick.
+        /// Returns `false` if there was nothing to submit or the actions repainted the texture
+        /// exactly like last time.
+        pub fn submit(&mut self) -> bool {
+            let id = self.handle.id();
+
+            BATCHES.with_borrow_mut(|batches| {
+                batches
+                    .get_mut(&id)
+                    .is_some_and(|batch| submit_batch(id, batch))
+            })
+        }
+
+        /// Draw a rectangle on the texture.
+        pub fn draw_rect(&mut self, start: impl Into<UVec2>, end: impl Into<UVec2>, color: Color) {
+            self.add_action(TextureAction::DrawRect {
+                start: start.into(),
+                end: end.into(),
+                color,
+            });
+        }
+
+        /// Clear the texture with a color.
+        pub fn clear(&mut self, color: Color) {
+            self.add_action(TextureAction::Clear(color));
+        }
+
+        /// Read the color of a pixel on the texture. Use [Texture::read_region] to read many pixels.
+        #[inline]
+        pub fn read_pixel(&self, x: u32, y: u32) -> Color {
+            self.submit_pending();
+            let packed = unsafe { lotus_script_sys::textures::get_pixel(self.handle.id(), x, y) };
+            packed.into()
+        }
+
+        /// Read the pixels of a rectangle of the texture in one call. The rectangle is clipped
+        /// to the texture.
+        ///
+        /// Like [Texture::flush], this first applies all pending actions, including the ones
+        /// collected in batching mode, so the result contains everything drawn so far.
+        /// Cause of streaming assets, this method will return [ReadRegionError::NotReady] if the
+        /// actions can't be applied yet. Just call this method again, e.g. in the next tick,
+        /// until it returns the pixels.
+        pub fn read_region(&self, region: Rectangle) -> Result<PixelBuffer, ReadRegionError> {
+            self.submit_pending();
+
+            let end = region.end().min(self.size);
+            let start = region.start().min(end);
+            let region = Rectangle::new(start, end);
+
+            // Nothing to read, and the engine's empty result would look like "not ready".
+            if region.width() == 0 || region.height() == 0 {
+                return Ok(PixelBuffer::new(region.size(), Vec::new())?);
+            }
+
+            let packed = unsafe {
+                lotus_script_sys::textures::read_region(
+                    self.handle.id(),
+                    FfiObject::new(&region).packed(),
+                )
+            };
+
+            if packed == 0 {
+                return Err(ReadRegionError::NotReady);
+            }
+
+            let data = FfiObject::from_packed(packed);
+            Ok(PixelBuffer::new(region.size(), data.as_bytes())?)
+        }
+
+        /// Read all pixels of the texture in one call. See [Texture::read_region].
+        pub fn read_all(&self) -> Result<PixelBuffer, ReadRegionError> {
+            self.read_region(Rectangle::from_size(UVec2::ZERO, self.size))
+        }
+
+        /// Draw multiple pixels on the texture.
+        pub fn draw_pixels<P>(&mut self, pixels: &[P])
+        where
+            P: Into<DrawPixel> + Copy,
+        {
+            let pixels = pixels.iter().map(|p| (*p).into()).collect();
+
+            self.add_action(TextureAction::DrawPixels(pixels));
+        }
+
+        /// Replace the pixels of a region with the given RGBA8 pixels, row by row starting at the
+        /// top left of the region. This is much cheaper than [Texture::draw_pixels] for larger areas.
+        ///
+        /// # Panics
+        /// Panics if `data` does not contain exactly `region.width() * region.height() * 4` bytes.
+        pub fn write_region(&mut self, region: Rectangle, data: impl Into<Box<[u8]>>) {
+            self.add_action(TextureAction::write_region(region, data));
+        }
+
+        /// Replace the pixels of a region with the given colors, row by row starting at the top left of the region.
+        ///
+        /// # Panics
+        /// Panics if `colors` does not contain exactly `region.width() * region.height()` colors.
+        pub fn write_region_colors(&mut self, region: Rectangle, colors: &[Color]) {
+            self.write_region(region, colors_to_rgba(colors));
+        }
+
+        /// Draws another texture on top of this one.
+        pub fn draw_texture(&mut self, other: &Texture, options: DrawTextureOpts) {
+            self.add_action(TextureAction::DrawScriptTexture {
+                handle: other.handle(),
+                options,
+            });
+        }
+
+        /// Call this once for every game-texture you want to apply this to. You can define the name in the content tool.
+        pub fn apply_to(&mut self, name: &str) {
+            let name = FfiObject::new(&name);
+            unsafe { lotus_script_sys::textures::apply_to(self.handle.id(), name.packed()) }
+        }
+
+        /// Only call this if you need your actions to be applied immediately.
+        /// Cause of streaming assets, this method will return false if the actions are not yet applied.
+        /// Just call this method again until it returns true.
+        pub fn flush(&mut self) -> bool {
+            self.submit();
+            unsafe { lotus_script_sys::textures::flush_actions(self.handle.id()) == 1 }
+        }
+
+        /// Draws another texture on top of this one.
+        pub fn draw_script_texture(&mut self, other: &Texture, options: DrawTextureOpts) {
+            self.add_action(TextureAction::DrawScriptTexture {
+                handle: other.handle(),
+                options,
+            });
+        }
+
+        /// Draws a content or script texture on top of this one.
+        pub fn draw(&mut self, texture: impl Into<DrawableTexture>, options: DrawTextureOpts) {
+            match texture.into() {
+                DrawableTexture::Content(texture) => {
+                    self.add_action(TextureAction::DrawTexture { texture, options })
+                }
+                DrawableTexture::Script(handle) => {
+                    self.add_action(TextureAction::DrawScriptTexture { handle, options })
+                }
+            }
+        }
+
+        /// Draws a content texture, e.g. an icon or a logo, on top of this one.
+        pub fn draw_content_texture(&mut self, texture: ContentId, options: DrawTextureOpts) {
+            self.add_action(TextureAction::DrawTexture { texture, options });
+        }
+
+        /// Draws a frame of the atlas to the target rectangle, blending it with the existing pixels.
+        /// Returns `false` if the atlas has no frame with the given name.
+        pub fn draw_sprite(&mut self, atlas: &SpriteAtlas, frame: &str, target: Rectangle) -> bool {
+            match atlas.draw_options(frame, target, AlphaMode::Blend) {
+                Some(options) => {
+                    self.draw_content_texture(atlas.texture, options);
+                    true
+                }
+                None => false,
+            }
+        }
+
+        /// Draws a frame of the atlas at the given position in its original size.
+        /// Returns `false` if the atlas has no frame with the given name.
+        pub fn draw_sprite_at(
+            &mut self,
+            atlas: &SpriteAtlas,
+            frame: &str,
+            top_left: impl Into<UVec2>,
+        ) -> bool {
+            match atlas.frame(frame) {
+                Some(source) => {
+                    let target = Rectangle::from_size(top_left.into(), source.size());
+                    self.draw_sprite(atlas, frame, target)
+                }
+                None => false,
+            }
+        }
+
+        /// Draws a text on the texture.
+        #[expect(clippy::too_many_arguments)]
+        pub fn draw_text(
+            &mut self,
+            font: ContentId,
+            text: impl Into<String>,
+            top_left: impl Into<IVec2>,
+            letter_spacing: u32,
+            full_color: impl Into<Option<Color>>,
+            alpha_mode: AlphaMode,
+            target_rect: impl Into<Option<Rectangle>>,
+        ) {
+            self.add_action(TextureAction::DrawText {
+                font,
+                text: text.into(),
+                top_left: top_left.into(),
+                letter_spacing,
+                full_color: full_color.into(),
+                alpha_mode,
+                target_rect: target_rect.into(),
+            });
+        }
+
+        /// Draws text laid out with [BitmapFont::layout](crate::font::BitmapFont::layout), clipped to its rectangle.
+        pub fn draw_text_layout(
+            &mut self,
+            font: ContentId,
+            layout: &TextLayout,
+            full_color: Option<Color>,
+            alpha_mode: AlphaMode,
+        ) {
+            for line in &layout.lines {
+                self.draw_text(
+                    font,
+                    line.text.as_str(),
+                    line.top_left,
+                    layout.letter_spacing,
+                    full_color,
+                    alpha_mode,
+                    layout.rect,
+                );
+            }
+        }
+
+        /// Set the options used by the shape drawing methods, e.g. [Texture::draw_line].
+        /// Defaults to [ShapeOptions::default].
+        pub fn set_shape_options(&mut self, options: ShapeOptions) {
+            self.shape_options = options;
+        }
+
+        /// Get the options used by the shape drawing methods.
+        pub fn shape_options(&self) -> ShapeOptions {
+            self.shape_options
+        }
+
+        /// Draw a line on the texture.
+        pub fn draw_line(
+            &mut self,
+            start: impl Into<Vec2>,
+            end: impl Into<Vec2>,
+            width: f32,
+            color: Color,
+        ) {
+            self.add_action(TextureAction::DrawLine {
+                start: start.into(),
+                end: end.into(),
+                width,
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw connected lines through the given points on the texture.
+        pub fn draw_polyline<P>(&mut self, points: &[P], width: f32, color: Color)
+        where
+            P: Into<Vec2> + Copy,
+        {
+            self.add_action(TextureAction::DrawPolyline {
+                points: points.iter().map(|p| (*p).into()).collect(),
+                closed: false,
+                width,
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw a filled polygon on the texture.
+        pub fn draw_polygon<P>(&mut self, points: &[P], color: Color)
+        where
+            P: Into<Vec2> + Copy,
+        {
+            self.add_action(TextureAction::DrawPolygon {
+                points: points.iter().map(|p| (*p).into()).collect(),
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw the outline of a polygon on the texture.
+        pub fn draw_polygon_outline<P>(&mut self, points: &[P], width: f32, color: Color)
+        where
+            P: Into<Vec2> + Copy,
+        {
+            self.add_action(TextureAction::DrawPolyline {
+                points: points.iter().map(|p| (*p).into()).collect(),
+                closed: true,
+                width,
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw the outline of a rectangle on the texture.
+        pub fn draw_rect_outline(
+            &mut self,
+            start: impl Into<UVec2>,
+            end: impl Into<UVec2>,
+            width: f32,
+            color: Color,
+        ) {
+            self.add_action(TextureAction::DrawRectOutline {
+                start: start.into(),
+                end: end.into(),
+                width,
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw a rectangle with rounded corners on the texture.
+        pub fn draw_rounded_rect(
+            &mut self,
+            start: impl Into<UVec2>,
+            end: impl Into<UVec2>,
+            radius: f32,
+            fill: FillMode,
+            color: Color,
+        ) {
+            self.add_action(TextureAction::DrawRoundedRect {
+                start: start.into(),
+                end: end.into(),
+                radius,
+                fill,
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw a circle on the texture.
+        pub fn draw_circle(
+            &mut self,
+            center: impl Into<Vec2>,
+            radius: f32,
+            fill: FillMode,
+            color: Color,
+        ) {
+            self.add_action(TextureAction::DrawCircle {
+                center: center.into(),
+                radius,
+                fill,
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw an arc of a circle on the texture, e.g. the scale of a gauge.
+        /// Angles are in radians, clockwise starting at the positive x axis.
+        pub fn draw_arc(
+            &mut self,
+            center: impl Into<Vec2>,
+            radius: f32,
+            angles: std::ops::Range<f32>,
+            fill: FillMode,
+            color: Color,
+        ) {
+            self.add_action(TextureAction::DrawArc {
+                center: center.into(),
+                radius,
+                start_angle: angles.start,
+                end_angle: angles.end,
+                fill,
+                color,
+                options: self.shape_options,
+            });
+        }
+
+        /// Draw a needle from `center` with the given length and angle, e.g. of a speedometer.
+        /// The angle is in radians, clockwise starting at the positive x axis.
+        pub fn draw_needle(
+            &mut self,
+            center: impl Into<Vec2>,
+            length: f32,
+            angle: f32,
+            width: f32,
+            color: Color,
+        ) {
+            let center = center.into();
+            let end = center + Vec2::from_angle(angle) * length;
+
+            self.draw_line(center, end, width, color);
+        }
+
+        /// Get the handle of the texture.
+        pub fn handle(&self) -> TextureHandle {
+            self.handle
+        }
+
+        /// Forget the texture. This means it will not be disposed when the texture is dropped.
+        /// Use this only if you want to keep the texture alive without keeping a reference to it.
+        pub fn forget(mut self) {
+            self.set_batching(false);
+            self.handle = TextureHandle::new(u32::MAX);
+        }
+
+        /// Expose the texture to the plugin API under the given name.
+        pub fn expose(&self, name: &str) {
+            let name = FfiObject::new(&name);
+            unsafe { lotus_script_sys::textures::expose(self.handle.id(), name.packed()) }
+        }
+    }
+
+    impl Drop for Texture {
+        fn drop(&mut self) {
+            if self.handle.id() != u32::MAX {
+                BATCHES.with_borrow_mut(|batches| batches.remove(&self.handle.id()));
+                unsafe { lotus_script_sys::textures::dispose(self.handle.id()) }
+            }
+        }
+    }
+
+    /// A texture that can be drawn onto a [Texture].
+    #[derive(Debug, Clone, Copy)]
+    pub enum DrawableTexture {
+        /// A texture from content. Use [crate::content::preload] to make sure it is loaded.
+        Content(ContentId),
+        /// A script texture.
+        Script(TextureHandle),
+    }
+
+    impl From<ContentId> for DrawableTexture {
+        fn from(id: ContentId) -> Self {
+            Self::Content(id)
+        }
+    }
+
+    impl From<&Texture> for DrawableTexture {
+        fn from(texture: &Texture) -> Self {
+            Self::Script(texture.handle())
+        }
+    }
+
+    impl From<TextureHandle> for DrawableTexture {
+        fn from(handle: TextureHandle) -> Self {
+            Self::Script(handle)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::f32::consts::FRAC_PI_2;
+
+        use super::*;
+
+        // The engine functions the texture methods refer to, so the test binary links. They are
+        // never called, as the textures of the tests are in batching mode.
+        #[no_mangle]
+        extern "C" fn add_action(_texture: u32, _action: u64) {
+            unreachable!()
+        }
+
+        #[no_mangle]
+        extern "C" fn add_actions(_texture: u32, _actions: u64) {
+            unreachable!()
+        }
+
+        #[no_mangle]
+        extern "C" fn dispose(_texture: u32) {
+            unreachable!()
+        }
+
+        /// Runs `draw` on a texture in batching mode and returns the collected actions, without
+        /// calling the engine.
+        fn draw(draw: impl FnOnce(&mut Texture)) -> Vec<TextureAction> {
+            let size = UVec2::new(64, 64);
+            let mut texture = Texture {
+                handle: TextureHandle::new(7),
+                size,
+                shape_options: ShapeOptions::default(),
+            };
+            BATCHES.with_borrow_mut(|batches| batches.insert(7, ActionBatch::new(Some(size))));
+
+            draw(&mut texture);
+
+            let actions = BATCHES.with_borrow_mut(|batches| batches.remove(&7).unwrap());
+            texture.handle = TextureHandle::new(u32::MAX);
+            actions.actions().to_vec()
+        }
+
+        #[test]
+        fn test_line_with_default_options() {
+            let actions =
+                draw(|texture| texture.draw_line((1.0, 2.0), (30.0, 2.0), 2.5, Color::RED));
+
+            let [TextureAction::DrawLine {
+                start,
+                end,
+                width,
+                color,
+                options,
+            }] = actions.as_slice()
+            else {
+                panic!("expected a line");
+            };
+            assert_eq!((*start, *end), (Vec2::new(1.0, 2.0), Vec2::new(30.0, 2.0)));
+            assert_eq!(*width, 2.5);
+            assert_eq!(*color, Color::RED);
+            assert!(options.anti_alias);
+            assert!(matches!(options.alpha_mode, AlphaMode::Blend));
+        }
+
+        #[test]
+        fn test_needle_angle() {
+            let actions = draw(|texture| {
+                texture.draw_needle((32.0, 32.0), 10.0, 0.0, 1.0, Color::WHITE);
+                texture.draw_needle((32.0, 32.0), 10.0, FRAC_PI_2, 1.0, Color::WHITE);
+            });
+
+            let ends = actions
+                .iter()
+                .map(|action| match action {
+                    TextureAction::DrawLine { start, end, .. } => {
+                        assert_eq!(*start, Vec2::new(32.0, 32.0));
+                        *end
+                    }
+                    _ => panic!("expected a line"),
+                })
+                .collect::<Vec<_>>();
+
+            // Clockwise on the texture, whose y axis points down.
+            assert!(ends[0].abs_diff_eq(Vec2::new(42.0, 32.0), 1e-4));
+            assert!(ends[1].abs_diff_eq(Vec2::new(32.0, 42.0), 1e-4));
+        }
+
+        #[test]
+        fn test_shapes() {
+            let actions = draw(|texture| {
+                texture.set_shape_options(ShapeOptions {
+                    anti_alias: false,
+                    alpha_mode: AlphaMode::Opaque,
+                });
+                texture.draw_polyline(&[(0.0, 0.0), (4.0, 4.0)], 1.0, Color::BLUE);
+                texture.draw_polygon_outline(
+                    &[(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)],
+                    1.0,
+                    Color::BLUE,
+                );
+                texture.draw_polygon(&[(0.0, 0.0), (4.0, 0.0), (0.0, 4.0)], Color::GREEN);
+                texture.draw_circle((8.0, 8.0), 3.0, FillMode::Stroke(1.0), Color::WHITE);
+                texture.draw_arc(
+                    (8.0, 8.0),
+                    6.0,
+                    0.0..FRAC_PI_2,
+                    FillMode::Fill,
+                    Color::WHITE,
+                );
+                texture.draw_rounded_rect((1, 1), (9, 5), 2.0, FillMode::Fill, Color::BLACK);
+                texture.draw_rect_outline((1, 1), (9, 5), 1.0, Color::BLACK);
+            });
+            assert_eq!(actions.len(), 7);
+
+            assert!(matches!(
+                &actions[0],
+                TextureAction::DrawPolyline { points, closed: false, .. } if points.len() == 2
+            ));
+            assert!(matches!(
+                &actions[1],
+                TextureAction::DrawPolyline { points, closed: true, .. } if points.len() == 3
+            ));
+            assert!(
+                matches!(&actions[2], TextureAction::DrawPolygon { points, .. } if points.len() == 3)
+            );
+            assert!(matches!(
+                actions[3],
+                TextureAction::DrawCircle {
+                    radius: 3.0,
+                    fill: FillMode::Stroke(1.0),
+                    ..
+                }
+            ));
+            assert!(matches!(
+                actions[4],
+                TextureAction::DrawArc {
+                    start_angle: 0.0,
+                    end_angle: FRAC_PI_2,
+                    fill: FillMode::Fill,
+                    ..
+                }
+            ));
+            assert!(matches!(
+                actions[5],
+                TextureAction::DrawRoundedRect { radius: 2.0, .. }
+            ));
+            assert!(matches!(
+                actions[6],
+                TextureAction::DrawRectOutline { width: 1.0, .. }
+            ));
+
+            // All shapes use the options set on the texture.
+            for action in &actions {
+                let options = match action {
+                    TextureAction::DrawPolyline { options, .. }
+                    | TextureAction::DrawPolygon { options, .. }
+                    | TextureAction::DrawCircle { options, .. }
+                    | TextureAction::DrawArc { options, .. }
+                    | TextureAction::DrawRoundedRect { options, .. }
+                    | TextureAction::DrawRectOutline { options, .. } => options,
+                    _ => unreachable!(),
+                };
+                assert!(!options.anti_alias);
+                assert!(matches!(options.alpha_mode, AlphaMode::Opaque));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "internal")]
+pub fn fetch_drawable_texture_properties() -> Vec<DrawableTextureProperties> {
+    let properties = unsafe { lotus_script_sys::textures::fetch_drawable_texture_properties() };
+    FfiObject::from_packed(properties).deserialize()
+}
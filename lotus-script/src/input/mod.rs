@@ -3,16 +3,22 @@ use lotus_shared::math::Vec2;
 
 pub use lotus_shared::input::*;
 
+pub mod recording;
+
 /// Get the delta of the mouse since the last frame.
 /// TODO: Specify the units.
 pub fn mouse_delta() -> Vec2 {
-    let delta = unsafe { lotus_script_sys::input::mouse_delta() };
-    FfiObject::from_packed(delta).deserialize()
+    recording::mouse_delta(|| {
+        let delta = unsafe { lotus_script_sys::input::mouse_delta() };
+        FfiObject::from_packed(delta).deserialize()
+    })
 }
 
 pub fn mouse_position() -> Vec2 {
-    let delta = unsafe { lotus_script_sys::input::mouse_position() };
-    FfiObject::from_packed(delta).deserialize()
+    recording::mouse_position(|| {
+        let position = unsafe { lotus_script_sys::input::mouse_position() };
+        FfiObject::from_packed(position).deserialize()
+    })
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -34,6 +40,6 @@ impl From<u32> for MouseSteeringMode {
 }
 
 pub fn mouse_steering_mode() -> MouseSteeringMode {
-    let active = unsafe { lotus_script_sys::input::mouse_steering_mode() };
-    active.into()
+    recording::mouse_steering_mode(|| unsafe { lotus_script_sys::input::mouse_steering_mode() })
+        .into()
 }
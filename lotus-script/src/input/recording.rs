@@ -0,0 +1,372 @@
+//! Recording and replaying the input of a script.
+//!
+//! While recording, everything the script gets from [action::state](crate::action::state),
+//! [action::axis_state](crate::action::axis_state), [mouse_delta](super::mouse_delta),
+//! [mouse_position](super::mouse_position), [mouse_steering_mode](super::mouse_steering_mode)
+//! and every received [ActionEvent] is stored per tick. While replaying, these functions return
+//! the recorded values instead of asking the engine, and live action events are replaced by the
+//! recorded ones, so a session can be replayed deterministically.
+//!
+//! Start recording and replaying at the same point of a tick, e.g. both at the start of
+//! [Script::tick](crate::Script::tick), so the frames line up.
+//!
+//! # Example
+//! ```no_run
+//! # use lotus_script::input::recording;
+//! // When the user starts a bug report:
+//! recording::start_recording();
+//!
+//! // When the user is done:
+//! if let Some(recording) = recording::stop_recording() {
+//!     recording::save("door_jam", &recording);
+//! }
+//!
+//! // Later, to reproduce it:
+//! recording::start_replay(recording::load("door_jam").unwrap());
+//! ```
+
+use std::cell::RefCell;
+
+use lotus_script_sys::FfiObject;
+pub use lotus_shared::input::recording::*;
+use lotus_shared::{
+    action::ActionEvent,
+    input::{ActionState, ActionStateKind, AxisState},
+    math::Vec2,
+    message::Message,
+};
+
+use crate::log;
+
+enum Mode {
+    Off,
+    Recording(InputRecording),
+    Replaying {
+        recording: InputRecording,
+        frame: usize,
+    },
+}
+
+thread_local! {
+    static MODE: RefCell<Mode> = const { RefCell::new(Mode::Off) };
+}
+
+/// Starts recording, discarding a previous unfinished recording and stopping a replay.
+/// The current tick is the first frame.
+pub fn start_recording() {
+    let mut recording = InputRecording::new(crate::time::ticks_alive());
+    recording.frames.push(InputFrame::default());
+
+    MODE.with_borrow_mut(|mode| *mode = Mode::Recording(recording));
+}
+
+/// Stops recording and returns the recording. Returns `None` if not recording.
+pub fn stop_recording() -> Option<InputRecording> {
+    MODE.with_borrow_mut(|mode| match std::mem::replace(mode, Mode::Off) {
+        Mode::Recording(recording) => Some(recording),
+        other => {
+            *mode = other;
+            None
+        }
+    })
+}
+
+/// Starts replaying, stopping a recording. The current tick gets the first frame. When all
+/// frames are replayed, the script gets live input again.
+pub fn start_replay(recording: InputRecording) {
+    MODE.with_borrow_mut(|mode| {
+        *mode = Mode::Replaying {
+            recording,
+            frame: 0,
+        }
+    });
+}
+
+/// Stops replaying and returns to live input.
+pub fn stop_replay() {
+    MODE.with_borrow_mut(|mode| {
+        if matches!(mode, Mode::Replaying { .. }) {
+            *mode = Mode::Off;
+        }
+    });
+}
+
+/// Returns `true` while recording.
+pub fn is_recording() -> bool {
+    MODE.with_borrow(|mode| matches!(mode, Mode::Recording(_)))
+}
+
+/// Returns `true` while replaying.
+pub fn is_replaying() -> bool {
+    MODE.with_borrow(|mode| matches!(mode, Mode::Replaying { .. }))
+}
+
+/// Saves a recording to the recordings folder of the engine as JSON Lines.
+pub fn save(name: &str, recording: &InputRecording) {
+    let name = FfiObject::new(&name);
+    let content = FfiObject::new(&recording.to_json_lines());
+    unsafe { lotus_script_sys::recording::save(name.packed(), content.packed()) };
+}
+
+/// Loads a recording saved with [save].
+pub fn load(name: &str) -> Result<InputRecording, RecordingError> {
+    let packed_name = FfiObject::new(&name);
+    let content = unsafe { lotus_script_sys::recording::load(packed_name.packed()) };
+    if content == 0 {
+        return Err(RecordingError::NotFound(name.to_string()));
+    }
+
+    let content: String = FfiObject::from_packed(content).deserialize();
+    InputRecording::from_json_lines(&content)
+}
+
+/// Moves to the next frame. Called by the [script](crate::script) macro before every tick.
+#[doc(hidden)]
+pub fn next_frame() {
+    if let Some(frames) = MODE.with_borrow_mut(advance) {
+        log::info!("input replay finished after {frames} frames");
+    }
+}
+
+/// Moves to the next frame. Returns the number of frames if a replay finished.
+fn advance(mode: &mut Mode) -> Option<usize> {
+    match mode {
+        Mode::Off => None,
+        Mode::Recording(recording) => {
+            recording.frames.push(InputFrame::default());
+            None
+        }
+        Mode::Replaying { recording, frame } => {
+            *frame += 1;
+            if *frame < recording.frames.len() {
+                return None;
+            }
+
+            let frames = *frame;
+            *mode = Mode::Off;
+            Some(frames)
+        }
+    }
+}
+
+/// Records or replays an input value. `select` returns the slot of the value in a frame.
+fn record<T: Clone>(
+    select: impl FnOnce(&mut InputFrame) -> &mut Option<T>,
+    live: impl FnOnce() -> T,
+    missing: impl FnOnce() -> T,
+) -> T {
+    MODE.with_borrow_mut(|mode| match mode {
+        Mode::Off => live(),
+        Mode::Recording(recording) => {
+            let frame = recording.frames.last_mut().expect("recording has a frame");
+            select(frame).get_or_insert_with(live).clone()
+        }
+        Mode::Replaying { recording, frame } => recording
+            .frames
+            .get_mut(*frame)
+            .and_then(|frame| select(frame).clone())
+            .unwrap_or_else(missing),
+    })
+}
+
+pub(crate) fn action_state(action: &str, live: impl FnOnce() -> ActionState) -> ActionState {
+    MODE.with_borrow_mut(|mode| match mode {
+        Mode::Off => live(),
+        Mode::Recording(recording) => {
+            let frame = recording.frames.last_mut().expect("recording has a frame");
+            *frame
+                .action_states
+                .entry(action.to_string())
+                .or_insert_with(live)
+        }
+        Mode::Replaying { recording, frame } => recording
+            .frames
+            .get(*frame)
+            .and_then(|frame| frame.action_states.get(action).copied())
+            .unwrap_or(ActionState {
+                kind: ActionStateKind::None,
+                cockpit_index: None,
+                uv: None,
+            }),
+    })
+}
+
+pub(crate) fn axis_state(action: &str, live: impl FnOnce() -> AxisState) -> AxisState {
+    MODE.with_borrow_mut(|mode| match mode {
+        Mode::Off => live(),
+        Mode::Recording(recording) => {
+            let frame = recording.frames.last_mut().expect("recording has a frame");
+            *frame
+                .axis_states
+                .entry(action.to_string())
+                .or_insert_with(live)
+        }
+        Mode::Replaying { recording, frame } => recording
+            .frames
+            .get(*frame)
+            .and_then(|frame| frame.axis_states.get(action).copied())
+            .unwrap_or_default(),
+    })
+}
+
+pub(crate) fn mouse_delta(live: impl FnOnce() -> Vec2) -> Vec2 {
+    record(|frame| &mut frame.mouse_delta, live, || Vec2::ZERO)
+}
+
+pub(crate) fn mouse_position(live: impl FnOnce() -> Vec2) -> Vec2 {
+    record(|frame| &mut frame.mouse_position, live, || Vec2::ZERO)
+}
+
+pub(crate) fn mouse_steering_mode(live: impl FnOnce() -> u32) -> u32 {
+    record(|frame| &mut frame.mouse_steering_mode, live, || 0)
+}
+
+/// Records the received action events, or replaces them with the recorded ones.
+pub(crate) fn action_events(messages: Vec<Message>) -> Vec<Message> {
+    MODE.with_borrow_mut(|mode| match mode {
+        Mode::Off => messages,
+        Mode::Recording(recording) => {
+            let frame = recording.frames.last_mut().expect("recording has a frame");
+            frame.action_events.extend(
+                messages
+                    .iter()
+                    .filter_map(|message| message.value::<ActionEvent>().ok()),
+            );
+            messages
+        }
+        Mode::Replaying { recording, frame } => {
+            let mut messages = messages
+                .into_iter()
+                .filter(|message| !message.has_type::<ActionEvent>())
+                .collect::<Vec<_>>();
+
+            if let Some(frame) = recording.frames.get(*frame) {
+                messages.extend(frame.action_events.iter().map(Message::new));
+            }
+            messages
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::message::BatterySwitch;
+
+    fn state(kind: ActionStateKind) -> ActionState {
+        ActionState {
+            kind,
+            cockpit_index: Some(0),
+            uv: None,
+        }
+    }
+
+    fn event(name: &str, kind: ActionStateKind) -> Message {
+        Message::new(&ActionEvent {
+            name: name.to_string(),
+            state: state(kind),
+        })
+    }
+
+    fn advance_frame() {
+        MODE.with_borrow_mut(advance);
+    }
+
+    fn record_session() -> InputRecording {
+        let mut recording = InputRecording::new(0);
+        recording.frames.push(InputFrame::default());
+        MODE.with_borrow_mut(|mode| *mode = Mode::Recording(recording));
+
+        let pressed = || state(ActionStateKind::JustPressed);
+        assert_eq!(
+            action_state("horn", pressed).kind,
+            ActionStateKind::JustPressed
+        );
+        // The value of a frame is asked for once.
+        assert_eq!(
+            action_state("horn", || unreachable!()).kind,
+            ActionStateKind::JustPressed
+        );
+        let throttle = AxisState {
+            value: 0.5,
+            cockpit_index: Some(0),
+        };
+        assert_eq!(axis_state("throttle", || throttle), throttle);
+        assert_eq!(mouse_delta(|| Vec2::new(2.0, -1.0)), Vec2::new(2.0, -1.0));
+        let received = action_events(vec![event("horn", ActionStateKind::JustPressed)]);
+        assert_eq!(received.len(), 1);
+
+        advance_frame();
+        let released = || state(ActionStateKind::JustReleased);
+        assert_eq!(
+            action_state("horn", released).kind,
+            ActionStateKind::JustReleased
+        );
+
+        stop_recording().unwrap()
+    }
+
+    #[test]
+    fn test_record() {
+        let recording = record_session();
+        assert!(!is_recording());
+
+        assert_eq!(recording.frames.len(), 2);
+        let first = &recording.frames[0];
+        assert_eq!(
+            first.action_states["horn"].kind,
+            ActionStateKind::JustPressed
+        );
+        assert_eq!(first.axis_states["throttle"].value, 0.5);
+        assert_eq!(first.mouse_delta, Some(Vec2::new(2.0, -1.0)));
+        assert_eq!(first.action_events.len(), 1);
+        assert_eq!(
+            recording.frames[1].action_states["horn"].kind,
+            ActionStateKind::JustReleased
+        );
+    }
+
+    #[test]
+    fn test_replay() {
+        start_replay(record_session());
+        assert!(is_replaying());
+
+        // Recorded values win over live input, values that weren't recorded are empty.
+        assert_eq!(
+            action_state("horn", || state(ActionStateKind::None)).kind,
+            ActionStateKind::JustPressed
+        );
+        assert_eq!(
+            action_state("bell", || state(ActionStateKind::Pressed)).kind,
+            ActionStateKind::None
+        );
+        assert_eq!(axis_state("throttle", AxisState::default).value, 0.5);
+        assert_eq!(mouse_position(|| Vec2::ONE), Vec2::ZERO);
+
+        // Live action events are replaced, other messages are kept.
+        let messages = action_events(vec![
+            event("bell", ActionStateKind::JustPressed),
+            Message::new(&BatterySwitch(true)),
+        ]);
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].has_type::<BatterySwitch>());
+        assert_eq!(messages[1].value::<ActionEvent>().unwrap().name, "horn");
+
+        advance_frame();
+        assert_eq!(
+            action_state("horn", || state(ActionStateKind::None)).kind,
+            ActionStateKind::JustReleased
+        );
+        assert!(action_events(Vec::new()).is_empty());
+
+        // After the last frame, the live input is back.
+        assert_eq!(MODE.with_borrow_mut(advance), Some(2));
+        assert!(!is_replaying());
+        assert_eq!(
+            action_state("horn", || state(ActionStateKind::Pressed)).kind,
+            ActionStateKind::Pressed
+        );
+        let live = action_events(vec![event("bell", ActionStateKind::JustPressed)]);
+        assert_eq!(live[0].value::<ActionEvent>().unwrap().name, "bell");
+    }
+}
@@ -1,4 +1,7 @@
 pub use lotus_bindgen_macros::lotus_bindgen;
+/// Re-exported for instrumenting scripts, see [log::init_tracing].
+#[cfg(feature = "tracing")]
+pub use tracing;
 
 use message::Message;
 
@@ -21,6 +24,7 @@ pub mod rand;
 #[doc(hidden)]
 pub mod settings;
 pub mod time;
+pub mod ui;
 pub mod var;
 pub mod vehicle;
 pub mod pis {
@@ -31,7 +35,9 @@ pub mod prelude {
         action,
         graphics::{textures::Texture, Color},
         log,
-        message::{message_type, send_message, Message, MessageTarget, MessageType},
+        message::{
+            message_type, send_message, Message, MessageSchedule, MessageTarget, MessageType,
+        },
         rand, script, time,
         var::{get_var, set_var, VariableType},
         vehicle, Script,
@@ -0,0 +1,176 @@
+use tracing::{
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+    subscriber::Interest,
+    Event, Metadata, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+use super::{Level, LogRecord, LogSpan};
+
+/// A [tracing_subscriber] layer that writes [tracing] events to the engine log, with their
+/// structured fields and the spans they happened in.
+///
+/// Events and spans are filtered with the same filter as the log macros, see
+/// [set_level](super::set_level) and [set_target_level](super::set_target_level).
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HostLayer {
+    _private: (),
+}
+
+impl HostLayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+/// Installs a [HostLayer] as the global [tracing] subscriber. Call it once in
+/// [Script::init](crate::Script::init).
+///
+/// # Example
+/// ```no_run
+/// # use lotus_script::{log, tracing};
+/// log::init_tracing();
+///
+/// let span = tracing::info_span!("door", index = 1).entered();
+/// tracing::debug!(speed = 0.5, "opening");
+/// ```
+pub fn init_tracing() {
+    let subscriber = tracing_subscriber::registry().with(HostLayer::new());
+    if tracing::subscriber::set_global_default(subscriber).is_err() {
+        crate::warning!("a global tracing subscriber is already set");
+    }
+}
+
+fn to_level(level: &tracing::Level) -> Level {
+    match *level {
+        tracing::Level::TRACE => Level::Trace,
+        tracing::Level::DEBUG => Level::Debug,
+        tracing::Level::INFO => Level::Info,
+        tracing::Level::WARN => Level::Warn,
+        _ => Level::Error,
+    }
+}
+
+/// The fields of a span, stored in its extensions.
+struct SpanFields(Vec<(String, serde_json::Value)>);
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: Option<String>,
+    fields: Vec<(String, serde_json::Value)>,
+}
+
+impl FieldVisitor {
+    fn push(&mut self, field: &Field, value: serde_json::Value) {
+        if field.name() == "message" {
+            self.message = Some(match value {
+                serde_json::Value::String(message) => message,
+                value => value.to_string(),
+            });
+        } else {
+            self.fields.push((field.name().to_string(), value));
+        }
+    }
+}
+
+impl Visit for FieldVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.push(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.push(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.push(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.push(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.push(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        self.push(field, format!("{value:?}").into());
+    }
+}
+
+impl<S> Layer<S> for HostLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn register_callsite(&self, _metadata: &'static Metadata<'static>) -> Interest {
+        // The filter can change at any time, so ask every time.
+        Interest::sometimes()
+    }
+
+    fn enabled(&self, metadata: &Metadata<'_>, _ctx: Context<'_, S>) -> bool {
+        super::enabled(to_level(metadata.level()), metadata.target())
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut visitor = FieldVisitor::default();
+        attrs.record(&mut visitor);
+        span.extensions_mut().insert(SpanFields(visitor.fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut visitor = FieldVisitor::default();
+        values.record(&mut visitor);
+
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+            for (name, value) in visitor.fields {
+                match fields.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, v)) => *v = value,
+                    None => fields.push((name, value)),
+                }
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+
+        let spans = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| LogSpan {
+                        name: span.name().to_string(),
+                        fields: span
+                            .extensions()
+                            .get::<SpanFields>()
+                            .map(|fields| fields.0.clone())
+                            .unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        super::write_record(&LogRecord {
+            level: to_level(metadata.level()),
+            target: Some(metadata.target().to_string()),
+            message: visitor.message.unwrap_or_default(),
+            fields: visitor.fields,
+            spans,
+        });
+    }
+}
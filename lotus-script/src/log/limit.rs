@@ -0,0 +1,428 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use super::{Level, LogRecord};
+
+/// How long identical messages are collapsed before a "repeated" entry is written anyway, in
+/// seconds of simulation time.
+const REPEAT_REPORT_INTERVAL: f64 = 10.0;
+
+/// The location of a limited log macro in the source code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CallSite {
+    pub file: &'static str,
+    pub line: u32,
+    pub column: u32,
+}
+
+impl CallSite {
+    #[doc(hidden)]
+    pub const fn new(file: &'static str, line: u32, column: u32) -> Self {
+        Self { file, line, column }
+    }
+}
+
+/// How many messages the limited log macros suppressed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Suppressed {
+    /// By [log_once!](crate::log_once) after the first message.
+    pub once: u64,
+    /// By [log_every!](crate::log_every) within the interval.
+    pub rate_limited: u64,
+    /// By [log_dedup!](crate::log_dedup) because they repeated the previous message.
+    pub repeated: u64,
+}
+
+impl Suppressed {
+    pub fn total(&self) -> u64 {
+        self.once + self.rate_limited + self.repeated
+    }
+}
+
+struct Repeated {
+    level: Level,
+    target: String,
+    message: String,
+    count: u64,
+    since: f64,
+}
+
+#[derive(Default)]
+struct Limits {
+    logged_once: HashSet<CallSite>,
+    last_logged: HashMap<CallSite, (f64, u64)>,
+    /// The message [log_dedup!](crate::log_dedup) is collapsing, per call site.
+    repeated: HashMap<CallSite, Repeated>,
+    suppressed: Suppressed,
+    by_site: HashMap<CallSite, u64>,
+}
+
+thread_local! {
+    static LIMITS: RefCell<Limits> = RefCell::default();
+}
+
+/// Returns the number of suppressed messages since the script started or the counters were
+/// reset.
+pub fn suppressed() -> Suppressed {
+    LIMITS.with_borrow(|limits| limits.suppressed)
+}
+
+/// Returns the number of suppressed messages per call site, most suppressed first.
+pub fn suppressed_by_site() -> Vec<(CallSite, u64)> {
+    let mut sites: Vec<_> = LIMITS.with_borrow(|limits| {
+        limits
+            .by_site
+            .iter()
+            .map(|(site, count)| (*site, *count))
+            .collect()
+    });
+    sites.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    sites
+}
+
+/// Resets the suppressed message counters. Messages already logged once stay suppressed.
+pub fn reset_suppressed() {
+    LIMITS.with_borrow_mut(|limits| {
+        limits.suppressed = Suppressed::default();
+        limits.by_site.clear();
+    });
+}
+
+/// A "repeated" entry that has to be written.
+#[derive(Debug, PartialEq)]
+struct RepeatedEntry {
+    level: Level,
+    target: String,
+    count: u64,
+}
+
+impl Repeated {
+    /// Starts counting again and returns the entry for the repetitions so far.
+    fn take(&mut self, now: f64) -> RepeatedEntry {
+        let count = std::mem::take(&mut self.count);
+        self.since = now;
+        RepeatedEntry {
+            level: self.level,
+            target: self.target.clone(),
+            count,
+        }
+    }
+}
+
+impl RepeatedEntry {
+    fn record(self) -> LogRecord {
+        let message = format!("last message repeated {} times", self.count);
+        LogRecord::new(self.level, message).with_target(self.target)
+    }
+}
+
+fn write_repeated(entries: Vec<RepeatedEntry>) {
+    for entry in entries {
+        super::write_record(&entry.record());
+    }
+}
+
+impl Limits {
+    fn suppress(&mut self, site: CallSite) {
+        *self.by_site.entry(site).or_default() += 1;
+    }
+
+    fn once(&mut self, site: CallSite) -> bool {
+        if self.logged_once.insert(site) {
+            return true;
+        }
+
+        self.suppressed.once += 1;
+        self.suppress(site);
+        false
+    }
+
+    fn every(&mut self, site: CallSite, seconds: f64, now: f64) -> Option<u64> {
+        match self.last_logged.get_mut(&site) {
+            Some((last, suppressed)) if now - *last < seconds => {
+                *suppressed += 1;
+                self.suppressed.rate_limited += 1;
+                self.suppress(site);
+                None
+            }
+            Some((last, suppressed)) => {
+                *last = now;
+                Some(std::mem::take(suppressed))
+            }
+            None => {
+                self.last_logged.insert(site, (now, 0));
+                Some(0)
+            }
+        }
+    }
+
+    /// Returns the "repeated" entry to write before the message, and whether the message is
+    /// written.
+    fn dedup(
+        &mut self,
+        site: CallSite,
+        level: Level,
+        target: &str,
+        message: &str,
+        now: f64,
+    ) -> (Option<RepeatedEntry>, bool) {
+        if let Some(repeated) = self.repeated.get_mut(&site) {
+            if repeated.level == level && repeated.target == target && repeated.message == message {
+                repeated.count += 1;
+                let entry =
+                    (now - repeated.since >= REPEAT_REPORT_INTERVAL).then(|| repeated.take(now));
+                self.suppressed.repeated += 1;
+                self.suppress(site);
+                return (entry, false);
+            }
+        }
+
+        let previous = self.repeated.insert(
+            site,
+            Repeated {
+                level,
+                target: target.to_string(),
+                message: message.to_string(),
+                count: 0,
+                since: now,
+            },
+        );
+        let entry = previous
+            .filter(|previous| previous.count > 0)
+            .map(|mut previous| previous.take(now));
+        (entry, true)
+    }
+
+    /// Takes the "repeated" entries of all call sites, or only of those that weren't reported
+    /// for [REPEAT_REPORT_INTERVAL].
+    fn take_repeated(&mut self, now: f64, stale_only: bool) -> Vec<RepeatedEntry> {
+        let mut sites = self
+            .repeated
+            .iter()
+            .filter(|(_, r)| {
+                r.count > 0 && (!stale_only || now - r.since >= REPEAT_REPORT_INTERVAL)
+            })
+            .map(|(site, _)| *site)
+            .collect::<Vec<_>>();
+        sites.sort_unstable();
+
+        sites
+            .into_iter()
+            .map(|site| self.repeated.get_mut(&site).unwrap().take(now))
+            .collect()
+    }
+}
+
+/// Writes the "repeated" entries for the messages [log_dedup!](crate::log_dedup) is currently
+/// collapsing, e.g. before dumping state to the log.
+pub fn flush_repeated() {
+    let now = crate::time::elapsed();
+    write_repeated(LIMITS.with_borrow_mut(|limits| limits.take_repeated(now, false)));
+}
+
+/// Writes the "repeated" entries that are due, even if the message isn't logged again.
+/// Called by the [script](crate::script) macro before every tick.
+#[doc(hidden)]
+pub fn flush_stale_repeated() {
+    let now = crate::time::elapsed();
+    write_repeated(LIMITS.with_borrow_mut(|limits| limits.take_repeated(now, true)));
+}
+
+/// Returns `true` the first time it is called for a call site.
+#[doc(hidden)]
+pub fn once(site: CallSite) -> bool {
+    LIMITS.with_borrow_mut(|limits| limits.once(site))
+}
+
+/// Returns the number of messages suppressed since the last one if at least `seconds` passed
+/// since then, `None` if the message is suppressed.
+#[doc(hidden)]
+pub fn every(site: CallSite, seconds: f64) -> Option<u64> {
+    let now = crate::time::elapsed();
+    LIMITS.with_borrow_mut(|limits| limits.every(site, seconds, now))
+}
+
+/// Writes the message unless it is the same as the previous one written from this call site.
+#[doc(hidden)]
+pub fn dedup(site: CallSite, level: Level, target: &str, message: String) {
+    let now = crate::time::elapsed();
+    let (entry, write) =
+        LIMITS.with_borrow_mut(|limits| limits.dedup(site, level, target, &message, now));
+
+    write_repeated(entry.into_iter().collect());
+    if write {
+        super::write_record(&LogRecord::new(level, message).with_target(target));
+    }
+}
+
+/// Log a message only the first time this call site is reached.
+///
+/// # Example
+/// ```no_run
+/// # use lotus_script::log::{self, Level};
+/// log::log_once!(Level::Warn, "texture {} is missing, using a placeholder", "door.png");
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_once {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        let target: &str = $target;
+        if $crate::log::enabled(level, target)
+            && $crate::log::once($crate::log::CallSite::new(file!(), line!(), column!()))
+        {
+            $crate::log::write_target(level, target, format!($($arg)+));
+        }
+    }};
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log_once!(target: module_path!(), $level, $($arg)+)
+    };
+}
+
+/// Log a message at most every given number of seconds of simulation time from this call site.
+/// The next message that is logged says how many were suppressed in between.
+///
+/// # Example
+/// ```no_run
+/// # use lotus_script::log::{self, Level};
+/// # let pressure = 4.5;
+/// log::log_every!(5.0, Level::Debug, "brake pressure {pressure:.2} bar");
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_every {
+    (target: $target:expr, $seconds:expr, $level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        let target: &str = $target;
+        if $crate::log::enabled(level, target) {
+            let site = $crate::log::CallSite::new(file!(), line!(), column!());
+            if let Some(suppressed) = $crate::log::every(site, $seconds) {
+                let mut message = format!($($arg)+);
+                if suppressed > 0 {
+                    message.push_str(&format!(" ({suppressed} similar messages suppressed)"));
+                }
+                $crate::log::write_target(level, target, message);
+            }
+        }
+    }};
+    ($seconds:expr, $level:expr, $($arg:tt)+) => {
+        $crate::log_every!(target: module_path!(), $seconds, $level, $($arg)+)
+    };
+}
+
+/// Log a message unless it is identical to the previous message logged from this call site.
+/// Identical consecutive messages are collapsed into a "last message repeated N times" entry,
+/// written when a different message is logged, at the latest every 10 seconds.
+///
+/// # Example
+/// ```no_run
+/// # use lotus_script::log::{self, Level};
+/// # let door = 1;
+/// log::log_dedup!(Level::Warn, "door {door} is jammed");
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log_dedup {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        let target: &str = $target;
+        if $crate::log::enabled(level, target) {
+            $crate::log::dedup(
+                $crate::log::CallSite::new(file!(), line!(), column!()),
+                level,
+                target,
+                format!($($arg)+),
+            );
+        }
+    }};
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log_dedup!(target: module_path!(), $level, $($arg)+)
+    };
+}
+
+#[doc(inline)]
+pub use log_dedup;
+#[doc(inline)]
+pub use log_every;
+#[doc(inline)]
+pub use log_once;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SITE: CallSite = CallSite::new("door.rs", 1, 1);
+    const OTHER: CallSite = CallSite::new("door.rs", 2, 1);
+
+    #[test]
+    fn test_once() {
+        let mut limits = Limits::default();
+
+        assert!(limits.once(SITE));
+        assert!(!limits.once(SITE));
+        assert!(!limits.once(SITE));
+        assert!(limits.once(OTHER));
+        assert_eq!(limits.suppressed.once, 2);
+        assert_eq!(limits.by_site[&SITE], 2);
+    }
+
+    #[test]
+    fn test_every() {
+        let mut limits = Limits::default();
+
+        assert_eq!(limits.every(SITE, 5.0, 0.0), Some(0));
+        assert_eq!(limits.every(SITE, 5.0, 1.0), None);
+        assert_eq!(limits.every(SITE, 5.0, 4.9), None);
+        assert_eq!(limits.every(OTHER, 5.0, 4.9), Some(0));
+        assert_eq!(limits.every(SITE, 5.0, 5.0), Some(2));
+        assert_eq!(limits.every(SITE, 5.0, 6.0), None);
+        assert_eq!(limits.suppressed.rate_limited, 3);
+    }
+
+    #[test]
+    fn test_dedup() {
+        let mut limits = Limits::default();
+        let mut dedup = |site, message, now| limits.dedup(site, Level::Warn, "doors", message, now);
+
+        assert_eq!(dedup(SITE, "jammed", 0.0), (None, true));
+        assert_eq!(dedup(SITE, "jammed", 1.0), (None, false));
+        // Other call sites don't end the repetition.
+        assert_eq!(dedup(OTHER, "open", 1.5), (None, true));
+        assert_eq!(dedup(SITE, "jammed", 2.0), (None, false));
+
+        let (entry, write) = dedup(SITE, "free", 3.0);
+        assert!(write);
+        assert_eq!(
+            entry.unwrap().record().message,
+            "last message repeated 2 times"
+        );
+
+        // Repetitions are reported at the latest every interval.
+        assert_eq!(dedup(SITE, "free", 4.0), (None, false));
+        let (entry, write) = dedup(SITE, "free", 13.0);
+        assert!(!write);
+        assert_eq!(entry.unwrap().count, 2);
+        assert_eq!(limits.suppressed.repeated, 4);
+    }
+
+    #[test]
+    fn test_stale_repeated() {
+        let mut limits = Limits::default();
+
+        limits.dedup(SITE, Level::Info, "doors", "jammed", 0.0);
+        limits.dedup(SITE, Level::Info, "doors", "jammed", 1.0);
+        limits.dedup(OTHER, Level::Info, "doors", "open", 5.0);
+        limits.dedup(OTHER, Level::Info, "doors", "open", 6.0);
+
+        assert!(limits.take_repeated(9.0, true).is_empty());
+
+        let stale = limits.take_repeated(10.0, true);
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].count, 1);
+
+        assert_eq!(limits.take_repeated(10.0, false).len(), 1);
+        assert!(limits.take_repeated(30.0, false).is_empty());
+    }
+}
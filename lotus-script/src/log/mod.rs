@@ -0,0 +1,198 @@
+//! Logging utilities.
+//!
+//! The macros log with the module path as target. Messages are filtered by level and target
+//! in the script, before they reach the engine, so detailed logging can stay in shipped scripts:
+//!
+//! ```no_run
+//! # use lotus_script::log::{self, LevelFilter};
+//! log::set_level(LevelFilter::Info);
+//! log::set_target_level("my_script::doors", LevelFilter::Trace);
+//!
+//! log::trace!("door {} at {:.2}", 1, 0.5);
+//! log::info!(target: "my_script::ibis", "route {} selected", 42);
+//! ```
+//!
+//! To keep messages logged every tick from flooding the log, use [log_once!], [log_every!] and
+//! [log_dedup!]. [suppressed] returns how many messages they held back.
+//!
+//! By default, messages are written with the `log::write` import every engine has, without
+//! their target. With the `structured-log` feature, they are sent as [LogRecord]s with target,
+//! fields and spans, which requires an engine with the `log::record` import. With the `tracing`
+//! feature, scripts using `tracing` can forward its spans and events with `init_tracing`.
+
+use std::cell::RefCell;
+
+use lotus_script_sys::FfiObject;
+pub use lotus_shared::log::*;
+
+#[cfg(feature = "tracing")]
+mod layer;
+mod limit;
+#[cfg(feature = "tracing")]
+pub use layer::*;
+pub use limit::*;
+
+thread_local! {
+    static FILTER: RefCell<LogFilter> = RefCell::default();
+}
+
+/// Replaces the filter for log messages. By default, everything but [Level::Trace] is logged.
+pub fn set_filter(filter: LogFilter) {
+    FILTER.with_borrow_mut(|f| *f = filter);
+}
+
+/// Returns the current filter for log messages.
+pub fn filter() -> LogFilter {
+    FILTER.with_borrow(|f| f.clone())
+}
+
+/// Sets the level of messages whose target has no level of its own.
+pub fn set_level(level: LevelFilter) {
+    FILTER.with_borrow_mut(|f| f.default = level);
+}
+
+/// Sets the level of a target and everything below it, e.g. `my_script::doors`.
+pub fn set_target_level(target: impl Into<String>, level: LevelFilter) {
+    FILTER.with_borrow_mut(|f| f.set_target(target, level));
+}
+
+/// Returns `true` if a message of the given level and target would be logged.
+pub fn enabled(level: Level, target: &str) -> bool {
+    FILTER.with_borrow(|f| f.enabled(level, target))
+}
+
+/// Write a message with the given level. This is a low-level function, use the [log!], [trace!], [debug!], [info!], [warning!], and [error!] macros instead.
+pub fn write(level: Level, message: impl AsRef<str>) {
+    if enabled(level, "") {
+        write_record(&LogRecord::new(level, message.as_ref()));
+    }
+}
+
+/// Write a message with the given level and target, e.g. a module path.
+pub fn write_target(level: Level, target: &str, message: impl AsRef<str>) {
+    if enabled(level, target) {
+        write_record(&LogRecord::new(level, message.as_ref()).with_target(target));
+    }
+}
+
+/// Write a log record to the engine log without filtering it.
+///
+/// Without the `structured-log` feature, the record is written as text without its target.
+pub fn write_record(record: &LogRecord) {
+    #[cfg(feature = "structured-log")]
+    {
+        let record = FfiObject::new(record);
+        unsafe {
+            lotus_script_sys::log::record(record.packed());
+        }
+    }
+
+    #[cfg(not(feature = "structured-log"))]
+    {
+        // Engines without `log::record` don't know the trace level.
+        let level = record.level.max(Level::Debug) as i32;
+        let message = if record.fields.is_empty() && record.spans.is_empty() {
+            FfiObject::new(&record.message.as_str())
+        } else {
+            let text = LogRecord {
+                target: None,
+                ..record.clone()
+            }
+            .to_string();
+            FfiObject::new(&text.as_str())
+        };
+        unsafe {
+            lotus_script_sys::log::write(level, message.packed());
+        }
+    }
+}
+
+/// Log a message with the given level.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! log {
+    (target: $target:expr, $level:expr, $($arg:tt)+) => {{
+        let level = $level;
+        let target: &str = $target;
+        if $crate::log::enabled(level, target) {
+            $crate::log::write_target(level, target, format!($($arg)+));
+        }
+    }};
+    ($level:expr, $($arg:tt)+) => {
+        $crate::log!(target: module_path!(), $level, $($arg)+)
+    };
+}
+
+/// Log a trace message. Trace messages are not logged unless enabled with [set_level] or
+/// [set_target_level].
+#[doc(hidden)]
+#[macro_export]
+macro_rules! trace {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Trace, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Trace, $($arg)+)
+    };
+}
+
+/// Log a debug message.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! debug {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Debug, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Debug, $($arg)+)
+    };
+}
+
+/// Log a info message.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! info {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Info, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Info, $($arg)+)
+    };
+}
+
+/// Log a warning message.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! warning {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Warn, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Warn, $($arg)+)
+    };
+}
+
+/// Log an error message.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! error {
+    (target: $target:expr, $($arg:tt)+) => {
+        $crate::log!(target: $target, $crate::log::Level::Error, $($arg)+)
+    };
+    ($($arg:tt)+) => {
+        $crate::log!($crate::log::Level::Error, $($arg)+)
+    };
+}
+
+#[doc(inline)]
+pub use debug;
+#[doc(inline)]
+pub use error;
+#[doc(inline)]
+pub use info;
+#[doc(inline)]
+pub use log;
+#[doc(inline)]
+pub use trace;
+#[doc(inline)]
+pub use warning;
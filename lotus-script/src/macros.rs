@@ -8,6 +8,7 @@ macro_rules! script {
 
         #[no_mangle]
         pub fn init() {
+            $crate::message::registry::run_constructors();
             SCRIPT.with(|s| s.lock().unwrap().init());
         }
 
@@ -19,7 +20,11 @@ macro_rules! script {
 
         #[no_mangle]
         pub fn tick() {
+            $crate::time::advance();
+            $crate::log::flush_stale_repeated();
+            $crate::input::recording::next_frame();
             SCRIPT.with(|s| s.lock().unwrap().tick());
+            $crate::graphics::textures::submit_batches();
         }
 
         #[no_mangle]
@@ -27,6 +32,7 @@ macro_rules! script {
             for message in $crate::message::get() {
                 SCRIPT.with(|s| s.lock().unwrap().on_message(message));
             }
+            $crate::graphics::textures::submit_batches();
         }
     };
 }
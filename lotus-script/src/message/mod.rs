@@ -1,12 +1,45 @@
 use lotus_script_sys::FfiObject;
 pub use lotus_shared::message::*;
 
+use crate::log;
+
 mod types;
 pub use types::*;
 
 #[doc(hidden)]
 pub fn get() -> Vec<Message> {
-    let messages = FfiObject::from_packed(unsafe { lotus_script_sys::messages::take() });
+    let messages: Vec<Message> =
+        FfiObject::from_packed(unsafe { lotus_script_sys::messages::take() }).deserialize();
+    let messages = crate::input::recording::action_events(messages);
+    let messages = crate::action::context::filter_events(messages);
+
+    if !trace::is_enabled() {
+        return messages;
+    }
+
+    let mut received = Vec::with_capacity(messages.len());
+
+    for message in messages {
+        trace::record_received(&message);
+
+        match message.value::<trace::DumpMessageTrace>() {
+            Ok(request) => {
+                log_trace();
+                if request.clear {
+                    trace::with_trace(|trace| trace.clear());
+                }
+            }
+            Err(_) => received.push(message),
+        }
+    }
+
+    received
+}
 
-    messages.deserialize()
+/// Writes the message trace to the log as JSON Lines. Does nothing if tracing is disabled.
+/// See [trace] for more information.
+pub fn log_trace() {
+    if let Some(lines) = trace::dump_json_lines() {
+        log::write(log::Level::Info, lines);
+    }
 }
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 ///
 /// A trigger event occurs when an object enters or leaves a sensor's detection area.
 /// Each sensor has a unique index and can detect both entry and exit events.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TriggerEvent {
     /// Unique identifier for the trigger event
     pub id: String,
@@ -30,7 +30,7 @@ impl TriggerEvent {
 message_type!(TriggerEvent, "builtin", "trigger_event");
 
 /// Represents the type of trigger event that occurred.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TriggerKind {
     /// Indicates an object entered the sensor's detection area
     Enter,
@@ -54,7 +54,7 @@ impl TriggerKind {
 ///
 /// Button events capture the state changes of buttons in different
 /// cockpit positions, tracking whether they are pressed or released.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ButtonEvent {
     /// Unique identifier for the button event
     pub id: String,
@@ -74,3 +74,102 @@ message_type!(ButtonEvent, "builtin", "button_event");
 pub struct BatterySwitch(pub bool);
 
 message_type!(BatterySwitch, "builtin", "battery_switch");
+
+/// Represents a change of an analog input in the cockpit, e.g. a lever or a knob.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FloatInputEvent {
+    /// Unique identifier for the input
+    pub id: String,
+    /// Current value of the input
+    pub value: f32,
+    /// Index identifying the cockpit position of this input
+    pub cockpit_index: u8,
+}
+
+message_type!(FloatInputEvent, "builtin", "float_input_event");
+
+/// Sent when the player entered the vehicle.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VehicleEnteredEvent {
+    /// Unique identifier for the event
+    pub id: String,
+    /// Current speed in meters per second
+    pub speed_mps: f32,
+}
+
+message_type!(VehicleEnteredEvent, "builtin", "vehicle_entered_event");
+
+/// A value broadcast on a bus by a legacy (non-script) vehicle.
+///
+/// Scripts should use [message_type!] with a bus instead of broadcasting values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BroadcastEvent {
+    /// The bus the value was broadcast on
+    pub bus_id: String,
+    /// Unique identifier for the value
+    pub id: String,
+    /// The broadcast value
+    pub value: BroadcastValue,
+}
+
+message_type!(BroadcastEvent, "builtin", "broadcast_event");
+
+/// The value of a [BroadcastEvent].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum BroadcastValue {
+    Single(f32),
+    String(String),
+    Integer(i32),
+    Serializeable(serde_json::Value),
+}
+
+/// A legacy message sent between a vehicle and its modules.
+///
+/// Scripts should use [message_type!] with [MessageTarget::Parent](super::MessageTarget::Parent)
+/// or [MessageTarget::ChildByIndex](super::MessageTarget::ChildByIndex) instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReceiveMessageEvent {
+    /// Index of the module slot of the child
+    pub slot_index: i32,
+    /// Unique identifier for the message
+    pub id: String,
+    /// Whether the message was sent by the parent or a child
+    pub kind: ReceiveMessageKind,
+    /// The message value
+    pub value: ReceiveMessageValue,
+}
+
+message_type!(ReceiveMessageEvent, "builtin", "receive_message_event");
+
+/// The sender of a [ReceiveMessageEvent].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReceiveMessageKind {
+    /// The message was sent by the parent vehicle
+    Parent,
+    /// The message was sent by a child module
+    Child,
+}
+
+/// The value of a [ReceiveMessageEvent].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ReceiveMessageValue {
+    Single(f32),
+    String(String),
+    Integer(i32),
+}
+
+#[cfg(test)]
+mod tests {
+    use lotus_shared::message::{registry, MessageType};
+
+    use super::*;
+
+    #[test]
+    fn test_registered() {
+        let registry = registry::registered();
+
+        assert!(registry.find(&TriggerEvent::MESSAGE_META).is_some());
+        assert!(registry.find(&BatterySwitch::MESSAGE_META).is_some());
+        assert!(registry.get("builtin", "vehicle_entered_event").is_some());
+    }
+}
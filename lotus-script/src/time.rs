@@ -1,3 +1,5 @@
+use std::cell::Cell;
+
 /// Get the current delta time in seconds.
 pub fn delta() -> f32 {
     unsafe { lotus_script_sys::time::delta_f64() as f32 }
@@ -8,6 +10,21 @@ pub fn delta_f64() -> f64 {
     unsafe { lotus_script_sys::time::delta_f64() }
 }
 
+thread_local! {
+    static ELAPSED: Cell<f64> = const { Cell::new(0.0) };
+}
+
+/// Get the simulation time in seconds since the script started, i.e. the sum of all deltas.
+pub fn elapsed() -> f64 {
+    ELAPSED.get()
+}
+
+/// Adds the current delta to [elapsed]. Called by the [script](crate::script) macro before every tick.
+#[doc(hidden)]
+pub fn advance() {
+    ELAPSED.set(ELAPSED.get() + delta_f64());
+}
+
 /// Get the number of ticks the script has been alive.
 pub fn ticks_alive() -> u64 {
     unsafe { lotus_script_sys::time::ticks_alive() }
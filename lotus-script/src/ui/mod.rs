@@ -0,0 +1,497 @@
+//! A small retained-mode UI for cockpit displays like ticket machines or driver displays.
+//!
+//! The UI is described once as a tree of [Widget]s, laid out in rows and columns, and drawn onto a
+//! [Texture]. Widgets are updated by their id, and only changed widgets are redrawn.
+//! Clicks on the model are routed to the widgets using the [uv](crate::input::ActionState::uv)
+//! coordinate of an action.
+//!
+//! # Example
+//! ```no_run
+//! # use lotus_script::{font::BitmapFont, graphics::textures::Texture, ui::*};
+//! # fn example(font: BitmapFont, texture: &mut Texture) {
+//! let mut ui = Ui::new(
+//!     (128, 64),
+//!     font,
+//!     Widget::column([
+//!         Widget::label("Zones").with_length(Length::Fixed(12)),
+//!         Widget::row([
+//!             Widget::button("1").with_id("zone_1"),
+//!             Widget::button("2").with_id("zone_2"),
+//!         ])
+//!         .with_spacing(2),
+//!         Widget::progress_bar(0.0).with_id("printing").with_length(Length::Fixed(6)),
+//!     ])
+//!     .with_padding(2)
+//!     .with_spacing(2),
+//! );
+//!
+//! // In `tick`:
+//! for event in ui.poll("touch") {
+//!     if let UiEvent::Clicked { id } = event {
+//!         ui.set_progress("printing", 0.5);
+//!     }
+//! }
+//! ui.draw(texture);
+//! # }
+//! ```
+
+use lotus_shared::{
+    graphics::Color,
+    input::{ActionState, ActionStateKind},
+    math::{Rectangle, UVec2, Vec2},
+};
+
+use crate::{font::BitmapFont, graphics::textures::Texture};
+
+mod widget;
+use widget::{contains, WidgetKind};
+pub use widget::{Length, Widget};
+
+/// The colors of the UI.
+#[derive(Debug, Clone, Copy)]
+pub struct Theme {
+    pub background: Color,
+    pub text: Color,
+    pub text_disabled: Color,
+    pub button: Color,
+    pub button_pressed: Color,
+    pub button_disabled: Color,
+    /// The color of selected list items and progress bars.
+    pub accent: Color,
+    pub border: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            background: Color::BLACK,
+            text: Color::WHITE,
+            text_disabled: Color::rgb(128, 128, 128),
+            button: Color::rgb(48, 48, 64),
+            button_pressed: Color::rgb(96, 96, 128),
+            button_disabled: Color::rgb(32, 32, 32),
+            accent: Color::rgb(0, 120, 215),
+            border: Color::WHITE,
+        }
+    }
+}
+
+/// An interaction with a widget that has an id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UiEvent {
+    /// A button was clicked.
+    Clicked { id: String },
+    /// An item of a list was clicked.
+    Selected { id: String, index: usize },
+}
+
+/// A tree of widgets drawn onto a texture.
+pub struct Ui {
+    size: UVec2,
+    font: BitmapFont,
+    theme: Theme,
+    root: Widget,
+    needs_layout: bool,
+    /// Set when the whole texture has to be redrawn, e.g. after a new layout.
+    needs_redraw: bool,
+    pressed: Option<UVec2>,
+}
+
+impl Ui {
+    /// Creates a UI of the given size in pixels, usually the size of the texture.
+    pub fn new(size: impl Into<UVec2>, font: BitmapFont, root: Widget) -> Self {
+        Self {
+            size: size.into(),
+            font,
+            theme: Theme::default(),
+            root,
+            needs_layout: true,
+            needs_redraw: true,
+            pressed: None,
+        }
+    }
+
+    /// Sets the colors of the UI.
+    pub fn with_theme(mut self, theme: Theme) -> Self {
+        self.set_theme(theme);
+        self
+    }
+
+    /// Sets the colors of the UI and redraws everything.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.needs_redraw = true;
+    }
+
+    /// Replaces the widget tree and redraws everything.
+    pub fn set_root(&mut self, root: Widget) {
+        self.root = root;
+        self.needs_layout = true;
+        self.pressed = None;
+    }
+
+    /// Returns the widget with the given id.
+    pub fn widget_mut(&mut self, id: &str) -> Option<&mut Widget> {
+        self.root.find_mut(id)
+    }
+
+    /// Sets the text of a label or button.
+    pub fn set_text(&mut self, id: &str, text: impl Into<String>) {
+        let text = text.into();
+        self.update(id, |kind| match kind {
+            WidgetKind::Label { text: t, .. } | WidgetKind::Button { text: t, .. } => {
+                replace(t, text)
+            }
+            _ => false,
+        });
+    }
+
+    /// Enables or disables a button. Disabled buttons don't send events.
+    pub fn set_enabled(&mut self, id: &str, enabled: bool) {
+        self.update(id, |kind| match kind {
+            WidgetKind::Button { enabled: e, .. } => replace(e, enabled),
+            _ => false,
+        });
+    }
+
+    /// Sets the value of a progress bar, between `0.0` and `1.0`.
+    pub fn set_progress(&mut self, id: &str, value: f32) {
+        self.update(id, |kind| match kind {
+            WidgetKind::ProgressBar { value: v, .. } => replace(v, value.clamp(0.0, 1.0)),
+            _ => false,
+        });
+    }
+
+    /// Replaces the items of a list, clearing the selection if it is out of range.
+    pub fn set_items(&mut self, id: &str, items: impl IntoIterator<Item = impl Into<String>>) {
+        let items = items.into_iter().map(Into::into).collect::<Vec<_>>();
+        self.update(id, |kind| match kind {
+            WidgetKind::List {
+                items: i,
+                selected,
+                scroll,
+                ..
+            } => {
+                *selected = selected.filter(|s| *s < items.len());
+                *scroll = (*scroll).min(items.len().saturating_sub(1));
+                replace(i, items)
+            }
+            _ => false,
+        });
+    }
+
+    /// Selects an item of a list.
+    pub fn set_selected(&mut self, id: &str, index: Option<usize>) {
+        self.update(id, |kind| match kind {
+            WidgetKind::List {
+                items, selected, ..
+            } => replace(selected, index.filter(|i| *i < items.len())),
+            _ => false,
+        });
+    }
+
+    /// Scrolls a list by the given number of items.
+    pub fn scroll(&mut self, id: &str, items: isize) {
+        self.update(id, |kind| match kind {
+            WidgetKind::List {
+                items: i, scroll, ..
+            } => {
+                let max = i.len().saturating_sub(1);
+                replace(scroll, scroll.saturating_add_signed(items).min(max))
+            }
+            _ => false,
+        });
+    }
+
+    /// Returns the selected item of a list.
+    pub fn selected(&self, id: &str) -> Option<usize> {
+        match &self.root.find(id)?.kind {
+            WidgetKind::List { selected, .. } => *selected,
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, id: &str, f: impl FnOnce(&mut WidgetKind) -> bool) {
+        if let Some(widget) = self.root.find_mut(id) {
+            if f(&mut widget.kind) {
+                widget.dirty = true;
+            }
+        }
+    }
+
+    /// Routes the state of an action, e.g. a click on the display, to the widgets.
+    /// Widgets are activated when the action is released over the widget it was pressed on.
+    /// Releasing the action off the display cancels the press.
+    pub fn handle_action(&mut self, state: &ActionState) -> Vec<UiEvent> {
+        let pos = state.uv.and_then(|uv| self.uv_to_pixel(uv));
+
+        match (state.kind, pos) {
+            (ActionStateKind::JustPressed, Some(pos)) => {
+                self.press(pos);
+                Vec::new()
+            }
+            (ActionStateKind::JustReleased, Some(pos)) => self.release(pos),
+            (ActionStateKind::JustReleased, None) => {
+                self.cancel();
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Fetches the state of the given action and routes it to the widgets. See [Ui::handle_action].
+    pub fn poll(&mut self, action: &str) -> Vec<UiEvent> {
+        self.handle_action(&crate::action::state(action))
+    }
+
+    /// Presses the widget at the given pixel.
+    pub fn press(&mut self, pos: UVec2) {
+        self.release_button();
+        self.layout_if_needed();
+        self.pressed = Some(pos);
+
+        if let Some(widget) = self.root.hit_mut(pos) {
+            if let WidgetKind::Button {
+                enabled: true,
+                pressed,
+                ..
+            } = &mut widget.kind
+            {
+                *pressed = true;
+                widget.dirty = true;
+            }
+        }
+    }
+
+    /// Releases the pressed widget at the given pixel, returning the resulting events.
+    pub fn release(&mut self, pos: UVec2) -> Vec<UiEvent> {
+        let Some(pressed_at) = self.pressed.take() else {
+            return Vec::new();
+        };
+        self.release_button();
+
+        let Some(widget) = self.root.hit_mut(pos) else {
+            return Vec::new();
+        };
+        if !contains(widget.rect, pressed_at) {
+            return Vec::new();
+        }
+        let Some(id) = widget.id.clone() else {
+            return Vec::new();
+        };
+
+        match &widget.kind {
+            WidgetKind::Button { enabled: true, .. } => vec![UiEvent::Clicked { id }],
+            WidgetKind::List { .. } => match widget.list_item_at(pos) {
+                Some(index) => {
+                    if let WidgetKind::List { selected, .. } = &mut widget.kind {
+                        *selected = Some(index);
+                    }
+                    widget.dirty = true;
+                    vec![UiEvent::Selected { id, index }]
+                }
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Releases the pressed widget without activating it.
+    pub fn cancel(&mut self) {
+        self.release_button();
+        self.pressed = None;
+    }
+
+    fn release_button(&mut self) {
+        let Some(pressed_at) = self.pressed else {
+            return;
+        };
+
+        if let Some(widget) = self.root.hit_mut(pressed_at) {
+            if let WidgetKind::Button { pressed, .. } = &mut widget.kind {
+                if replace(pressed, false) {
+                    widget.dirty = true;
+                }
+            }
+        }
+    }
+
+    /// Converts a uv coordinate on the display, `(0, 0)` being the top left corner, to a pixel.
+    pub fn uv_to_pixel(&self, uv: Vec2) -> Option<UVec2> {
+        if !(0.0..1.0).contains(&uv.x) || !(0.0..1.0).contains(&uv.y) {
+            return None;
+        }
+
+        Some((uv * self.size.as_vec2()).as_uvec2())
+    }
+
+    fn layout_if_needed(&mut self) {
+        if self.needs_layout {
+            self.root
+                .layout(Rectangle::from_size(UVec2::ZERO, self.size));
+            self.needs_layout = false;
+            self.needs_redraw = true;
+        }
+    }
+
+    /// Draws all widgets that changed since the last call. Returns the redrawn regions.
+    pub fn draw(&mut self, texture: &mut Texture) -> Vec<Rectangle> {
+        let mut regions = Vec::new();
+        self.layout_if_needed();
+
+        if self.needs_redraw {
+            self.needs_redraw = false;
+            texture.clear(self.theme.background);
+            regions.push(Rectangle::from_size(UVec2::ZERO, self.size));
+            self.root
+                .draw_dirty(texture, &self.font, &self.theme, &mut Vec::new());
+        } else {
+            self.root
+                .draw_dirty(texture, &self.font, &self.theme, &mut regions);
+        }
+
+        regions
+    }
+}
+
+fn replace<T: PartialEq>(target: &mut T, value: T) -> bool {
+    if *target == value {
+        false
+    } else {
+        *target = value;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lotus_shared::{content::ContentId, font::BitmapFontProperties};
+
+    use super::*;
+
+    fn ui(root: Widget) -> Ui {
+        let properties = BitmapFontProperties {
+            horizontal_distance: 1,
+            vertical_size: 8,
+            letters: Default::default(),
+        };
+        Ui::new(
+            (100, 50),
+            BitmapFont::from_properties(ContentId::default(), properties),
+            root,
+        )
+    }
+
+    fn action(kind: ActionStateKind, uv: Option<Vec2>) -> ActionState {
+        ActionState {
+            kind,
+            cockpit_index: None,
+            uv,
+        }
+    }
+
+    fn rect(x: u32, y: u32, width: u32, height: u32) -> Rectangle {
+        Rectangle::from_size(UVec2::new(x, y), UVec2::new(width, height))
+    }
+
+    #[test]
+    fn test_layout() {
+        let mut root = Widget::row([
+            Widget::spacer()
+                .with_id("fixed")
+                .with_length(Length::Fixed(10)),
+            Widget::spacer().with_id("one"),
+            Widget::spacer().with_id("two").with_length(Length::Fill(2)),
+        ])
+        .with_padding(2)
+        .with_spacing(3);
+        root.layout(rect(0, 0, 100, 50));
+
+        // 96 pixels minus 10 fixed and 6 spacing leave 80, split 1:2 with the remainder last.
+        let rect_of = |id| root.find(id).unwrap().rect();
+        assert_eq!(rect_of("fixed"), rect(2, 2, 10, 46));
+        assert_eq!(rect_of("one"), rect(15, 2, 26, 46));
+        assert_eq!(rect_of("two"), rect(44, 2, 54, 46));
+    }
+
+    #[test]
+    fn test_layout_overflow() {
+        let mut root = Widget::column([
+            Widget::spacer().with_id("a").with_length(Length::Fixed(40)),
+            Widget::spacer().with_id("b").with_length(Length::Fixed(40)),
+            Widget::spacer().with_id("c"),
+        ]);
+        root.layout(rect(0, 0, 100, 50));
+
+        assert_eq!(root.find("b").unwrap().rect(), rect(0, 40, 100, 10));
+        assert_eq!(root.find("c").unwrap().rect(), rect(0, 50, 100, 0));
+    }
+
+    #[test]
+    fn test_click() {
+        let mut ui = ui(Widget::row([
+            Widget::button("1").with_id("one"),
+            Widget::button("2").with_id("two"),
+        ]));
+        let left = Some(Vec2::new(0.25, 0.5));
+        let right = Some(Vec2::new(0.75, 0.5));
+
+        assert!(ui
+            .handle_action(&action(ActionStateKind::JustPressed, left))
+            .is_empty());
+        assert_eq!(
+            ui.handle_action(&action(ActionStateKind::JustReleased, left)),
+            [UiEvent::Clicked {
+                id: "one".to_string()
+            }]
+        );
+
+        // Released over another widget.
+        ui.handle_action(&action(ActionStateKind::JustPressed, left));
+        assert!(ui
+            .handle_action(&action(ActionStateKind::JustReleased, right))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_release_off_display() {
+        let mut ui = ui(Widget::row([Widget::button("1").with_id("one")]));
+        let center = Some(Vec2::new(0.5, 0.5));
+
+        ui.handle_action(&action(ActionStateKind::JustPressed, center));
+        assert!(matches!(
+            ui.root.find("one").unwrap().kind,
+            WidgetKind::Button { pressed: true, .. }
+        ));
+
+        assert!(ui
+            .handle_action(&action(ActionStateKind::JustReleased, None))
+            .is_empty());
+        assert!(matches!(
+            ui.root.find("one").unwrap().kind,
+            WidgetKind::Button { pressed: false, .. }
+        ));
+        assert!(ui
+            .handle_action(&action(ActionStateKind::JustReleased, center))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_select_list_item() {
+        let mut ui = ui(Widget::column([
+            Widget::list(["a", "b", "c"], 10).with_id("list")
+        ]));
+        let second = Some(Vec2::new(0.5, 0.3));
+
+        ui.handle_action(&action(ActionStateKind::JustPressed, second));
+        assert!(!ui.needs_layout);
+        assert_eq!(
+            ui.handle_action(&action(ActionStateKind::JustReleased, second)),
+            [UiEvent::Selected {
+                id: "list".to_string(),
+                index: 1
+            }]
+        );
+        assert_eq!(ui.selected("list"), Some(1));
+    }
+}
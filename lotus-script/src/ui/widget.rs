@@ -0,0 +1,487 @@
+use lotus_shared::{
+    content::ContentId,
+    font::layout::TextLayoutOptions,
+    graphics::{
+        textures::{AlphaMode, DrawTextureOpts},
+        Color, TextHorizontalAlignment, TextVerticalAlignment,
+    },
+    math::{Rectangle, UVec2},
+};
+
+use super::Theme;
+use crate::{font::BitmapFont, graphics::textures::Texture};
+
+/// The size of a widget along the main axis of its container.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Length {
+    /// A fixed size in pixels.
+    Fixed(u32),
+    /// A share of the space left after fixed widgets and spacing, proportional to the weight.
+    Fill(u32),
+}
+
+impl Default for Length {
+    fn default() -> Self {
+        Self::Fill(1)
+    }
+}
+
+/// A node of the widget tree. Create widgets with the constructors, e.g. [Widget::button].
+#[derive(Debug, Clone)]
+pub struct Widget {
+    pub(super) id: Option<String>,
+    pub(super) length: Length,
+    pub(super) kind: WidgetKind,
+    pub(super) rect: Rectangle,
+    pub(super) dirty: bool,
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum WidgetKind {
+    Label {
+        text: String,
+        color: Option<Color>,
+        alignment: TextHorizontalAlignment,
+    },
+    Button {
+        text: String,
+        enabled: bool,
+        pressed: bool,
+    },
+    List {
+        items: Vec<String>,
+        selected: Option<usize>,
+        scroll: usize,
+        item_height: u32,
+    },
+    ProgressBar {
+        value: f32,
+        color: Option<Color>,
+    },
+    Image {
+        texture: ContentId,
+        source: Option<Rectangle>,
+    },
+    Spacer,
+    Container {
+        direction: Direction,
+        children: Vec<Widget>,
+        spacing: u32,
+        padding: u32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Direction {
+    Row,
+    Column,
+}
+
+impl Widget {
+    fn new(kind: WidgetKind) -> Self {
+        Self {
+            id: None,
+            length: Length::default(),
+            kind,
+            rect: Rectangle::default(),
+            dirty: true,
+        }
+    }
+
+    /// A single line of text.
+    pub fn label(text: impl Into<String>) -> Self {
+        Self::new(WidgetKind::Label {
+            text: text.into(),
+            color: None,
+            alignment: TextHorizontalAlignment::Left,
+        })
+    }
+
+    /// A button sending [UiEvent::Clicked](super::UiEvent::Clicked) when clicked.
+    pub fn button(text: impl Into<String>) -> Self {
+        Self::new(WidgetKind::Button {
+            text: text.into(),
+            enabled: true,
+            pressed: false,
+        })
+    }
+
+    /// A scrollable list sending [UiEvent::Selected](super::UiEvent::Selected) when an item is clicked.
+    pub fn list(items: impl IntoIterator<Item = impl Into<String>>, item_height: u32) -> Self {
+        Self::new(WidgetKind::List {
+            items: items.into_iter().map(Into::into).collect(),
+            selected: None,
+            scroll: 0,
+            item_height: item_height.max(1),
+        })
+    }
+
+    /// A bar filled according to a value between `0.0` and `1.0`.
+    pub fn progress_bar(value: f32) -> Self {
+        Self::new(WidgetKind::ProgressBar {
+            value: value.clamp(0.0, 1.0),
+            color: None,
+        })
+    }
+
+    /// A content texture scaled to the size of the widget, e.g. a frame of a
+    /// [SpriteAtlas](crate::graphics::atlas::SpriteAtlas).
+    pub fn image(texture: ContentId, source: Option<Rectangle>) -> Self {
+        Self::new(WidgetKind::Image { texture, source })
+    }
+
+    /// Empty space.
+    pub fn spacer() -> Self {
+        Self::new(WidgetKind::Spacer)
+    }
+
+    /// Lays out the children from left to right.
+    pub fn row(children: impl IntoIterator<Item = Widget>) -> Self {
+        Self::container(Direction::Row, children)
+    }
+
+    /// Lays out the children from top to bottom.
+    pub fn column(children: impl IntoIterator<Item = Widget>) -> Self {
+        Self::container(Direction::Column, children)
+    }
+
+    fn container(direction: Direction, children: impl IntoIterator<Item = Widget>) -> Self {
+        Self::new(WidgetKind::Container {
+            direction,
+            children: children.into_iter().collect(),
+            spacing: 0,
+            padding: 0,
+        })
+    }
+
+    /// Sets the id used to update the widget and reported in events.
+    pub fn with_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets the size of the widget along the main axis of its container. Defaults to `Length::Fill(1)`.
+    pub fn with_length(mut self, length: Length) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Sets the space between the children of a container.
+    pub fn with_spacing(mut self, spacing: u32) -> Self {
+        if let WidgetKind::Container { spacing: s, .. } = &mut self.kind {
+            *s = spacing;
+        }
+        self
+    }
+
+    /// Sets the space around the children of a container.
+    pub fn with_padding(mut self, padding: u32) -> Self {
+        if let WidgetKind::Container { padding: p, .. } = &mut self.kind {
+            *p = padding;
+        }
+        self
+    }
+
+    /// Sets the color of a label or progress bar, overriding the theme.
+    pub fn with_color(mut self, color: Color) -> Self {
+        match &mut self.kind {
+            WidgetKind::Label { color: c, .. } | WidgetKind::ProgressBar { color: c, .. } => {
+                *c = Some(color)
+            }
+            _ => {}
+        }
+        self
+    }
+
+    /// Sets the horizontal alignment of a label.
+    pub fn with_alignment(mut self, alignment: TextHorizontalAlignment) -> Self {
+        if let WidgetKind::Label { alignment: a, .. } = &mut self.kind {
+            *a = alignment;
+        }
+        self
+    }
+
+    /// Returns the id of the widget.
+    pub fn id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the rectangle of the widget from the last layout.
+    pub fn rect(&self) -> Rectangle {
+        self.rect
+    }
+
+    pub(super) fn find(&self, id: &str) -> Option<&Widget> {
+        if self.id.as_deref() == Some(id) {
+            return Some(self);
+        }
+
+        match &self.kind {
+            WidgetKind::Container { children, .. } => {
+                children.iter().find_map(|child| child.find(id))
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) fn find_mut(&mut self, id: &str) -> Option<&mut Widget> {
+        if self.id.as_deref() == Some(id) {
+            return Some(self);
+        }
+
+        match &mut self.kind {
+            WidgetKind::Container { children, .. } => {
+                children.iter_mut().find_map(|child| child.find_mut(id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the leaf widget at the given position.
+    pub(super) fn hit_mut(&mut self, pos: UVec2) -> Option<&mut Widget> {
+        if !contains(self.rect, pos) {
+            return None;
+        }
+
+        if !matches!(self.kind, WidgetKind::Container { .. }) {
+            return Some(self);
+        }
+
+        match &mut self.kind {
+            WidgetKind::Container { children, .. } => {
+                children.iter_mut().find_map(|child| child.hit_mut(pos))
+            }
+            _ => None,
+        }
+    }
+
+    pub(super) fn layout(&mut self, rect: Rectangle) {
+        self.rect = rect;
+        self.dirty = true;
+
+        let WidgetKind::Container {
+            direction,
+            children,
+            spacing,
+            padding,
+        } = &mut self.kind
+        else {
+            return;
+        };
+
+        let inner = shrink(rect, *padding);
+        let main_size = match direction {
+            Direction::Row => inner.width(),
+            Direction::Column => inner.height(),
+        };
+
+        let gaps = *spacing * (children.len() as u32).saturating_sub(1);
+        let fixed: u32 = children
+            .iter()
+            .map(|child| match child.length {
+                Length::Fixed(size) => size,
+                Length::Fill(_) => 0,
+            })
+            .sum();
+        let weights: u32 = children
+            .iter()
+            .map(|child| match child.length {
+                Length::Fixed(_) => 0,
+                Length::Fill(weight) => weight,
+            })
+            .sum();
+        let free = main_size.saturating_sub(fixed + gaps);
+
+        let mut offset = 0;
+        let mut distributed = 0;
+        let mut remaining_weights = weights;
+        for child in children.iter_mut() {
+            let size = match child.length {
+                Length::Fixed(size) => size,
+                // The last filling child gets the rounding remainder.
+                Length::Fill(weight) if weight == remaining_weights => free - distributed,
+                Length::Fill(weight) => free * weight / weights.max(1),
+            };
+            if let Length::Fill(weight) = child.length {
+                distributed += size;
+                remaining_weights -= weight;
+            }
+
+            let size = size.min(main_size.saturating_sub(offset));
+            let child_rect = match direction {
+                Direction::Row => Rectangle::from_size(
+                    inner.start() + UVec2::new(offset, 0),
+                    UVec2::new(size, inner.height()),
+                ),
+                Direction::Column => Rectangle::from_size(
+                    inner.start() + UVec2::new(0, offset),
+                    UVec2::new(inner.width(), size),
+                ),
+            };
+            child.layout(child_rect);
+
+            offset = (offset + size + *spacing).min(main_size);
+        }
+    }
+
+    /// Redraws dirty leaf widgets and collects their rectangles.
+    pub(super) fn draw_dirty(
+        &mut self,
+        texture: &mut Texture,
+        font: &BitmapFont,
+        theme: &Theme,
+        regions: &mut Vec<Rectangle>,
+    ) {
+        if let WidgetKind::Container { children, .. } = &mut self.kind {
+            for child in children {
+                child.draw_dirty(texture, font, theme, regions);
+            }
+            self.dirty = false;
+            return;
+        }
+
+        if !self.dirty {
+            return;
+        }
+
+        self.dirty = false;
+        if self.rect.width() == 0 || self.rect.height() == 0 {
+            return;
+        }
+
+        texture.draw_rect(self.rect.start(), self.rect.end(), theme.background);
+        self.draw(texture, font, theme);
+        regions.push(self.rect);
+    }
+
+    fn draw(&self, texture: &mut Texture, font: &BitmapFont, theme: &Theme) {
+        let rect = self.rect;
+
+        match &self.kind {
+            WidgetKind::Label {
+                text,
+                color,
+                alignment,
+            } => draw_text(
+                texture,
+                font,
+                text,
+                rect,
+                *alignment,
+                color.unwrap_or(theme.text),
+            ),
+            WidgetKind::Button {
+                text,
+                enabled,
+                pressed,
+            } => {
+                let (fill, text_color) = match (enabled, pressed) {
+                    (false, _) => (theme.button_disabled, theme.text_disabled),
+                    (true, true) => (theme.button_pressed, theme.text),
+                    (true, false) => (theme.button, theme.text),
+                };
+                texture.draw_rect(rect.start(), rect.end(), fill);
+                draw_text(
+                    texture,
+                    font,
+                    text,
+                    rect,
+                    TextHorizontalAlignment::Center,
+                    text_color,
+                );
+            }
+            WidgetKind::List {
+                items,
+                selected,
+                scroll,
+                item_height,
+            } => {
+                let visible = (rect.height() / item_height) as usize;
+                for (index, item) in items.iter().enumerate().skip(*scroll).take(visible) {
+                    let top = rect.start().y + (index - scroll) as u32 * item_height;
+                    let item_rect = Rectangle::new(
+                        UVec2::new(rect.start().x, top),
+                        UVec2::new(rect.end().x, top + item_height),
+                    );
+
+                    if *selected == Some(index) {
+                        texture.draw_rect(item_rect.start(), item_rect.end(), theme.accent);
+                    }
+                    draw_text(
+                        texture,
+                        font,
+                        item,
+                        item_rect,
+                        TextHorizontalAlignment::Left,
+                        theme.text,
+                    );
+                }
+            }
+            WidgetKind::ProgressBar { value, color } => {
+                let filled = (rect.width() as f32 * value).round() as u32;
+                if filled > 0 {
+                    texture.draw_rect(
+                        rect.start(),
+                        UVec2::new(rect.start().x + filled, rect.end().y),
+                        color.unwrap_or(theme.accent),
+                    );
+                }
+                texture.draw_rect_outline(rect.start(), rect.end(), 1.0, theme.border);
+            }
+            WidgetKind::Image {
+                texture: id,
+                source,
+            } => {
+                let options = DrawTextureOpts {
+                    source_rect: *source,
+                    target_rect: Some(rect),
+                    alpha_mode: AlphaMode::Blend,
+                };
+                texture.draw_content_texture(*id, options);
+            }
+            WidgetKind::Spacer | WidgetKind::Container { .. } => {}
+        }
+    }
+
+    /// Returns the index of the list item at the given position.
+    pub(super) fn list_item_at(&self, pos: UVec2) -> Option<usize> {
+        let WidgetKind::List {
+            items,
+            scroll,
+            item_height,
+            ..
+        } = &self.kind
+        else {
+            return None;
+        };
+
+        let index = scroll + ((pos.y - self.rect.start().y) / item_height) as usize;
+        (index < items.len()).then_some(index)
+    }
+}
+
+fn draw_text(
+    texture: &mut Texture,
+    font: &BitmapFont,
+    text: &str,
+    rect: Rectangle,
+    alignment: TextHorizontalAlignment,
+    color: Color,
+) {
+    let options =
+        TextLayoutOptions::default().with_alignment(alignment, TextVerticalAlignment::Center);
+    let layout = font.layout(text, rect, &options);
+
+    texture.draw_text_layout(font.content_id(), &layout, Some(color), AlphaMode::Blend);
+}
+
+pub(super) fn contains(rect: Rectangle, pos: UVec2) -> bool {
+    pos.cmpge(rect.start()).all() && pos.cmplt(rect.end()).all()
+}
+
+fn shrink(rect: Rectangle, padding: u32) -> Rectangle {
+    let start = (rect.start() + padding).min(rect.end());
+    let end = rect.end().saturating_sub(UVec2::splat(padding)).max(start);
+
+    Rectangle::new(start, end)
+}
@@ -2,44 +2,290 @@ use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
 use crate::{
-    input::{ActionState, KeyCode},
-    message::{MessageMeta, MessageType},
+    input::{ActionState, AxisBinding, AxisOptions, InputButton, KeyChord, KeyCode},
+    message_type,
 };
 
 /// Describes an action that can be registered with the engine.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "RawRegisterAction")]
 pub struct RegisterAction {
     pub id: String,
-    pub default_key: KeyCode,
+    /// The first default binding if it is a key without modifiers. Still sent for engines that
+    /// only know a single default key.
+    #[deprecated(note = "use `default_bindings`")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_key: Option<KeyCode>,
+    /// The keys, mouse buttons and chords that trigger the action until the user changes them.
+    /// Any of them triggers the action. Can be empty for actions the user has to bind.
+    pub default_bindings: Vec<KeyChord>,
+    /// Set for axis actions, which have a continuous value instead of a pressed state.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub axis: Option<RegisterAxis>,
+    /// The input context the action belongs to. Actions without a context are always active,
+    /// unless blocked by an active context. Contexts are handled by the script, not the engine.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// The name shown in the options menu. Defaults to the id.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    /// The group the action is listed under in the options menu.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// A longer explanation shown in the options menu.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
 }
 
+/// Describes the analog part of an axis action, e.g. a master controller on a throttle quadrant.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterAxis {
+    /// The axes that provide the value until the user changes them.
+    pub default_bindings: Vec<AxisBinding>,
+    /// The default dead zone, inversion and curve. The engine applies them before the value
+    /// reaches the script.
+    pub options: AxisOptions,
+}
+
+/// A [RegisterAction] as sent by scripts, which may only have a `default_key`.
+#[derive(Deserialize)]
+struct RawRegisterAction {
+    id: String,
+    #[serde(default)]
+    default_key: Option<KeyCode>,
+    #[serde(default)]
+    default_bindings: Option<Vec<KeyChord>>,
+    #[serde(default)]
+    axis: Option<RegisterAxis>,
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(default)]
+    display_name: Option<String>,
+    #[serde(default)]
+    category: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+impl From<RawRegisterAction> for RegisterAction {
+    #[allow(deprecated)]
+    fn from(raw: RawRegisterAction) -> Self {
+        let default_bindings = raw
+            .default_bindings
+            .unwrap_or_else(|| raw.default_key.into_iter().map(Into::into).collect());
+
+        Self {
+            id: raw.id,
+            default_key: raw.default_key.or_else(|| legacy_key(&default_bindings)),
+            default_bindings,
+            axis: raw.axis,
+            context: raw.context,
+            display_name: raw.display_name,
+            category: raw.category,
+            description: raw.description,
+        }
+    }
+}
+
+/// Returns the first binding if it is a plain key, the only kind of binding older engines know.
+fn legacy_key(bindings: &[KeyChord]) -> Option<KeyCode> {
+    match bindings.first()? {
+        KeyChord {
+            button: InputButton::Key(key),
+            modifiers,
+        } if modifiers.is_empty() => Some(*key),
+        _ => None,
+    }
+}
+
+#[allow(deprecated)]
 impl RegisterAction {
-    pub fn new(id: String, default_key: KeyCode) -> Self {
-        Self { id, default_key }
+    pub fn new(id: String, default_key: impl Into<KeyChord>) -> Self {
+        Self::with_bindings(id, [default_key])
+    }
+
+    pub fn with_bindings(
+        id: String,
+        default_bindings: impl IntoIterator<Item = impl Into<KeyChord>>,
+    ) -> Self {
+        let default_bindings: Vec<KeyChord> =
+            default_bindings.into_iter().map(Into::into).collect();
+        Self {
+            id,
+            default_key: legacy_key(&default_bindings),
+            default_bindings,
+            axis: None,
+            context: None,
+            display_name: None,
+            category: None,
+            description: None,
+        }
+    }
+
+    /// Creates an axis action with a default binding.
+    pub fn axis(id: String, default_binding: impl Into<AxisBinding>, options: AxisOptions) -> Self {
+        Self {
+            axis: Some(RegisterAxis {
+                default_bindings: vec![default_binding.into()],
+                options,
+            }),
+            ..Self::with_bindings(id, std::iter::empty::<KeyChord>())
+        }
+    }
+
+    /// Adds another default binding.
+    pub fn with_binding(mut self, binding: impl Into<KeyChord>) -> Self {
+        self.default_bindings.push(binding.into());
+        self.default_key = legacy_key(&self.default_bindings);
+        self
+    }
+
+    /// Puts the action into the given input context.
+    pub fn with_context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+
+    pub fn with_display_name(mut self, display_name: impl Into<String>) -> Self {
+        self.display_name = Some(display_name.into());
+        self
+    }
+
+    pub fn with_category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Adds another default axis binding. Does nothing if this isn't an axis action.
+    pub fn with_axis_binding(mut self, binding: impl Into<AxisBinding>) -> Self {
+        if let Some(axis) = &mut self.axis {
+            axis.default_bindings.push(binding.into());
+        }
+        self
     }
 }
 
-impl<T: Into<String>> From<(T, KeyCode)> for RegisterAction {
-    fn from((id, default_key): (T, KeyCode)) -> Self {
+impl<T: Into<String>, K: Into<KeyChord>> From<(T, K)> for RegisterAction {
+    fn from((id, default_key): (T, K)) -> Self {
         Self::new(id.into(), default_key)
     }
 }
 
+/// Serialized as the list of its actions.
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
-pub struct ActionsBuilder(Vec<RegisterAction>);
+#[serde(from = "Vec<RegisterAction>", into = "Vec<RegisterAction>")]
+pub struct ActionsBuilder {
+    actions: Vec<RegisterAction>,
+    context: Option<String>,
+    category: Option<String>,
+}
+
+impl From<Vec<RegisterAction>> for ActionsBuilder {
+    fn from(actions: Vec<RegisterAction>) -> Self {
+        Self {
+            actions,
+            ..Self::default()
+        }
+    }
+}
+
+impl From<ActionsBuilder> for Vec<RegisterAction> {
+    fn from(builder: ActionsBuilder) -> Self {
+        builder.actions
+    }
+}
 
 impl ActionsBuilder {
     pub fn new() -> Self {
         Self::default()
     }
 
-    pub fn push(mut self, id: impl Into<String>, default_key: KeyCode) -> Self {
-        self.0.push(RegisterAction::new(id.into(), default_key));
+    /// Puts the actions added after this call into the given input context.
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
         self
     }
 
+    /// Puts the actions added after this call into no input context, so they are always active
+    /// unless blocked.
+    pub fn global(mut self) -> Self {
+        self.context = None;
+        self
+    }
+
+    /// Lists the actions added after this call under the given category in the options menu.
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Sets the name shown in the options menu of the action added last.
+    pub fn display_name(mut self, display_name: impl Into<String>) -> Self {
+        if let Some(action) = self.actions.last_mut() {
+            action.display_name = Some(display_name.into());
+        }
+        self
+    }
+
+    /// Sets the explanation shown in the options menu of the action added last.
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        if let Some(action) = self.actions.last_mut() {
+            action.description = Some(description.into());
+        }
+        self
+    }
+
+    /// Adds an action, putting it into the current context and category if it has none.
+    pub fn push_action(mut self, mut action: RegisterAction) -> Self {
+        if action.context.is_none() {
+            action.context.clone_from(&self.context);
+        }
+        if action.category.is_none() {
+            action.category.clone_from(&self.category);
+        }
+        self.actions.push(action);
+        self
+    }
+
+    /// Adds an action triggered by a key, mouse button or chord.
+    pub fn push(self, id: impl Into<String>, default_key: impl Into<KeyChord>) -> Self {
+        self.push_action(RegisterAction::new(id.into(), default_key))
+    }
+
+    /// Adds an action triggered by any of the given keys, mouse buttons or chords.
+    pub fn push_with_bindings(
+        self,
+        id: impl Into<String>,
+        default_bindings: impl IntoIterator<Item = impl Into<KeyChord>>,
+    ) -> Self {
+        self.push_action(RegisterAction::with_bindings(id.into(), default_bindings))
+    }
+
+    /// Adds an action without default bindings, for the user to bind.
+    pub fn push_unbound(self, id: impl Into<String>) -> Self {
+        self.push_action(RegisterAction::with_bindings(
+            id.into(),
+            std::iter::empty::<KeyChord>(),
+        ))
+    }
+
+    /// Adds an axis action. Query its value with `action::axis`.
+    pub fn push_axis(
+        self,
+        id: impl Into<String>,
+        default_binding: impl Into<AxisBinding>,
+        options: AxisOptions,
+    ) -> Self {
+        self.push_action(RegisterAction::axis(id.into(), default_binding, options))
+    }
+
     pub fn build(self) -> Vec<RegisterAction> {
-        self.0
+        self.actions
     }
 }
 
@@ -50,10 +296,46 @@ pub struct ActionEvent {
     pub state: ActionState,
 }
 
-impl MessageType for ActionEvent {
-    const MESSAGE_META: MessageMeta = MessageMeta::new("builtin", "action_event", None);
+message_type!(ActionEvent, "builtin", "action_event");
+
+/// The effective bindings of an action, i.e. the default bindings or the ones the user chose.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ActionBindings {
+    pub keys: Vec<KeyChord>,
+    /// The axes of an axis action.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub axes: Vec<AxisBinding>,
+}
+
+impl ActionBindings {
+    /// Returns `true` if the action can't be triggered.
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty() && self.axes.is_empty()
+    }
+}
+
+/// A request to change the bindings of an action.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rebind {
+    /// Let the user press the new key, button or axis. The engine shows a prompt and replaces
+    /// the bindings with the captured one.
+    Capture,
+    /// Replace the bindings.
+    Set(ActionBindings),
+    /// Restore the default bindings.
+    Reset,
 }
 
+/// Sent when the bindings of an action changed, after a [Rebind] or in the options menu.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BindingsChanged {
+    pub action: String,
+    pub bindings: ActionBindings,
+}
+
+message_type!(BindingsChanged, "builtin", "bindings_changed");
+
 /// Describes the kind of action that was triggered.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize_repr, Deserialize_repr)]
 #[repr(u8)]
@@ -61,3 +343,48 @@ pub enum ActionKind {
     Pressed = 1,
     Released = 2,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Modifiers, MouseButton};
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_register_action_default_key() {
+        let action: RegisterAction =
+            serde_json::from_str(r#"{"id":"horn","default_key":"key_h"}"#).unwrap();
+        assert_eq!(action.default_bindings, vec![KeyChord::from(KeyCode::KeyH)]);
+        assert_eq!(action.default_key, Some(KeyCode::KeyH));
+
+        let action = RegisterAction::new(
+            "wiper".into(),
+            KeyCode::KeyW.with_modifiers(Modifiers::CTRL),
+        );
+        assert_eq!(action.default_key, None);
+        let json = serde_json::to_value(&action).unwrap();
+        assert!(json.get("default_key").is_none());
+
+        let action =
+            RegisterAction::new("bell".into(), KeyCode::KeyB).with_binding(MouseButton::Left);
+        let json = serde_json::to_value(&action).unwrap();
+        assert_eq!(json["default_key"], "key_b");
+
+        let action: RegisterAction = serde_json::from_value(json).unwrap();
+        assert_eq!(action.default_bindings.len(), 2);
+    }
+
+    #[test]
+    fn test_actions_builder_serde() {
+        let builder = ActionsBuilder::new()
+            .context("ibis")
+            .push("ibis_confirm", KeyCode::Enter);
+
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(json[0]["id"], "ibis_confirm");
+        assert_eq!(json[0]["context"], "ibis");
+
+        let builder: ActionsBuilder = serde_json::from_value(json).unwrap();
+        assert_eq!(builder.build().len(), 1);
+    }
+}
@@ -0,0 +1,403 @@
+//! Lay out text with a bitmap font, without asking the engine to measure it.
+//!
+//! See [BitmapFontProperties::layout].
+
+use serde::{Deserialize, Serialize};
+
+use super::BitmapFontProperties;
+use crate::{
+    graphics::{TextHorizontalAlignment, TextVerticalAlignment},
+    math::{IVec2, Rectangle},
+};
+
+/// What to do with text that doesn't fit into the target rectangle.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TextOverflow {
+    /// Keep the text, it is clipped when drawn.
+    #[default]
+    Clip,
+    /// Shorten the last visible line and end it with an ellipsis.
+    /// Uses `…` if the font contains it, `...` otherwise.
+    Ellipsis,
+}
+
+/// Options for laying out text.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TextLayoutOptions {
+    /// Additional space between letters in pixels. Text is drawn with an unsigned spacing, so
+    /// negative values are laid out as `0`.
+    pub letter_spacing: i32,
+    /// Additional space between lines in pixels.
+    pub line_spacing: i32,
+    pub horizontal_alignment: TextHorizontalAlignment,
+    pub vertical_alignment: TextVerticalAlignment,
+    /// Whether to break lines between words to fit the width of the rectangle.
+    /// Words wider than the rectangle are broken between letters.
+    pub wrap: bool,
+    /// What to do with text that doesn't fit into the rectangle.
+    pub overflow: TextOverflow,
+    /// The maximum number of lines. Fewer lines are used if they don't fit into the rectangle,
+    /// but at least one unless this is `Some(0)`.
+    pub max_lines: Option<usize>,
+}
+
+impl TextLayoutOptions {
+    pub fn with_letter_spacing(mut self, letter_spacing: i32) -> Self {
+        self.letter_spacing = letter_spacing;
+        self
+    }
+
+    pub fn with_line_spacing(mut self, line_spacing: i32) -> Self {
+        self.line_spacing = line_spacing;
+        self
+    }
+
+    pub fn with_alignment(
+        mut self,
+        horizontal: TextHorizontalAlignment,
+        vertical: TextVerticalAlignment,
+    ) -> Self {
+        self.horizontal_alignment = horizontal;
+        self.vertical_alignment = vertical;
+        self
+    }
+
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_overflow(mut self, overflow: TextOverflow) -> Self {
+        self.overflow = overflow;
+        self
+    }
+
+    pub fn with_max_lines(mut self, max_lines: usize) -> Self {
+        self.max_lines = Some(max_lines);
+        self
+    }
+}
+
+/// A line of laid out text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextLine {
+    pub text: String,
+    /// The position of the top left corner of the line.
+    pub top_left: IVec2,
+    /// The width of the line in pixels.
+    pub width: u32,
+}
+
+/// Laid out text, ready to be drawn line by line.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextLayout {
+    pub lines: Vec<TextLine>,
+    /// The rectangle the text was laid out in.
+    pub rect: Rectangle,
+    /// The letter spacing the text was laid out with.
+    pub letter_spacing: u32,
+    /// `true` if lines were dropped or shortened because they did not fit.
+    pub truncated: bool,
+}
+
+impl TextLayout {
+    /// Returns the width of the widest line.
+    pub fn width(&self) -> u32 {
+        self.lines.iter().map(|line| line.width).max().unwrap_or(0)
+    }
+}
+
+impl BitmapFontProperties {
+    /// Lays out the text in the rectangle. Lines are separated by `\n`.
+    pub fn layout(&self, text: &str, rect: Rectangle, options: &TextLayoutOptions) -> TextLayout {
+        let max_width = rect.width();
+        let spacing = options.letter_spacing.max(0);
+
+        let mut lines = Vec::new();
+        for paragraph in text.split('\n') {
+            if options.wrap {
+                self.wrap(paragraph, max_width, spacing, &mut lines);
+            } else {
+                lines.push(paragraph.to_string());
+            }
+        }
+
+        let line_height = self.vertical_size.max(0) as i64;
+        let line_advance = line_height + options.line_spacing as i64;
+        let fitting_lines = if line_advance > 0 {
+            ((rect.height() as i64 - line_height) / line_advance + 1).max(1) as usize
+        } else {
+            usize::MAX
+        };
+        let max_lines = options
+            .max_lines
+            .map_or(fitting_lines, |max| max.min(fitting_lines));
+
+        let mut truncated = false;
+        if lines.len() > max_lines {
+            lines.truncate(max_lines);
+            truncated = true;
+
+            if let Some(last) = lines
+                .last_mut()
+                .filter(|_| options.overflow == TextOverflow::Ellipsis)
+            {
+                *last = self.ellipsize(&format!("{last}{}", self.ellipsis()), max_width, spacing);
+            }
+        }
+
+        if options.overflow == TextOverflow::Ellipsis {
+            for line in &mut lines {
+                if self.text_width(line, spacing) > max_width {
+                    *line = self.ellipsize(line, max_width, spacing);
+                    truncated = true;
+                }
+            }
+        }
+
+        let height = (lines.len() as i64 * line_advance - options.line_spacing as i64).max(0);
+        let free_height = rect.height() as i64 - height;
+        let top = rect.start().y as i64
+            + match options.vertical_alignment {
+                TextVerticalAlignment::Top => 0,
+                TextVerticalAlignment::Center => free_height / 2,
+                TextVerticalAlignment::Bottom => free_height,
+            };
+
+        let lines = lines
+            .into_iter()
+            .enumerate()
+            .map(|(index, text)| {
+                let width = self.text_width(&text, spacing);
+                let free_width = max_width as i64 - width as i64;
+                let x = rect.start().x as i64
+                    + match options.horizontal_alignment {
+                        TextHorizontalAlignment::Left => 0,
+                        TextHorizontalAlignment::Right => free_width,
+                        TextHorizontalAlignment::Center => (free_width as f64 / 2.0).round() as i64,
+                        TextHorizontalAlignment::IntCenterLeft => free_width.div_euclid(2),
+                        TextHorizontalAlignment::IntCenterRight => {
+                            free_width.div_euclid(2) + free_width.rem_euclid(2)
+                        }
+                    };
+                let y = top + index as i64 * line_advance;
+
+                TextLine {
+                    text,
+                    top_left: IVec2::new(x as i32, y as i32),
+                    width,
+                }
+            })
+            .collect();
+
+        TextLayout {
+            lines,
+            rect,
+            letter_spacing: spacing as u32,
+            truncated,
+        }
+    }
+
+    /// Breaks the paragraph into lines not wider than `max_width`.
+    fn wrap(&self, paragraph: &str, max_width: u32, spacing: i32, lines: &mut Vec<String>) {
+        let mut line = String::new();
+
+        for word in paragraph.split(' ').filter(|word| !word.is_empty()) {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{line} {word}")
+            };
+
+            if self.text_width(&candidate, spacing) <= max_width {
+                line = candidate;
+                continue;
+            }
+
+            if !line.is_empty() {
+                lines.push(std::mem::take(&mut line));
+            }
+
+            // Break words that don't fit on a line of their own between letters.
+            for c in word.chars() {
+                line.push(c);
+                if line.chars().count() > 1 && self.text_width(&line, spacing) > max_width {
+                    line.pop();
+                    lines.push(std::mem::replace(&mut line, c.to_string()));
+                }
+            }
+        }
+
+        lines.push(line);
+    }
+
+    /// Shortens the text until it fits into `max_width` with an ellipsis appended.
+    fn ellipsize(&self, text: &str, max_width: u32, spacing: i32) -> String {
+        let ellipsis = self.ellipsis();
+        let mut text = text.strip_suffix(ellipsis).unwrap_or(text).to_string();
+
+        loop {
+            let candidate = format!("{text}{ellipsis}");
+            if text.is_empty() || self.text_width(&candidate, spacing) <= max_width {
+                return candidate;
+            }
+            text.pop();
+            text.truncate(text.trim_end().len());
+        }
+    }
+
+    fn ellipsis(&self) -> &'static str {
+        if self.contains('…') {
+            "…"
+        } else {
+            "..."
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{font::FontLetter, math::UVec2};
+
+    use super::*;
+
+    /// A font with 4 pixel wide letters, 1 pixel apart.
+    fn font() -> BitmapFontProperties {
+        let letters = ('a'..='z')
+            .chain([' ', '.'])
+            .map(|character| {
+                (
+                    character,
+                    FontLetter {
+                        character,
+                        start: 0,
+                        width: 4,
+                    },
+                )
+            })
+            .collect();
+
+        BitmapFontProperties {
+            horizontal_distance: 1,
+            vertical_size: 8,
+            letters,
+        }
+    }
+
+    fn texts(layout: &TextLayout) -> Vec<&str> {
+        layout.lines.iter().map(|line| line.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_text_width() {
+        let font = font();
+        assert_eq!(font.text_width("", 0), 0);
+        assert_eq!(font.text_width("abc", 0), 14);
+        assert_eq!(font.text_width("abc", 2), 18);
+        assert_eq!(font.text_width("a€b", 0), 9);
+    }
+
+    #[test]
+    fn test_alignment() {
+        let font = font();
+        let rect = Rectangle::new(UVec2::new(10, 0), UVec2::new(25, 20));
+        let layout = |horizontal, vertical| {
+            let options = TextLayoutOptions::default().with_alignment(horizontal, vertical);
+            font.layout("ab", rect, &options).lines[0].top_left
+        };
+
+        // "ab" is 9 pixels wide, leaving 6 pixels.
+        assert_eq!(
+            layout(TextHorizontalAlignment::Left, TextVerticalAlignment::Top),
+            IVec2::new(10, 0)
+        );
+        assert_eq!(
+            layout(
+                TextHorizontalAlignment::Right,
+                TextVerticalAlignment::Bottom
+            ),
+            IVec2::new(16, 12)
+        );
+        assert_eq!(
+            layout(
+                TextHorizontalAlignment::Center,
+                TextVerticalAlignment::Center
+            ),
+            IVec2::new(13, 6)
+        );
+
+        let rect = Rectangle::new(UVec2::new(0, 0), UVec2::new(14, 8));
+        let x = |horizontal| {
+            let options =
+                TextLayoutOptions::default().with_alignment(horizontal, TextVerticalAlignment::Top);
+            font.layout("ab", rect, &options).lines[0].top_left.x
+        };
+
+        assert_eq!(x(TextHorizontalAlignment::IntCenterLeft), 2);
+        assert_eq!(x(TextHorizontalAlignment::IntCenterRight), 3);
+    }
+
+    #[test]
+    fn test_wrap_and_truncate() {
+        let font = font();
+        // Fits 5 letters per line and 2 lines with a line spacing of 2.
+        let rect = Rectangle::new(UVec2::new(0, 0), UVec2::new(24, 18));
+        let options = TextLayoutOptions::default()
+            .with_wrap(true)
+            .with_line_spacing(2);
+
+        let tall_rect = Rectangle::new(UVec2::new(0, 0), UVec2::new(24, 100));
+        let layout = font.layout("abc de fghijkl", tall_rect, &options);
+        assert!(!layout.truncated);
+        assert_eq!(texts(&layout), ["abc", "de", "fghij", "kl"]);
+
+        let layout = font.layout(
+            "abc de fghijkl",
+            tall_rect,
+            &options.clone().with_max_lines(3),
+        );
+        assert!(layout.truncated);
+        assert_eq!(texts(&layout), ["abc", "de", "fghij"]);
+
+        let layout = font.layout("abc de fghijkl", rect, &options);
+        assert!(layout.truncated);
+        assert_eq!(texts(&layout), ["abc", "de"]);
+
+        let layout = font.layout(
+            "abc de fghijkl",
+            rect,
+            &options.with_overflow(TextOverflow::Ellipsis),
+        );
+        assert!(layout.truncated);
+        assert_eq!(texts(&layout), ["abc", "de..."]);
+        assert_eq!(layout.lines[1].top_left, IVec2::new(0, 10));
+    }
+
+    #[test]
+    fn test_zero_max_lines() {
+        let font = font();
+        let rect = Rectangle::new(UVec2::new(0, 0), UVec2::new(24, 18));
+        let options = TextLayoutOptions::default()
+            .with_overflow(TextOverflow::Ellipsis)
+            .with_max_lines(0);
+
+        let layout = font.layout("abc", rect, &options);
+        assert!(layout.truncated);
+        assert!(layout.lines.is_empty());
+    }
+
+    #[test]
+    fn test_negative_letter_spacing() {
+        let font = font();
+        let rect = Rectangle::new(UVec2::new(0, 0), UVec2::new(20, 8));
+        let options = TextLayoutOptions::default()
+            .with_letter_spacing(-1)
+            .with_alignment(TextHorizontalAlignment::Right, TextVerticalAlignment::Top);
+
+        // Laid out like the spacing of `0` it is drawn with.
+        let layout = font.layout("abc", rect, &options);
+        assert_eq!(layout.letter_spacing, 0);
+        assert_eq!(layout.lines[0].width, 14);
+        assert_eq!(layout.lines[0].top_left, IVec2::new(6, 0));
+    }
+}
@@ -0,0 +1,58 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+pub mod layout;
+
+/// Properties of a bitmap font.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BitmapFontProperties {
+    /// The horizontal distance between letters.
+    pub horizontal_distance: i32,
+    /// The vertical size of the font.
+    pub vertical_size: i32,
+    /// The letters in the font.
+    pub letters: HashMap<char, FontLetter>,
+}
+
+/// A letter in a bitmap font.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FontLetter {
+    /// The character represented by the letter.
+    pub character: char,
+    /// The start of the letter in the texture.
+    pub start: u32,
+    /// The width of the letter in the texture.
+    pub width: u32,
+}
+
+impl BitmapFontProperties {
+    /// Returns the letter for the given character, if the font contains it.
+    pub fn letter(&self, character: char) -> Option<&FontLetter> {
+        self.letters.get(&character)
+    }
+
+    /// Returns `true` if the font contains the given character.
+    pub fn contains(&self, character: char) -> bool {
+        self.letters.contains_key(&character)
+    }
+
+    /// Returns the width of the text in pixels. Characters missing in the font are skipped.
+    ///
+    /// Consecutive letters are separated by [horizontal_distance](Self::horizontal_distance) plus `letter_spacing`.
+    pub fn text_width(&self, text: &str, letter_spacing: i32) -> u32 {
+        let mut width = 0i64;
+        let mut letters = 0i64;
+
+        for letter in text.chars().filter_map(|c| self.letter(c)) {
+            width += letter.width as i64;
+            letters += 1;
+        }
+
+        if letters > 1 {
+            width += (letters - 1) * (self.horizontal_distance as i64 + letter_spacing as i64);
+        }
+
+        width.max(0) as u32
+    }
+}
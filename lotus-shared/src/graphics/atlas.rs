@@ -0,0 +1,106 @@
+//! Sprite atlases, content textures containing multiple named frames like icons or pictograms.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::textures::{AlphaMode, DrawTextureOpts};
+use crate::{
+    content::ContentId,
+    math::{Rectangle, UVec2},
+};
+
+/// A content texture divided into named frames.
+///
+/// # Example
+/// ```
+/// # use lotus_shared::{content::ContentId, graphics::atlas::SpriteAtlas, math::UVec2};
+/// let atlas = SpriteAtlas::from_grid(
+///     ContentId { user_id: 1, sub_id: 2 },
+///     UVec2::new(16, 16),
+///     2,
+///     ["stop_requested", "wheelchair", "bicycle"],
+/// );
+///
+/// assert_eq!(atlas.frame("bicycle").unwrap().start(), UVec2::new(0, 16));
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpriteAtlas {
+    /// The content texture containing the frames. Preload it with
+    /// `lotus_script::content::preload` before drawing frames of the atlas.
+    pub texture: ContentId,
+    /// The frames by name.
+    pub frames: BTreeMap<String, Rectangle>,
+}
+
+impl SpriteAtlas {
+    /// Creates an atlas without frames.
+    pub fn new(texture: ContentId) -> Self {
+        Self {
+            texture,
+            frames: BTreeMap::new(),
+        }
+    }
+
+    /// Creates an atlas of equally sized frames, laid out in rows of `columns` frames.
+    /// The names are assigned row by row, starting at the top left.
+    pub fn from_grid(
+        texture: ContentId,
+        frame_size: UVec2,
+        columns: u32,
+        names: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        assert!(columns > 0, "columns must be positive");
+
+        let frames = names
+            .into_iter()
+            .zip(0..)
+            .map(|(name, index)| {
+                let cell = UVec2::new(index % columns, index / columns);
+                (
+                    name.into(),
+                    Rectangle::from_size(cell * frame_size, frame_size),
+                )
+            })
+            .collect();
+
+        Self { texture, frames }
+    }
+
+    /// Adds a frame, replacing an existing one with the same name.
+    pub fn with_frame(mut self, name: impl Into<String>, rect: Rectangle) -> Self {
+        self.insert(name, rect);
+        self
+    }
+
+    /// Adds a frame, replacing an existing one with the same name.
+    pub fn insert(&mut self, name: impl Into<String>, rect: Rectangle) {
+        self.frames.insert(name.into(), rect);
+    }
+
+    /// Returns the rectangle of the given frame.
+    pub fn frame(&self, name: &str) -> Option<Rectangle> {
+        self.frames.get(name).copied()
+    }
+
+    /// Returns the names of all frames.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.frames.keys().map(String::as_str)
+    }
+
+    /// Returns the options to draw the given frame to the target rectangle.
+    /// Returns `None` if there is no frame with that name.
+    pub fn draw_options(
+        &self,
+        name: &str,
+        target: Rectangle,
+        alpha_mode: AlphaMode,
+    ) -> Option<DrawTextureOpts> {
+        self.frame(name).map(|source| {
+            DrawTextureOpts::default()
+                .with_source_rect(source)
+                .with_target_rect(target)
+                .with_alpha_mode(alpha_mode)
+        })
+    }
+}
@@ -0,0 +1,259 @@
+//! Collect texture actions locally and submit them as a single list.
+//!
+//! [ActionBatch] drops actions that are completely painted over later in the same frame and
+//! remembers the last submitted frame, so an unchanged frame that repaints the whole texture
+//! with its own pixels doesn't have to be submitted again.
+
+use super::textures::TextureAction;
+use crate::math::UVec2;
+
+/// The actions of a texture for one frame.
+#[derive(Clone, Default)]
+pub struct ActionBatch {
+    actions: Vec<TextureAction>,
+    size: Option<UVec2>,
+    last_submitted: Option<Box<[u8]>>,
+}
+
+impl ActionBatch {
+    /// Creates an empty batch for a texture of the given size. If the size is known,
+    /// rectangles covering the whole texture replace all previous actions like a clear.
+    pub fn new(size: Option<UVec2>) -> Self {
+        Self {
+            size,
+            ..Default::default()
+        }
+    }
+
+    /// Adds an action, removing previous actions it paints over completely.
+    pub fn push(&mut self, action: TextureAction) {
+        match opaque_bounds(&action, self.size) {
+            Some(Bounds::All) => self.actions.clear(),
+            Some(Bounds::Rect(start, end)) => self.actions.retain(|previous| {
+                !matches!(bounds(previous), Some((s, e)) if s.cmpge(start).all() && e.cmple(end).all())
+            }),
+            None => {}
+        }
+
+        self.actions.push(action);
+    }
+
+    /// Returns the actions of the current frame.
+    pub fn actions(&self) -> &[TextureAction] {
+        &self.actions
+    }
+
+    /// Returns the number of actions in the current frame.
+    pub fn len(&self) -> usize {
+        self.actions.len()
+    }
+
+    /// Returns `true` if there are no actions in the current frame.
+    pub fn is_empty(&self) -> bool {
+        self.actions.is_empty()
+    }
+
+    /// Ends the frame, returning the actions encoded as msgpack. Returns `None` if the frame is
+    /// empty, or if it repaints the whole texture exactly like the last submitted frame.
+    ///
+    /// Frames that don't start with a clear or an opaque action covering the whole texture
+    /// build on the previous pixels, e.g. translucent or incremental drawing, so they change
+    /// the texture every time and are always submitted. The same goes for frames that draw
+    /// text, content textures or other script textures, as their pixels can change or still
+    /// be loading without the actions changing.
+    pub fn take_changed(&mut self) -> Option<Box<[u8]>> {
+        if self.actions.is_empty() {
+            return None;
+        }
+
+        let replaces_all = matches!(
+            self.actions
+                .first()
+                .and_then(|action| opaque_bounds(action, self.size)),
+            Some(Bounds::All)
+        ) && self.actions.iter().all(is_self_contained);
+
+        let actions = std::mem::take(&mut self.actions);
+        let encoded = rmp_serde::to_vec_named(&actions)
+            .expect("failed to serialize texture actions")
+            .into_boxed_slice();
+
+        if !replaces_all {
+            self.last_submitted = None;
+            return Some(encoded);
+        }
+
+        if self.last_submitted.as_ref() == Some(&encoded) {
+            return None;
+        }
+
+        self.last_submitted = Some(encoded.clone());
+        Some(encoded)
+    }
+
+    /// Forgets the last submitted frame, so the next frame is submitted even if it is unchanged.
+    pub fn invalidate(&mut self) {
+        self.last_submitted = None;
+    }
+}
+
+enum Bounds {
+    All,
+    Rect(UVec2, UVec2),
+}
+
+/// Returns the area an action replaces completely, regardless of the previous pixels.
+fn opaque_bounds(action: &TextureAction, size: Option<UVec2>) -> Option<Bounds> {
+    let (start, end) = match action {
+        TextureAction::Clear(_) => return Some(Bounds::All),
+        TextureAction::DrawRect { start, end, color } if color.a == u8::MAX => (*start, *end),
+        TextureAction::WriteRegion { region, .. } => (region.start(), region.end()),
+        _ => return None,
+    };
+
+    match size {
+        Some(size) if start == UVec2::ZERO && end.cmpge(size).all() => Some(Bounds::All),
+        _ => Some(Bounds::Rect(start, end)),
+    }
+}
+
+/// Returns `true` if the pixels an action draws only depend on the action itself.
+fn is_self_contained(action: &TextureAction) -> bool {
+    !matches!(
+        action,
+        TextureAction::DrawText { .. }
+            | TextureAction::DrawTexture { .. }
+            | TextureAction::DrawScriptTexture { .. }
+    )
+}
+
+/// Returns the area an action draws to, for actions that can be dropped when painted over.
+fn bounds(action: &TextureAction) -> Option<(UVec2, UVec2)> {
+    match action {
+        TextureAction::DrawRect { start, end, .. } => Some((*start, *end)),
+        TextureAction::WriteRegion { region, .. } => Some((region.start(), region.end())),
+        TextureAction::DrawPixels(pixels) => {
+            let start = pixels.iter().map(|p| p.pos).reduce(UVec2::min)?;
+            let end = pixels.iter().map(|p| p.pos).reduce(UVec2::max)? + 1;
+            Some((start, end))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        content::ContentId,
+        graphics::{
+            textures::{AlphaMode, DrawPixel, DrawTextureOpts, TextureHandle},
+            Color,
+        },
+        math::{IVec2, Rectangle},
+    };
+
+    fn rect(start: (u32, u32), end: (u32, u32), color: Color) -> TextureAction {
+        TextureAction::DrawRect {
+            start: start.into(),
+            end: end.into(),
+            color,
+        }
+    }
+
+    #[test]
+    fn test_coalesce() {
+        let mut batch = ActionBatch::new(Some(UVec2::new(64, 32)));
+
+        batch.push(TextureAction::Clear(Color::BLACK));
+        batch.push(rect((0, 0), (10, 10), Color::RED));
+        batch.push(TextureAction::DrawPixels(
+            [DrawPixel::from((5, 5, Color::BLUE))].into(),
+        ));
+        // Covers the red rectangle and the pixel, but not the clear.
+        batch.push(rect((0, 0), (20, 20), Color::GREEN));
+        assert_eq!(batch.len(), 2);
+
+        // Translucent rectangles don't cover anything.
+        batch.push(rect((0, 0), (64, 32), Color::rgba(0, 0, 0, 128)));
+        assert_eq!(batch.len(), 3);
+
+        batch.push(TextureAction::write_region(
+            Rectangle::new(UVec2::ZERO, UVec2::new(64, 32)),
+            vec![0; 64 * 32 * 4],
+        ));
+        assert_eq!(batch.len(), 1);
+    }
+
+    #[test]
+    fn test_skip_unchanged() {
+        let mut batch = ActionBatch::new(None);
+        let frame = |batch: &mut ActionBatch, color| {
+            batch.push(TextureAction::Clear(Color::BLACK));
+            batch.push(rect((1, 1), (4, 4), color));
+            batch.take_changed()
+        };
+
+        assert!(frame(&mut batch, Color::RED).is_some());
+        assert!(frame(&mut batch, Color::RED).is_none());
+        assert!(frame(&mut batch, Color::GREEN).is_some());
+        assert!(batch.is_empty());
+
+        batch.invalidate();
+        assert!(frame(&mut batch, Color::GREEN).is_some());
+        assert!(batch.take_changed().is_none());
+    }
+
+    #[test]
+    fn test_frames_with_external_pixels() {
+        let mut batch = ActionBatch::new(None);
+        let frame = |batch: &mut ActionBatch, action: TextureAction| {
+            batch.push(TextureAction::Clear(Color::BLACK));
+            batch.push(action);
+            batch.take_changed()
+        };
+
+        let live = TextureAction::DrawScriptTexture {
+            handle: serde_json::from_str::<TextureHandle>("3").unwrap(),
+            options: DrawTextureOpts::default(),
+        };
+        let icon = TextureAction::DrawTexture {
+            texture: ContentId::default(),
+            options: DrawTextureOpts::default(),
+        };
+        let text = TextureAction::DrawText {
+            font: ContentId::default(),
+            text: "Hauptbahnhof".into(),
+            top_left: IVec2::ZERO,
+            letter_spacing: 1,
+            full_color: None,
+            alpha_mode: AlphaMode::Blend,
+            target_rect: None,
+        };
+
+        for action in [live, icon, text] {
+            assert!(frame(&mut batch, action.clone()).is_some());
+            assert!(frame(&mut batch, action).is_some());
+        }
+    }
+
+    #[test]
+    fn test_repeated_translucent_frame() {
+        let mut batch = ActionBatch::new(Some(UVec2::new(8, 8)));
+        let fade = |batch: &mut ActionBatch| {
+            batch.push(rect((0, 0), (8, 8), Color::rgba(0, 0, 0, 32)));
+            batch.take_changed()
+        };
+
+        // Every application darkens the texture further.
+        assert!(fade(&mut batch).is_some());
+        assert!(fade(&mut batch).is_some());
+
+        // A full repaint after an incremental frame is submitted even if it was submitted before.
+        batch.push(TextureAction::Clear(Color::BLACK));
+        assert!(batch.take_changed().is_some());
+        assert!(fade(&mut batch).is_some());
+        batch.push(TextureAction::Clear(Color::BLACK));
+        assert!(batch.take_changed().is_some());
+    }
+}
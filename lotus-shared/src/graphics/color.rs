@@ -0,0 +1,606 @@
+//! Color conversions, blending, gradients, parsing and palette quantization for [Color].
+
+use std::str::FromStr;
+
+use super::{textures::AlphaMode, Color};
+
+/// Converts an sRGB channel value between `0.0` and `1.0` to linear light.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Converts a linear light channel value between `0.0` and `1.0` to sRGB.
+pub fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+fn to_u8(value: f32) -> u8 {
+    (value.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+fn mul_u8(a: u8, b: u8) -> u8 {
+    ((a as u32 * b as u32 + 127) / 255) as u8
+}
+
+impl Color {
+    pub const TRANSPARENT: Self = Self::rgba(0, 0, 0, 0);
+
+    /// Returns the color with the given alpha.
+    pub const fn with_alpha(self, a: u8) -> Self {
+        Self::rgba(self.r, self.g, self.b, a)
+    }
+
+    /// Creates a color from sRGB channels between `0.0` and `1.0`.
+    pub fn from_f32([r, g, b, a]: [f32; 4]) -> Self {
+        Self::rgba(to_u8(r), to_u8(g), to_u8(b), to_u8(a))
+    }
+
+    /// Returns the sRGB channels between `0.0` and `1.0`.
+    pub fn to_f32(self) -> [f32; 4] {
+        [self.r, self.g, self.b, self.a].map(|c| c as f32 / 255.0)
+    }
+
+    /// Creates a color from linear light channels between `0.0` and `1.0`. Alpha is not converted.
+    pub fn from_linear([r, g, b, a]: [f32; 4]) -> Self {
+        Self::from_f32([linear_to_srgb(r), linear_to_srgb(g), linear_to_srgb(b), a])
+    }
+
+    /// Returns the channels in linear light between `0.0` and `1.0`. Alpha is not converted.
+    pub fn to_linear(self) -> [f32; 4] {
+        let [r, g, b, a] = self.to_f32();
+        [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+    }
+
+    /// Creates an opaque color from a hue in degrees and saturation and value between `0.0`
+    /// and `1.0`.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Self {
+        let c = value * saturation;
+        Self::from_hue(hue, c, value - c)
+    }
+
+    /// Returns the hue in degrees and the saturation and value between `0.0` and `1.0`.
+    pub fn to_hsv(self) -> (f32, f32, f32) {
+        let (hue, min, max) = self.hue();
+        let saturation = if max > 0.0 { (max - min) / max } else { 0.0 };
+        (hue, saturation, max)
+    }
+
+    /// Creates an opaque color from a hue in degrees and saturation and lightness between `0.0`
+    /// and `1.0`.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Self {
+        let c = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        Self::from_hue(hue, c, lightness - c / 2.0)
+    }
+
+    /// Returns the hue in degrees and the saturation and lightness between `0.0` and `1.0`.
+    pub fn to_hsl(self) -> (f32, f32, f32) {
+        let (hue, min, max) = self.hue();
+        let lightness = (max + min) / 2.0;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    /// Creates a color from a hue, chroma and the value of the smallest channel.
+    fn from_hue(hue: f32, chroma: f32, min: f32) -> Self {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        Self::from_f32([r + min, g + min, b + min, 1.0])
+    }
+
+    /// Returns the hue in degrees and the smallest and largest channel.
+    fn hue(self) -> (f32, f32, f32) {
+        let [r, g, b, _] = self.to_f32();
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let delta = max - min;
+
+        let hue = if delta == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        (hue, min, max)
+    }
+
+    /// Returns the color with its color channels multiplied by alpha.
+    pub fn premultiplied(self) -> Self {
+        Self::rgba(
+            mul_u8(self.r, self.a),
+            mul_u8(self.g, self.a),
+            mul_u8(self.b, self.a),
+            self.a,
+        )
+    }
+
+    /// Reverts [Color::premultiplied]. Fully transparent colors become [Color::TRANSPARENT].
+    pub fn unpremultiplied(self) -> Self {
+        if self.a == 0 {
+            return Self::TRANSPARENT;
+        }
+
+        let div = |c: u8| ((c as u32 * 255 + self.a as u32 / 2) / self.a as u32).min(255) as u8;
+        Self::rgba(div(self.r), div(self.g), div(self.b), self.a)
+    }
+
+    /// Draws this color over `dst` like a texture action with the given [AlphaMode] does.
+    pub fn over(self, dst: Color, mode: AlphaMode) -> Self {
+        match mode {
+            AlphaMode::Opaque => self.with_alpha(u8::MAX),
+            AlphaMode::Mask(threshold) if self.a as f32 / 255.0 >= threshold => {
+                self.with_alpha(u8::MAX)
+            }
+            AlphaMode::Mask(_) => dst,
+            AlphaMode::Blend => self.blend(dst, BlendMode::Normal),
+        }
+    }
+
+    /// Blends this color over `dst` using its alpha and the given [BlendMode].
+    pub fn blend(self, dst: Color, mode: BlendMode) -> Self {
+        let [sr, sg, sb, sa] = self.to_f32();
+        let [dr, dg, db, da] = dst.to_f32();
+
+        let a = sa + da * (1.0 - sa);
+        if a == 0.0 {
+            return Self::TRANSPARENT;
+        }
+
+        let channel = |s: f32, d: f32| {
+            let blended = match mode {
+                BlendMode::Normal => s,
+                BlendMode::Multiply => s * d,
+                BlendMode::Screen => s + d - s * d,
+                BlendMode::Add => (s + d).min(1.0),
+                BlendMode::Darken => s.min(d),
+                BlendMode::Lighten => s.max(d),
+            };
+            // Where the destination is transparent, only the source color is visible.
+            let s = s + (blended - s) * da;
+            (s * sa + d * da * (1.0 - sa)) / a
+        };
+
+        Self::from_f32([channel(sr, dr), channel(sg, dg), channel(sb, db), a])
+    }
+
+    /// Interpolates between two colors in sRGB space, `t` being between `0.0` and `1.0`.
+    pub fn lerp(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let [a, b] = [self.to_f32(), other.to_f32()];
+        Self::from_f32(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+    }
+
+    /// Interpolates between two colors in linear light, which avoids dark transitions between
+    /// saturated colors.
+    pub fn lerp_linear(self, other: Color, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let [a, b] = [self.to_linear(), other.to_linear()];
+        Self::from_linear(std::array::from_fn(|i| a[i] + (b[i] - a[i]) * t))
+    }
+
+    /// Returns the relative luminance between `0.0` and `1.0`, ignoring alpha.
+    pub fn luminance(self) -> f32 {
+        let [r, g, b, _] = self.to_linear();
+        0.2126 * r + 0.7152 * g + 0.0722 * b
+    }
+
+    /// Parses a hex color like `#f80`, `#ff8800` or `#ff880080`. The `#` is optional.
+    pub fn from_hex(hex: &str) -> Result<Self, ParseColorError> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.is_ascii() {
+            return Err(ParseColorError::InvalidHex(hex.into()));
+        }
+
+        let channel = |i: usize, len: usize| {
+            let value = u8::from_str_radix(&digits[i * len..(i + 1) * len], 16)
+                .map_err(|_| ParseColorError::InvalidHex(hex.into()))?;
+            Ok(if len == 1 { value * 17 } else { value })
+        };
+
+        match digits.len() {
+            3 => Ok(Self::rgb(channel(0, 1)?, channel(1, 1)?, channel(2, 1)?)),
+            4 => Ok(Self::rgba(
+                channel(0, 1)?,
+                channel(1, 1)?,
+                channel(2, 1)?,
+                channel(3, 1)?,
+            )),
+            6 => Ok(Self::rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+            8 => Ok(Self::rgba(
+                channel(0, 2)?,
+                channel(1, 2)?,
+                channel(2, 2)?,
+                channel(3, 2)?,
+            )),
+            _ => Err(ParseColorError::InvalidHex(hex.into())),
+        }
+    }
+
+    /// Returns the color as `#rrggbb`, or `#rrggbbaa` if it isn't opaque.
+    pub fn to_hex(self) -> String {
+        if self.a == u8::MAX {
+            format!("#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            format!("#{:02x}{:02x}{:02x}{:02x}", self.r, self.g, self.b, self.a)
+        }
+    }
+
+    /// Returns the color with the given CSS name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_ascii_lowercase();
+        NAMED_COLORS
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, rgb)| Color::from(rgb << 8 | 0xff))
+    }
+}
+
+/// Parses a hex color (see [Color::from_hex]) or a CSS color name (see [Color::from_name]).
+impl FromStr for Color {
+    type Err = ParseColorError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            return Self::from_hex(s);
+        }
+
+        Self::from_name(s)
+            .ok_or_else(|| ParseColorError::UnknownName(s.into()))
+            .or_else(|e| Self::from_hex(s).map_err(|_| e))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ParseColorError {
+    #[error("invalid hex color: {0}")]
+    InvalidHex(String),
+    #[error("unknown color name: {0}")]
+    UnknownName(String),
+}
+
+/// How a color is combined with the color below it, before alpha is applied.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BlendMode {
+    /// The color replaces the color below, like [AlphaMode::Blend].
+    #[default]
+    Normal,
+    Multiply,
+    Screen,
+    /// The channels are added and clamped, e.g. for glowing lamps.
+    Add,
+    Darken,
+    Lighten,
+}
+
+/// A gradient through colors at positions between `0.0` and `1.0`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f32, Color)>,
+    linear: bool,
+}
+
+impl Gradient {
+    /// Creates a gradient with evenly spaced colors.
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Self {
+        let colors = colors.into_iter().collect::<Vec<_>>();
+        let last = colors.len().saturating_sub(1).max(1) as f32;
+
+        Self {
+            stops: colors
+                .into_iter()
+                .enumerate()
+                .map(|(i, c)| (i as f32 / last, c))
+                .collect(),
+            linear: false,
+        }
+    }
+
+    /// Adds a color at the given position.
+    pub fn with_stop(mut self, position: f32, color: Color) -> Self {
+        let index = self.stops.partition_point(|(p, _)| *p <= position);
+        self.stops.insert(index, (position, color));
+        self
+    }
+
+    /// Interpolates in linear light instead of sRGB. See [Color::lerp_linear].
+    pub fn with_linear(mut self, linear: bool) -> Self {
+        self.linear = linear;
+        self
+    }
+
+    /// Returns the color at the given position. Positions outside of the stops get the first or
+    /// last color. An empty gradient is [Color::TRANSPARENT].
+    pub fn sample(&self, t: f32) -> Color {
+        let index = self.stops.partition_point(|(p, _)| *p <= t);
+
+        match (
+            index.checked_sub(1).map(|i| self.stops[i]),
+            self.stops.get(index).copied(),
+        ) {
+            (Some((p0, c0)), Some((p1, c1))) => {
+                let t = (t - p0) / (p1 - p0);
+                if self.linear {
+                    c0.lerp_linear(c1, t)
+                } else {
+                    c0.lerp(c1, t)
+                }
+            }
+            (Some((_, c)), None) | (None, Some((_, c))) => c,
+            (None, None) => Color::TRANSPARENT,
+        }
+    }
+
+    /// Returns `count` evenly spaced colors from the start to the end of the gradient.
+    pub fn colors(&self, count: usize) -> Vec<Color> {
+        let last = count.saturating_sub(1).max(1) as f32;
+        (0..count).map(|i| self.sample(i as f32 / last)).collect()
+    }
+}
+
+/// A limited set of colors, e.g. of an LCD with few gray levels, to reduce images to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Palette {
+    colors: Vec<Color>,
+}
+
+impl Palette {
+    pub fn new(colors: impl IntoIterator<Item = Color>) -> Self {
+        Self {
+            colors: colors.into_iter().collect(),
+        }
+    }
+
+    /// Creates a palette of evenly spaced gray levels from black to white.
+    pub fn grayscale(levels: usize) -> Self {
+        Self::new(Gradient::new([Color::BLACK, Color::WHITE]).colors(levels))
+    }
+
+    /// Creates a palette of evenly spaced colors from `off` to `on`, e.g. for a monochrome
+    /// display with brightness levels.
+    pub fn levels(off: Color, on: Color, levels: usize) -> Self {
+        Self::new(Gradient::new([off, on]).colors(levels))
+    }
+
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Returns the index of the palette color closest to the given color, `None` if the
+    /// palette is empty. Alpha is ignored.
+    pub fn nearest_index(&self, color: Color) -> Option<usize> {
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, c)| distance(**c, color))
+            .map(|(i, _)| i)
+    }
+
+    /// Returns the palette color closest to the given color, keeping its alpha. Returns the
+    /// color unchanged if the palette is empty.
+    pub fn nearest(&self, color: Color) -> Color {
+        match self.nearest_index(color) {
+            Some(i) => self.colors[i].with_alpha(color.a),
+            None => color,
+        }
+    }
+
+    /// Replaces every color with the closest palette color.
+    pub fn quantize(&self, colors: &mut [Color]) {
+        for color in colors {
+            *color = self.nearest(*color);
+        }
+    }
+
+    /// Replaces every color of an image `width` pixels wide with a palette color, spreading the
+    /// error to neighboring pixels (Floyd-Steinberg dithering) to keep gradients smooth.
+    pub fn dither(&self, colors: &mut [Color], width: usize) {
+        if self.colors.is_empty() || width == 0 {
+            return;
+        }
+
+        let mut errors = vec![[0.0f32; 3]; colors.len()];
+        for i in 0..colors.len() {
+            let [r, g, b, a] = colors[i].to_f32();
+            let [er, eg, eb] = errors[i];
+            let wanted = Color::from_f32([r + er, g + eg, b + eb, a]);
+            let chosen = self.nearest(wanted);
+            colors[i] = chosen;
+
+            let [wr, wg, wb, _] = wanted.to_f32();
+            let [cr, cg, cb, _] = chosen.to_f32();
+            let error = [wr - cr, wg - cg, wb - cb];
+
+            let (x, y) = (i % width, i / width);
+            let mut spread = |dx: isize, dy: usize, weight: f32| {
+                let Some(nx) = x.checked_add_signed(dx).filter(|nx| *nx < width) else {
+                    return;
+                };
+                if let Some(e) = errors.get_mut((y + dy) * width + nx) {
+                    for (e, error) in e.iter_mut().zip(error) {
+                        *e += error * weight;
+                    }
+                }
+            };
+
+            spread(1, 0, 7.0 / 16.0);
+            spread(-1, 1, 3.0 / 16.0);
+            spread(0, 1, 5.0 / 16.0);
+            spread(1, 1, 1.0 / 16.0);
+        }
+    }
+}
+
+/// A perceptually weighted ("redmean") distance between two colors.
+fn distance(a: Color, b: Color) -> u32 {
+    let mean = (a.r as i32 + b.r as i32) / 2;
+    let dr = a.r as i32 - b.r as i32;
+    let dg = a.g as i32 - b.g as i32;
+    let db = a.b as i32 - b.b as i32;
+
+    ((((512 + mean) * dr * dr) >> 8) + 4 * dg * dg + (((767 - mean) * db * db) >> 8)) as u32
+}
+
+const NAMED_COLORS: &[(&str, u32)] = &[
+    ("black", 0x000000),
+    ("silver", 0xc0c0c0),
+    ("gray", 0x808080),
+    ("grey", 0x808080),
+    ("white", 0xffffff),
+    ("maroon", 0x800000),
+    ("red", 0xff0000),
+    ("purple", 0x800080),
+    ("fuchsia", 0xff00ff),
+    ("magenta", 0xff00ff),
+    ("green", 0x008000),
+    ("lime", 0x00ff00),
+    ("olive", 0x808000),
+    ("yellow", 0xffff00),
+    ("navy", 0x000080),
+    ("blue", 0x0000ff),
+    ("teal", 0x008080),
+    ("aqua", 0x00ffff),
+    ("cyan", 0x00ffff),
+    ("orange", 0xffa500),
+    ("amber", 0xffbf00),
+    ("brown", 0xa52a2a),
+    ("pink", 0xffc0cb),
+    ("gold", 0xffd700),
+    ("beige", 0xf5f5dc),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("coral", 0xff7f50),
+    ("salmon", 0xfa8072),
+    ("tomato", 0xff6347),
+    ("crimson", 0xdc143c),
+    ("orangered", 0xff4500),
+    ("darkorange", 0xff8c00),
+    ("chocolate", 0xd2691e),
+    ("tan", 0xd2b48c),
+    ("violet", 0xee82ee),
+    ("indigo", 0x4b0082),
+    ("turquoise", 0x40e0d0),
+    ("skyblue", 0x87ceeb),
+    ("steelblue", 0x4682b4),
+    ("royalblue", 0x4169e1),
+    ("dodgerblue", 0x1e90ff),
+    ("darkblue", 0x00008b),
+    ("lightblue", 0xadd8e6),
+    ("darkgreen", 0x006400),
+    ("lightgreen", 0x90ee90),
+    ("limegreen", 0x32cd32),
+    ("forestgreen", 0x228b22),
+    ("darkred", 0x8b0000),
+    ("darkgray", 0xa9a9a9),
+    ("darkgrey", 0xa9a9a9),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("lightgray", 0xd3d3d3),
+    ("lightgrey", 0xd3d3d3),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hsv_hsl_roundtrip() {
+        let color = Color::rgb(255, 136, 0);
+        let (h, s, v) = color.to_hsv();
+        assert!((h - 32.0).abs() < 0.1 && s == 1.0 && v == 1.0);
+        assert_eq!(Color::from_hsv(h, s, v), color);
+
+        let (h, s, l) = color.to_hsl();
+        assert_eq!(Color::from_hsl(h, s, l), color);
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.25), Color::rgb(0, 128, 0));
+    }
+
+    #[test]
+    fn test_linear() {
+        let color = Color::rgb(10, 128, 250);
+        assert_eq!(Color::from_linear(color.to_linear()), color);
+        assert!((Color::rgb(128, 128, 128).to_linear()[0] - 0.2159).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_blend() {
+        let half_red = Color::rgba(255, 0, 0, 128);
+        assert_eq!(
+            half_red.over(Color::BLUE, AlphaMode::Blend),
+            Color::rgb(128, 0, 127)
+        );
+        assert_eq!(
+            half_red.over(Color::BLUE, AlphaMode::Mask(0.6)),
+            Color::BLUE
+        );
+        assert_eq!(half_red.over(Color::BLUE, AlphaMode::Opaque), Color::RED);
+        assert_eq!(
+            Color::rgb(255, 128, 0).blend(Color::rgb(128, 128, 128), BlendMode::Multiply),
+            Color::rgb(128, 64, 0)
+        );
+        assert_eq!(half_red.premultiplied(), Color::rgba(128, 0, 0, 128));
+        assert_eq!(half_red.premultiplied().unpremultiplied(), half_red);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("#f80".parse::<Color>().unwrap(), Color::rgb(255, 136, 0));
+        assert_eq!(
+            Color::from_hex("ff880080").unwrap(),
+            Color::rgba(255, 136, 0, 128)
+        );
+        assert_eq!("Orange".parse::<Color>().unwrap(), Color::rgb(255, 165, 0));
+        assert_eq!("ff0000".parse::<Color>().unwrap(), Color::RED);
+        assert!("#ff00".parse::<Color>().is_ok());
+        assert!("#ff0".parse::<Color>().is_ok());
+        assert!("#gg0000".parse::<Color>().is_err());
+        assert!("not a color".parse::<Color>().is_err());
+        assert_eq!(Color::rgba(255, 136, 0, 128).to_hex(), "#ff880080");
+    }
+
+    #[test]
+    fn test_gradient_and_palette() {
+        let gradient = Gradient::new([Color::BLACK, Color::WHITE]).with_stop(0.5, Color::RED);
+        assert_eq!(gradient.sample(-1.0), Color::BLACK);
+        assert_eq!(gradient.sample(0.5), Color::RED);
+        assert_eq!(gradient.sample(0.25), Color::rgb(128, 0, 0));
+        assert_eq!(gradient.sample(2.0), Color::WHITE);
+
+        let palette = Palette::grayscale(4);
+        assert_eq!(palette.colors()[1], Color::rgb(85, 85, 85));
+        assert_eq!(
+            palette.nearest(Color::rgb(100, 90, 80)),
+            Color::rgb(85, 85, 85)
+        );
+
+        let mut image = vec![Color::rgb(128, 128, 128); 16];
+        Palette::grayscale(2).dither(&mut image, 4);
+        let white = image.iter().filter(|c| **c == Color::WHITE).count();
+        assert_eq!(white, 8);
+    }
+}
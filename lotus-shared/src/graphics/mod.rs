@@ -0,0 +1,654 @@
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "internal")]
+use crate::content::ContentId;
+
+pub mod atlas;
+pub mod batch;
+pub mod color;
+
+/// A color in the RGBA format.
+///
+/// See [color] for conversions, blending, gradients and parsing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+impl Color {
+    pub const WHITE: Self = Self::rgb(255, 255, 255);
+    pub const BLACK: Self = Self::rgb(0, 0, 0);
+    pub const RED: Self = Self::rgb(255, 0, 0);
+    pub const GREEN: Self = Self::rgb(0, 255, 0);
+    pub const BLUE: Self = Self::rgb(0, 0, 255);
+    pub const YELLOW: Self = Self::rgb(255, 255, 0);
+    pub const CYAN: Self = Self::rgb(0, 255, 255);
+    pub const MAGENTA: Self = Self::rgb(255, 0, 255);
+
+    pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Self::rgba(r, g, b, 255)
+    }
+
+    pub const fn rgba(r: u8, g: u8, b: u8, a: u8) -> Self {
+        Self { r, g, b, a }
+    }
+}
+
+impl From<u32> for Color {
+    fn from(value: u32) -> Self {
+        let r = ((value >> 24) & 0xFF) as u8;
+        let g = ((value >> 16) & 0xFF) as u8;
+        let b = ((value >> 8) & 0xFF) as u8;
+        let a = (value & 0xFF) as u8;
+
+        Color::rgba(r, g, b, a)
+    }
+}
+
+impl From<Color> for u32 {
+    fn from(value: Color) -> Self {
+        let r = value.r as u32;
+        let g = value.g as u32;
+        let b = value.b as u32;
+        let a = value.a as u32;
+
+        (r << 24) | (g << 16) | (b << 8) | a
+    }
+}
+
+#[cfg(feature = "bevy")]
+mod _bevy {
+    use super::*;
+
+    impl From<bevy::color::Color> for Color {
+        fn from(value: bevy::color::Color) -> Self {
+            let value = value.to_srgba();
+
+            Self::rgba(
+                (value.red * 255.0) as u8,
+                (value.green * 255.0) as u8,
+                (value.blue * 255.0) as u8,
+                (value.alpha * 255.0) as u8,
+            )
+        }
+    }
+
+    impl From<Color> for bevy::color::Color {
+        fn from(value: Color) -> Self {
+            bevy::color::Color::srgba(
+                value.r as f32 / 255.0,
+                value.g as f32 / 255.0,
+                value.b as f32 / 255.0,
+                value.a as f32 / 255.0,
+            )
+        }
+    }
+}
+
+#[cfg(feature = "image")]
+mod _image {
+    use super::*;
+
+    impl From<image::Rgba<u8>> for Color {
+        fn from(value: image::Rgba<u8>) -> Self {
+            Self::rgba(value[0], value[1], value[2], value[3])
+        }
+    }
+
+    impl From<Color> for image::Rgba<u8> {
+        fn from(value: Color) -> Self {
+            [value.r, value.g, value.b, value.a].into()
+        }
+    }
+}
+
+pub mod textures {
+    use std::borrow::Cow;
+
+    use glam::{IVec2, Vec2};
+    use serde::{Deserialize, Serialize};
+
+    use crate::{
+        content::ContentId,
+        math::{Rectangle, UVec2},
+    };
+
+    use super::Color;
+
+    /// Options for creating a texture.
+    #[derive(Clone, Serialize, Deserialize)]
+    pub struct TextureCreationOptions<'a> {
+        /// The width of the texture.
+        pub width: u32,
+        /// The height of the texture.
+        pub height: u32,
+        /// The initial pixels of the texture as RGBA8, row by row starting at the top left.
+        /// Must contain exactly `width * height * 4` bytes. If `None`, the texture is transparent.
+        #[serde(
+            serialize_with = "serde_bytes::serialize",
+            deserialize_with = "deserialize_data"
+        )]
+        pub data: Option<Cow<'a, [u8]>>,
+        /// Whether to generate mipmaps for the texture.
+        pub mipmaps: bool,
+    }
+
+    impl<'a> TextureCreationOptions<'a> {
+        /// Creates the options for a texture with the given RGBA8 pixels, row by row starting at the top left.
+        ///
+        /// # Panics
+        /// Panics if `data` does not contain exactly `width * height * 4` bytes.
+        pub fn from_rgba(width: u32, height: u32, data: impl Into<Cow<'a, [u8]>>) -> Self {
+            let data = data.into();
+            assert_rgba_len(width, height, &data);
+
+            Self {
+                width,
+                height,
+                data: Some(data),
+                mipmaps: false,
+            }
+        }
+
+        /// Creates the options for a texture with the given pixels, row by row starting at the top left.
+        ///
+        /// # Panics
+        /// Panics if `colors` does not contain exactly `width * height` colors.
+        pub fn from_colors(width: u32, height: u32, colors: &[Color]) -> Self {
+            Self::from_rgba(width, height, colors_to_rgba(colors))
+        }
+
+        /// Sets whether to generate mipmaps for the texture.
+        pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+            self.mipmaps = mipmaps;
+            self
+        }
+    }
+
+    impl From<(u32, u32)> for TextureCreationOptions<'_> {
+        fn from((width, height): (u32, u32)) -> Self {
+            Self {
+                width,
+                height,
+                data: None,
+                mipmaps: false,
+            }
+        }
+    }
+
+    fn deserialize_data<'de, 'a, D>(deserializer: D) -> Result<Option<Cow<'a, [u8]>>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let data: Option<serde_bytes::ByteBuf> = serde_bytes::deserialize(deserializer)?;
+        Ok(data.map(|data| Cow::Owned(data.into_vec())))
+    }
+
+    /// Converts colors to RGBA8 bytes.
+    pub fn colors_to_rgba(colors: &[Color]) -> Vec<u8> {
+        colors
+            .iter()
+            .flat_map(|color| [color.r, color.g, color.b, color.a])
+            .collect()
+    }
+
+    /// Pixels read from a texture as RGBA8 bytes, row by row starting at the top left.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct PixelBuffer {
+        size: UVec2,
+        data: Box<[u8]>,
+    }
+
+    impl PixelBuffer {
+        /// Wraps the RGBA8 pixels of an area of the given size.
+        /// Fails if `data` does not contain exactly `width * height * 4` bytes.
+        pub fn new(size: UVec2, data: impl Into<Box<[u8]>>) -> Result<Self, PixelBufferError> {
+            let data = data.into();
+            check_rgba_len(size.x, size.y, &data)?;
+            Ok(Self { size, data })
+        }
+
+        pub fn size(&self) -> UVec2 {
+            self.size
+        }
+
+        pub fn width(&self) -> u32 {
+            self.size.x
+        }
+
+        pub fn height(&self) -> u32 {
+            self.size.y
+        }
+
+        /// Returns the raw RGBA8 bytes.
+        pub fn data(&self) -> &[u8] {
+            &self.data
+        }
+
+        /// Returns the raw RGBA8 bytes, e.g. to write them to another texture with
+        /// [TextureAction::write_region].
+        pub fn into_data(self) -> Box<[u8]> {
+            self.data
+        }
+
+        /// Returns the color of a pixel relative to the top left corner of the buffer.
+        pub fn get(&self, x: u32, y: u32) -> Option<Color> {
+            if x >= self.size.x || y >= self.size.y {
+                return None;
+            }
+
+            let i = (y as usize * self.size.x as usize + x as usize) * 4;
+            let [r, g, b, a] = self.data[i..i + 4] else {
+                unreachable!()
+            };
+            Some(Color::rgba(r, g, b, a))
+        }
+
+        /// Returns an iterator over all pixels, row by row.
+        pub fn colors(&self) -> impl Iterator<Item = Color> + '_ {
+            self.data
+                .chunks_exact(4)
+                .map(|p| Color::rgba(p[0], p[1], p[2], p[3]))
+        }
+
+        /// Returns the average color of all pixels, e.g. to compute the glare of a display.
+        /// An empty buffer is [Color::TRANSPARENT].
+        pub fn average(&self) -> Color {
+            let count = (self.data.len() / 4) as u64;
+            if count == 0 {
+                return Color::TRANSPARENT;
+            }
+
+            let mut sums = [0u64; 4];
+            for pixel in self.data.chunks_exact(4) {
+                for (sum, channel) in sums.iter_mut().zip(pixel) {
+                    *sum += *channel as u64;
+                }
+            }
+
+            let [r, g, b, a] = sums.map(|sum| ((sum + count / 2) / count) as u8);
+            Color::rgba(r, g, b, a)
+        }
+    }
+
+    /// The pixel data doesn't match the size of the area.
+    #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+    #[error("expected {width}x{height} RGBA8 pixels, got {len} bytes")]
+    pub struct PixelBufferError {
+        pub width: u32,
+        pub height: u32,
+        pub len: usize,
+    }
+
+    /// An error reading the pixels of a texture.
+    #[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+    pub enum ReadRegionError {
+        /// The pending actions can't be applied yet, e.g. cause of streaming assets.
+        /// Try again later, e.g. in the next tick.
+        #[error("the texture is not ready yet")]
+        NotReady,
+        #[error(transparent)]
+        InvalidData(#[from] PixelBufferError),
+    }
+
+    fn check_rgba_len(width: u32, height: u32, data: &[u8]) -> Result<(), PixelBufferError> {
+        if data.len() == width as usize * height as usize * 4 {
+            Ok(())
+        } else {
+            Err(PixelBufferError {
+                width,
+                height,
+                len: data.len(),
+            })
+        }
+    }
+
+    fn assert_rgba_len(width: u32, height: u32, data: &[u8]) {
+        if let Err(e) = check_rgba_len(width, height, data) {
+            panic!("{e}");
+        }
+    }
+
+    /// A handle to a texture.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    #[serde(transparent)]
+    pub struct TextureHandle(u32);
+
+    #[cfg(feature = "internal")]
+    impl TextureHandle {
+        /// Create a new texture handle.
+        pub fn new(value: u32) -> Self {
+            Self(value)
+        }
+
+        /// Get the ID of the texture handle.
+        pub fn id(&self) -> u32 {
+            self.0
+        }
+    }
+
+    /// An action to perform on a texture.
+    #[derive(Clone, Serialize, Deserialize)]
+    pub enum TextureAction {
+        /// Clear the texture with a color.
+        Clear(Color),
+        /// Draw pixels on the texture.
+        DrawPixels(Box<[DrawPixel]>),
+        /// Draw a rectangle on the texture.
+        DrawRect {
+            start: UVec2,
+            end: UVec2,
+            color: Color,
+        },
+        /// Draw text on the texture.
+        DrawText {
+            font: ContentId,
+            text: String,
+            top_left: IVec2,
+            letter_spacing: u32,
+            full_color: Option<Color>,
+            alpha_mode: AlphaMode,
+            target_rect: Option<Rectangle>,
+        },
+        /// Draw a content texture on the texture, e.g. an icon or a logo.
+        DrawTexture {
+            texture: ContentId,
+            options: DrawTextureOpts,
+        },
+        /// Draw a script texture on the texture.
+        DrawScriptTexture {
+            handle: TextureHandle,
+            options: DrawTextureOpts,
+        },
+        /// Replace the pixels of a region of the texture.
+        WriteRegion {
+            /// The region to write. Must lie within the texture.
+            region: Rectangle,
+            /// The new pixels of the region as RGBA8, row by row starting at the top left of the region.
+            /// Must contain exactly `region.width() * region.height() * 4` bytes.
+            #[serde(with = "serde_bytes")]
+            data: Box<[u8]>,
+        },
+        /// Draw a line on the texture.
+        DrawLine {
+            start: Vec2,
+            end: Vec2,
+            width: f32,
+            color: Color,
+            options: ShapeOptions,
+        },
+        /// Draw connected lines on the texture. If `closed` is set, the last point is connected to the first one.
+        DrawPolyline {
+            points: Box<[Vec2]>,
+            closed: bool,
+            width: f32,
+            color: Color,
+            options: ShapeOptions,
+        },
+        /// Draw the outline of a rectangle on the texture.
+        DrawRectOutline {
+            start: UVec2,
+            end: UVec2,
+            width: f32,
+            color: Color,
+            options: ShapeOptions,
+        },
+        /// Draw a rectangle with rounded corners on the texture.
+        DrawRoundedRect {
+            start: UVec2,
+            end: UVec2,
+            radius: f32,
+            fill: FillMode,
+            color: Color,
+            options: ShapeOptions,
+        },
+        /// Draw a circle on the texture.
+        DrawCircle {
+            center: Vec2,
+            radius: f32,
+            fill: FillMode,
+            color: Color,
+            options: ShapeOptions,
+        },
+        /// Draw an arc of a circle on the texture. Angles are in radians, clockwise starting at the positive x axis.
+        /// If filled, the arc is drawn as a pie slice.
+        DrawArc {
+            center: Vec2,
+            radius: f32,
+            start_angle: f32,
+            end_angle: f32,
+            fill: FillMode,
+            color: Color,
+            options: ShapeOptions,
+        },
+        /// Draw a filled polygon on the texture. Self-intersecting polygons are filled using the even-odd rule.
+        DrawPolygon {
+            points: Box<[Vec2]>,
+            color: Color,
+            options: ShapeOptions,
+        },
+    }
+
+    impl TextureAction {
+        /// Creates a [TextureAction::WriteRegion] action for the given RGBA8 pixels.
+        ///
+        /// # Panics
+        /// Panics if `data` does not contain exactly `region.width() * region.height() * 4` bytes.
+        pub fn write_region(region: Rectangle, data: impl Into<Box<[u8]>>) -> Self {
+            let data = data.into();
+            assert_rgba_len(region.width(), region.height(), &data);
+
+            Self::WriteRegion { region, data }
+        }
+    }
+
+    /// Options for drawing shapes.
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+    pub struct ShapeOptions {
+        /// Whether to smooth the edges of the shape. Anti-aliased edges are only blended with
+        /// [AlphaMode::Blend], otherwise they are treated according to the alpha mode.
+        pub anti_alias: bool,
+        /// How the shape is combined with the existing pixels.
+        pub alpha_mode: AlphaMode,
+    }
+
+    impl Default for ShapeOptions {
+        fn default() -> Self {
+            Self {
+                anti_alias: true,
+                alpha_mode: AlphaMode::Blend,
+            }
+        }
+    }
+
+    impl ShapeOptions {
+        /// Options drawing hard pixel edges without blending, e.g. for pixel displays.
+        pub const ALIASED: Self = Self {
+            anti_alias: false,
+            alpha_mode: AlphaMode::Opaque,
+        };
+    }
+
+    /// Whether a shape is filled or only its outline is drawn.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+    pub enum FillMode {
+        /// Fill the shape.
+        #[default]
+        Fill,
+        /// Draw the outline of the shape with the given width in pixels.
+        Stroke(f32),
+    }
+
+    /// Controls how alpha (transparency) is handled when drawing.
+    #[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+    pub enum AlphaMode {
+        /// The texture is drawn completely opaque, ignoring alpha values.
+        /// Any pixels drawn will completely replace the existing pixels.
+        #[default]
+        Opaque,
+        /// Alpha values below the threshold are considered fully transparent,
+        /// while values above or equal to the threshold are considered fully opaque.
+        /// The threshold should be between 0.0 and 1.0.
+        Mask(f32),
+        /// Alpha values are used to blend the new pixels with existing pixels.
+        /// The alpha channel determines the opacity of each pixel being drawn.
+        Blend,
+    }
+
+    /// Options for drawing a texture.
+    #[derive(Clone, Copy, Serialize, Deserialize)]
+    pub struct DrawTextureOpts {
+        /// The source rectangle of the texture to draw.
+        pub source_rect: Option<Rectangle>,
+        /// The target rectangle of the texture to draw to.
+        pub target_rect: Option<Rectangle>,
+        /// How the texture is combined with the existing pixels. Defaults to
+        /// [AlphaMode::Blend], which is how textures were drawn before this option existed.
+        #[serde(default = "DrawTextureOpts::default_alpha_mode")]
+        pub alpha_mode: AlphaMode,
+    }
+
+    impl Default for DrawTextureOpts {
+        fn default() -> Self {
+            Self {
+                source_rect: None,
+                target_rect: None,
+                alpha_mode: Self::default_alpha_mode(),
+            }
+        }
+    }
+
+    impl DrawTextureOpts {
+        fn default_alpha_mode() -> AlphaMode {
+            AlphaMode::Blend
+        }
+
+        /// Draws the given part of the texture.
+        pub fn with_source_rect(mut self, rect: Rectangle) -> Self {
+            self.source_rect = Some(rect);
+            self
+        }
+
+        /// Draws the texture to the given part of the target texture, scaling it if necessary.
+        pub fn with_target_rect(mut self, rect: Rectangle) -> Self {
+            self.target_rect = Some(rect);
+            self
+        }
+
+        /// Sets how the texture is combined with the existing pixels.
+        pub fn with_alpha_mode(mut self, alpha_mode: AlphaMode) -> Self {
+            self.alpha_mode = alpha_mode;
+            self
+        }
+    }
+
+    /// A pixel to draw on a texture.
+    #[derive(Clone, Copy, Serialize, Deserialize)]
+    pub struct DrawPixel {
+        /// The position of the pixel.
+        pub pos: UVec2,
+        /// The color of the pixel.
+        pub color: Color,
+    }
+
+    impl From<(UVec2, Color)> for DrawPixel {
+        fn from((position, color): (UVec2, Color)) -> Self {
+            Self {
+                pos: position,
+                color,
+            }
+        }
+    }
+
+    impl From<(u32, u32, Color)> for DrawPixel {
+        fn from((x, y, color): (u32, u32, Color)) -> Self {
+            Self {
+                pos: UVec2 { x, y },
+                color,
+            }
+        }
+    }
+}
+
+#[cfg(feature = "internal")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DrawableTextureProperties {
+    pub width: u32,
+    pub height: u32,
+    pub texture_variable_id: String,
+    pub font: ContentId,
+    pub text_variable_id: String,
+    pub set_color: bool,
+    pub color: Color,
+    pub horizontal_alignment: TextHorizontalAlignment,
+    pub vertical_alignment: TextVerticalAlignment,
+    pub alignment_resolution: u8,
+}
+
+/// The horizontal alignment of text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextHorizontalAlignment {
+    #[default]
+    Center,
+    Left,
+    Right,
+    /// Centered, rounding down to whole pixels if the text can't be centered exactly.
+    IntCenterLeft,
+    /// Centered, rounding up to whole pixels if the text can't be centered exactly.
+    IntCenterRight,
+}
+
+/// The vertical alignment of text.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TextVerticalAlignment {
+    #[default]
+    Center,
+    Top,
+    Bottom,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::textures::*;
+
+    #[test]
+    fn test_draw_texture_opts_default_blends() {
+        assert!(matches!(
+            DrawTextureOpts::default().alpha_mode,
+            AlphaMode::Blend
+        ));
+
+        let options: DrawTextureOpts =
+            serde_json::from_str(r#"{"source_rect":null,"target_rect":null}"#).unwrap();
+        assert!(matches!(options.alpha_mode, AlphaMode::Blend));
+    }
+
+    #[test]
+    fn test_pixel_buffer_length() {
+        use crate::math::UVec2;
+
+        let buffer =
+            PixelBuffer::new(UVec2::new(2, 1), vec![255, 0, 0, 255, 0, 0, 255, 128]).unwrap();
+        assert_eq!(
+            buffer.get(1, 0),
+            Some(crate::graphics::Color::rgba(0, 0, 255, 128))
+        );
+        assert_eq!(buffer.get(2, 0), None);
+
+        assert_eq!(
+            PixelBuffer::new(UVec2::new(2, 2), vec![0; 8]),
+            Err(PixelBufferError {
+                width: 2,
+                height: 2,
+                len: 8
+            })
+        );
+
+        let empty = PixelBuffer::new(UVec2::new(0, 3), Vec::new()).unwrap();
+        assert_eq!(empty.colors().count(), 0);
+        assert_eq!(empty.average(), crate::graphics::Color::TRANSPARENT);
+    }
+}
@@ -0,0 +1,710 @@
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+pub mod recording;
+
+/// The state kind of an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum ActionStateKind {
+    /// The action is not being performed.
+    None,
+    /// The action has just been pressed.
+    JustPressed,
+    /// The action is currently pressed.
+    Pressed,
+    /// The action has just been released.
+    JustReleased,
+}
+
+impl ActionStateKind {
+    /// Returns `true` if the action has just been pressed.
+    pub fn is_just_pressed(self) -> bool {
+        matches!(self, ActionStateKind::JustPressed)
+    }
+
+    /// Returns `true` if the action is currently pressed.
+    pub fn is_pressed(self) -> bool {
+        matches!(
+            self,
+            ActionStateKind::JustPressed | ActionStateKind::Pressed
+        )
+    }
+
+    /// Returns `true` if the action has just been released.
+    pub fn is_just_released(self) -> bool {
+        matches!(self, ActionStateKind::JustReleased)
+    }
+
+    /// Returns `true` if the action is currently released.
+    pub fn is_released(self) -> bool {
+        matches!(self, ActionStateKind::JustReleased | ActionStateKind::None)
+    }
+}
+
+/// The state of an action.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ActionState {
+    pub kind: ActionStateKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cockpit_index: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uv: Option<Vec2>,
+}
+
+macro_rules! key_code_struct {
+    ($($key:ident),*) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        #[serde(rename_all = "snake_case")]
+        pub enum KeyCode {
+            $($key,)*
+        }
+
+        #[cfg(feature = "bevy")]
+        mod _bevy {
+            use super::*;
+
+            impl TryFrom<bevy::input::keyboard::KeyCode> for KeyCode {
+                type Error = ();
+
+                fn try_from(key: bevy::input::keyboard::KeyCode) -> Result<Self, Self::Error> {
+                    match key {
+                        $(bevy::input::keyboard::KeyCode::$key => Ok(KeyCode::$key),)*
+                        _ => Err(()),
+                    }
+                }
+            }
+
+            impl From<KeyCode> for bevy::input::keyboard::KeyCode {
+                fn from(key: KeyCode) -> Self {
+                    match key {
+                        $(KeyCode::$key => bevy::input::keyboard::KeyCode::$key,)*
+                    }
+                }
+            }
+        }
+    }
+}
+
+key_code_struct! {
+    Space,
+    ArrowUp,
+    ArrowDown,
+    ArrowLeft,
+    ArrowRight,
+    KeyA,
+    KeyB,
+    KeyC,
+    KeyD,
+    KeyE,
+    KeyF,
+    KeyG,
+    KeyH,
+    KeyI,
+    KeyJ,
+    KeyK,
+    KeyL,
+    KeyM,
+    KeyN,
+    KeyO,
+    KeyP,
+    KeyQ,
+    KeyR,
+    KeyS,
+    KeyT,
+    KeyU,
+    KeyV,
+    KeyW,
+    KeyX,
+    KeyY,
+    KeyZ,
+    Digit0,
+    Digit1,
+    Digit2,
+    Digit3,
+    Digit4,
+    Digit5,
+    Digit6,
+    Digit7,
+    Digit8,
+    Digit9,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadSubtract,
+    NumpadMultiply,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadEnter,
+    NumpadEqual,
+    NumLock,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Enter,
+    Escape,
+    Tab,
+    Backspace,
+    Insert,
+    Delete,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    CapsLock,
+    ScrollLock,
+    PrintScreen,
+    Pause,
+    ShiftLeft,
+    ShiftRight,
+    ControlLeft,
+    ControlRight,
+    AltLeft,
+    AltRight,
+    SuperLeft,
+    SuperRight,
+    Minus,
+    Equal,
+    BracketLeft,
+    BracketRight,
+    Backslash,
+    IntlBackslash,
+    Semicolon,
+    Quote,
+    Backquote,
+    Comma,
+    Period,
+    Slash
+}
+
+impl KeyCode {
+    /// Returns the modifier this key sets, if it is a modifier key.
+    pub fn modifier(self) -> Option<Modifiers> {
+        match self {
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => Some(Modifiers::SHIFT),
+            KeyCode::ControlLeft | KeyCode::ControlRight => Some(Modifiers::CTRL),
+            KeyCode::AltLeft | KeyCode::AltRight => Some(Modifiers::ALT),
+            _ => None,
+        }
+    }
+
+    /// Returns a chord of this key and the given modifiers.
+    pub fn with_modifiers(self, modifiers: Modifiers) -> KeyChord {
+        KeyChord::new(self).with_modifiers(modifiers)
+    }
+}
+
+/// Shows the key like on a keyboard, e.g. `A`, `1`, `Num 1` or `Page Up`.
+impl std::fmt::Display for KeyCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = format!("{self:?}");
+        let name = match name.as_str() {
+            "ControlLeft" | "ControlRight" => "Ctrl",
+            "ShiftLeft" | "ShiftRight" => "Shift",
+            "AltLeft" | "AltRight" => "Alt",
+            "Quote" => "'",
+            "Backquote" => "`",
+            "Minus" => "-",
+            "Equal" => "=",
+            "BracketLeft" => "[",
+            "BracketRight" => "]",
+            "Backslash" | "IntlBackslash" => "\\",
+            "Semicolon" => ";",
+            "Comma" => ",",
+            "Period" => ".",
+            "Slash" => "/",
+            _ => {
+                let name = name
+                    .strip_prefix("Key")
+                    .or_else(|| name.strip_prefix("Digit"))
+                    .unwrap_or(&name);
+                let name = match name.strip_prefix("Numpad") {
+                    Some(rest) => format!("Num {rest}"),
+                    None => name.to_string(),
+                };
+
+                // Split camel case, e.g. `PageUp` into `Page Up`.
+                let mut words = String::new();
+                for (i, c) in name.char_indices() {
+                    if i > 0 && c.is_ascii_uppercase() && !words.ends_with(' ') {
+                        words.push(' ');
+                    }
+                    words.push(c);
+                }
+                return f.write_str(&words);
+            }
+        };
+        f.write_str(name)
+    }
+}
+
+/// A mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Back,
+    Forward,
+}
+
+#[cfg(feature = "bevy")]
+mod _bevy_mouse {
+    use super::*;
+
+    impl TryFrom<bevy::input::mouse::MouseButton> for MouseButton {
+        type Error = ();
+
+        fn try_from(button: bevy::input::mouse::MouseButton) -> Result<Self, Self::Error> {
+            match button {
+                bevy::input::mouse::MouseButton::Left => Ok(MouseButton::Left),
+                bevy::input::mouse::MouseButton::Right => Ok(MouseButton::Right),
+                bevy::input::mouse::MouseButton::Middle => Ok(MouseButton::Middle),
+                bevy::input::mouse::MouseButton::Back => Ok(MouseButton::Back),
+                bevy::input::mouse::MouseButton::Forward => Ok(MouseButton::Forward),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl From<MouseButton> for bevy::input::mouse::MouseButton {
+        fn from(button: MouseButton) -> Self {
+            match button {
+                MouseButton::Left => bevy::input::mouse::MouseButton::Left,
+                MouseButton::Right => bevy::input::mouse::MouseButton::Right,
+                MouseButton::Middle => bevy::input::mouse::MouseButton::Middle,
+                MouseButton::Back => bevy::input::mouse::MouseButton::Back,
+                MouseButton::Forward => bevy::input::mouse::MouseButton::Forward,
+            }
+        }
+    }
+}
+
+/// The modifier keys that have to be held for a [KeyChord]. Left and right keys are treated
+/// the same.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub const NONE: Self = Self {
+        shift: false,
+        ctrl: false,
+        alt: false,
+    };
+    pub const SHIFT: Self = Self {
+        shift: true,
+        ..Self::NONE
+    };
+    pub const CTRL: Self = Self {
+        ctrl: true,
+        ..Self::NONE
+    };
+    pub const ALT: Self = Self {
+        alt: true,
+        ..Self::NONE
+    };
+
+    /// Returns the modifiers set by the given pressed keys.
+    pub fn from_keys(keys: impl IntoIterator<Item = KeyCode>) -> Self {
+        keys.into_iter()
+            .filter_map(KeyCode::modifier)
+            .fold(Self::NONE, |a, b| a | b)
+    }
+
+    /// Returns `true` if no modifier is set.
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// Returns `true` if all modifiers of `other` are set in `self`.
+    pub fn contains(self, other: Modifiers) -> bool {
+        (self | other) == self
+    }
+}
+
+impl std::ops::BitOr for Modifiers {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self {
+            shift: self.shift || rhs.shift,
+            ctrl: self.ctrl || rhs.ctrl,
+            alt: self.alt || rhs.alt,
+        }
+    }
+}
+
+/// A key or mouse button that can be bound to an action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InputButton {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl From<KeyCode> for InputButton {
+    fn from(key: KeyCode) -> Self {
+        Self::Key(key)
+    }
+}
+
+impl From<MouseButton> for InputButton {
+    fn from(button: MouseButton) -> Self {
+        Self::Mouse(button)
+    }
+}
+
+/// A key or mouse button together with the modifiers that have to be held, e.g. `Ctrl+F1`.
+///
+/// A chord only matches if exactly its modifiers are held, so `F1` and `Ctrl+F1` can be bound to
+/// different actions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct KeyChord {
+    pub button: InputButton,
+    #[serde(default, skip_serializing_if = "Modifiers::is_empty")]
+    pub modifiers: Modifiers,
+}
+
+impl KeyChord {
+    pub fn new(button: impl Into<InputButton>) -> Self {
+        Self {
+            button: button.into(),
+            modifiers: Modifiers::NONE,
+        }
+    }
+
+    pub fn with_modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = self.modifiers | modifiers;
+        self
+    }
+
+    pub fn with_shift(self) -> Self {
+        self.with_modifiers(Modifiers::SHIFT)
+    }
+
+    pub fn with_ctrl(self) -> Self {
+        self.with_modifiers(Modifiers::CTRL)
+    }
+
+    pub fn with_alt(self) -> Self {
+        self.with_modifiers(Modifiers::ALT)
+    }
+
+    /// Returns `true` if the chord is triggered by the button while exactly the given
+    /// modifiers are held.
+    pub fn matches(&self, button: InputButton, held: Modifiers) -> bool {
+        self.button == button && self.modifiers == held
+    }
+}
+
+/// Shows the chord like `Ctrl+Shift+F1`.
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.shift {
+            f.write_str("Shift+")?;
+        }
+        if self.modifiers.alt {
+            f.write_str("Alt+")?;
+        }
+
+        match self.button {
+            InputButton::Key(key) => write!(f, "{key}"),
+            InputButton::Mouse(button) => write!(f, "Mouse {button:?}"),
+        }
+    }
+}
+
+impl From<KeyCode> for KeyChord {
+    fn from(key: KeyCode) -> Self {
+        Self::new(key)
+    }
+}
+
+impl From<MouseButton> for KeyChord {
+    fn from(button: MouseButton) -> Self {
+        Self::new(button)
+    }
+}
+
+impl From<InputButton> for KeyChord {
+    fn from(button: InputButton) -> Self {
+        Self::new(button)
+    }
+}
+
+/// An axis of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GamepadAxis {
+    LeftStickX,
+    LeftStickY,
+    /// The left trigger on most gamepads.
+    LeftZ,
+    RightStickX,
+    RightStickY,
+    /// The right trigger on most gamepads.
+    RightZ,
+}
+
+#[cfg(feature = "bevy")]
+mod _bevy_gamepad {
+    use super::*;
+
+    impl TryFrom<bevy::input::gamepad::GamepadAxis> for GamepadAxis {
+        type Error = ();
+
+        fn try_from(axis: bevy::input::gamepad::GamepadAxis) -> Result<Self, Self::Error> {
+            match axis {
+                bevy::input::gamepad::GamepadAxis::LeftStickX => Ok(GamepadAxis::LeftStickX),
+                bevy::input::gamepad::GamepadAxis::LeftStickY => Ok(GamepadAxis::LeftStickY),
+                bevy::input::gamepad::GamepadAxis::LeftZ => Ok(GamepadAxis::LeftZ),
+                bevy::input::gamepad::GamepadAxis::RightStickX => Ok(GamepadAxis::RightStickX),
+                bevy::input::gamepad::GamepadAxis::RightStickY => Ok(GamepadAxis::RightStickY),
+                bevy::input::gamepad::GamepadAxis::RightZ => Ok(GamepadAxis::RightZ),
+                _ => Err(()),
+            }
+        }
+    }
+
+    impl From<GamepadAxis> for bevy::input::gamepad::GamepadAxis {
+        fn from(axis: GamepadAxis) -> Self {
+            match axis {
+                GamepadAxis::LeftStickX => bevy::input::gamepad::GamepadAxis::LeftStickX,
+                GamepadAxis::LeftStickY => bevy::input::gamepad::GamepadAxis::LeftStickY,
+                GamepadAxis::LeftZ => bevy::input::gamepad::GamepadAxis::LeftZ,
+                GamepadAxis::RightStickX => bevy::input::gamepad::GamepadAxis::RightStickX,
+                GamepadAxis::RightStickY => bevy::input::gamepad::GamepadAxis::RightStickY,
+                GamepadAxis::RightZ => bevy::input::gamepad::GamepadAxis::RightZ,
+            }
+        }
+    }
+}
+
+/// An input that provides the value of an axis action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisBinding {
+    /// An axis of a gamepad.
+    Gamepad(GamepadAxis),
+    /// An axis of another game controller, e.g. a joystick or throttle quadrant, by its index.
+    Joystick(u8),
+    /// Two keys, buttons or chords moving the value towards `-1.0` and `1.0` while held.
+    Keys {
+        negative: KeyChord,
+        positive: KeyChord,
+    },
+    /// The mouse wheel.
+    MouseWheel,
+}
+
+impl From<GamepadAxis> for AxisBinding {
+    fn from(axis: GamepadAxis) -> Self {
+        Self::Gamepad(axis)
+    }
+}
+
+impl AxisBinding {
+    /// Creates a binding moving the value with two keys, buttons or chords.
+    pub fn keys(negative: impl Into<KeyChord>, positive: impl Into<KeyChord>) -> Self {
+        Self::Keys {
+            negative: negative.into(),
+            positive: positive.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for AxisBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AxisBinding::Gamepad(axis) => write!(f, "Gamepad {axis:?}"),
+            AxisBinding::Joystick(index) => write!(f, "Joystick Axis {index}"),
+            AxisBinding::Keys { negative, positive } => write!(f, "{negative} / {positive}"),
+            AxisBinding::MouseWheel => f.write_str("Mouse Wheel"),
+        }
+    }
+}
+
+/// The range of the value of an axis action.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisRange {
+    /// From `-1.0` to `1.0`, centered at `0.0`, e.g. steering or a combined master controller.
+    #[default]
+    Bipolar,
+    /// From `0.0` to `1.0`, e.g. a brake valve or a throttle. The raw range is mapped to
+    /// `0.0..=1.0` first, so the dead zone and the curve apply around the rest position at `-1.0`.
+    Unipolar,
+}
+
+/// Shapes the response of an axis, applied after the dead zone.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisCurve {
+    #[default]
+    Linear,
+    /// Raises the magnitude to the given power. Values above `1.0` give finer control near the
+    /// center.
+    Power(f32),
+    /// Blends linear and cubic response, `0.0` being linear and `1.0` cubic.
+    Expo(f32),
+}
+
+impl AxisCurve {
+    /// Applies the curve to a value between `-1.0` and `1.0`, keeping its sign.
+    pub fn apply(self, value: f32) -> f32 {
+        match self {
+            AxisCurve::Linear => value,
+            AxisCurve::Power(power) => value.abs().powf(power).copysign(value),
+            AxisCurve::Expo(expo) => {
+                let expo = expo.clamp(0.0, 1.0);
+                value * (1.0 - expo) + value.powi(3) * expo
+            }
+        }
+    }
+}
+
+/// How the raw value of an axis is turned into the value of an axis action.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AxisOptions {
+    /// Raw magnitudes below this are `0.0`. The remaining range is stretched back to `1.0`.
+    pub dead_zone: f32,
+    pub invert: bool,
+    pub curve: AxisCurve,
+    pub range: AxisRange,
+}
+
+impl AxisOptions {
+    pub fn with_dead_zone(mut self, dead_zone: f32) -> Self {
+        self.dead_zone = dead_zone;
+        self
+    }
+
+    pub fn with_invert(mut self, invert: bool) -> Self {
+        self.invert = invert;
+        self
+    }
+
+    pub fn with_curve(mut self, curve: AxisCurve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn with_range(mut self, range: AxisRange) -> Self {
+        self.range = range;
+        self
+    }
+
+    /// Turns a raw value between `-1.0` and `1.0` into the value of the action, applying
+    /// inversion, range, dead zone and curve in that order.
+    pub fn apply(&self, raw: f32) -> f32 {
+        let value = raw.clamp(-1.0, 1.0);
+        let value = if self.invert { -value } else { value };
+        let value = match self.range {
+            AxisRange::Bipolar => value,
+            AxisRange::Unipolar => (value + 1.0) / 2.0,
+        };
+
+        let dead_zone = self.dead_zone.clamp(0.0, 0.99);
+        let magnitude = ((value.abs() - dead_zone) / (1.0 - dead_zone)).max(0.0);
+        self.curve.apply(magnitude.copysign(value))
+    }
+}
+
+/// The state of an axis action.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AxisState {
+    /// The value with the [AxisOptions] of the action applied.
+    pub value: f32,
+    /// The cockpit the input is routed to, like [ActionState::cockpit_index].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cockpit_index: Option<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chord_matches() {
+        let chord = KeyCode::F1.with_modifiers(Modifiers::CTRL);
+        let held = Modifiers::from_keys([KeyCode::ControlRight, KeyCode::KeyA]);
+
+        assert!(chord.matches(KeyCode::F1.into(), held));
+        assert!(!chord.matches(KeyCode::F1.into(), held | Modifiers::SHIFT));
+        assert!(!KeyChord::from(KeyCode::F1).matches(KeyCode::F1.into(), held));
+        assert!(Modifiers::CTRL.contains(Modifiers::NONE));
+    }
+
+    #[test]
+    fn test_chord_serialization() {
+        let json = serde_json::to_string(&KeyChord::from(KeyCode::PageUp)).unwrap();
+        assert_eq!(json, r#"{"button":{"key":"page_up"}}"#);
+
+        let chord: KeyChord =
+            serde_json::from_str(r#"{"button":{"mouse":"left"},"modifiers":{"shift":true}}"#)
+                .unwrap();
+        assert_eq!(chord, KeyChord::new(MouseButton::Left).with_shift());
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(KeyCode::KeyA.to_string(), "A");
+        assert_eq!(KeyCode::Numpad1.to_string(), "Num 1");
+        assert_eq!(KeyCode::PageUp.to_string(), "Page Up");
+        assert_eq!(KeyCode::NumpadAdd.to_string(), "Num Add");
+        assert_eq!(
+            KeyChord::new(KeyCode::F1)
+                .with_shift()
+                .with_ctrl()
+                .to_string(),
+            "Ctrl+Shift+F1"
+        );
+        assert_eq!(KeyChord::new(MouseButton::Left).to_string(), "Mouse Left");
+    }
+
+    #[test]
+    fn test_axis_options() {
+        let options = AxisOptions::default().with_dead_zone(0.25);
+        assert_eq!(options.apply(0.125), 0.0);
+        assert_eq!(options.apply(-0.625), -0.5);
+        assert_eq!(options.apply(2.0), 1.0);
+
+        let options = options
+            .with_invert(true)
+            .with_curve(AxisCurve::Power(2.0))
+            .with_range(AxisRange::Unipolar);
+        // The dead zone is at the rest position and the curve refines the start of the travel.
+        assert_eq!(options.apply(1.0), 0.0);
+        assert_eq!(options.apply(0.5), 0.0);
+        assert_eq!(options.apply(-0.25), 0.25);
+        assert_eq!(options.apply(-1.0), 1.0);
+    }
+}
@@ -0,0 +1,151 @@
+//! Recorded input of a script, to replay a session for reproducing bugs.
+//!
+//! A recording has one [InputFrame] per tick with everything the script got from the engine's
+//! input functions. Recordings are stored as JSON Lines, a header line followed by one frame per
+//! line, so they can be attached to bug reports and inspected by hand.
+
+use std::collections::BTreeMap;
+
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+use super::{ActionState, AxisState};
+use crate::action::ActionEvent;
+
+/// The version of the recording format.
+pub const RECORDING_VERSION: u32 = 1;
+
+/// The first line of a recording.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub version: u32,
+    /// The tick of the script in which the recording started.
+    pub start_tick: u64,
+}
+
+/// The input of a script in one tick. Only values the script queried are recorded.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct InputFrame {
+    /// Action events received as messages.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub action_events: Vec<ActionEvent>,
+    /// Results of `action::state`, by action.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub action_states: BTreeMap<String, ActionState>,
+    /// Results of `action::axis_state`, by action.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub axis_states: BTreeMap<String, AxisState>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse_delta: Option<Vec2>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse_position: Option<Vec2>,
+    /// The raw value of the mouse steering mode.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mouse_steering_mode: Option<u32>,
+}
+
+/// The recorded input of a session, one frame per tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputRecording {
+    pub header: RecordingHeader,
+    pub frames: Vec<InputFrame>,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordingError {
+    #[error("recording not found: {0}")]
+    NotFound(String),
+    #[error("the recording is empty")]
+    Empty,
+    #[error("unsupported recording version {0}")]
+    UnsupportedVersion(u32),
+    #[error("invalid recording on line {line}: {error}")]
+    Json {
+        line: usize,
+        error: serde_json::Error,
+    },
+}
+
+impl InputRecording {
+    /// Creates an empty recording starting at the given tick.
+    pub fn new(start_tick: u64) -> Self {
+        Self {
+            header: RecordingHeader {
+                version: RECORDING_VERSION,
+                start_tick,
+            },
+            frames: Vec::new(),
+        }
+    }
+
+    /// Returns the recording as JSON Lines.
+    pub fn to_json_lines(&self) -> String {
+        let mut lines = serde_json::to_string(&self.header).unwrap();
+        lines.push('\n');
+
+        for frame in &self.frames {
+            lines.push_str(&serde_json::to_string(frame).unwrap());
+            lines.push('\n');
+        }
+
+        lines
+    }
+
+    /// Parses a recording from JSON Lines.
+    pub fn from_json_lines(lines: &str) -> Result<Self, RecordingError> {
+        let mut lines = lines.lines().enumerate();
+        let parse_error = |line: usize| move |error| RecordingError::Json { line, error };
+
+        let (index, header) = lines.next().ok_or(RecordingError::Empty)?;
+        let header: RecordingHeader =
+            serde_json::from_str(header).map_err(parse_error(index + 1))?;
+        if header.version != RECORDING_VERSION {
+            return Err(RecordingError::UnsupportedVersion(header.version));
+        }
+
+        let frames = lines
+            .map(|(index, line)| serde_json::from_str(line).map_err(parse_error(index + 1)))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { header, frames })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::ActionStateKind;
+
+    #[test]
+    fn test_json_lines_roundtrip() {
+        let mut recording = InputRecording::new(42);
+        recording.frames.push(InputFrame::default());
+        recording.frames.push(InputFrame {
+            action_states: [(
+                "horn".to_string(),
+                ActionState {
+                    kind: ActionStateKind::JustPressed,
+                    cockpit_index: Some(0),
+                    uv: None,
+                },
+            )]
+            .into(),
+            mouse_delta: Some(Vec2::new(1.0, -2.0)),
+            ..Default::default()
+        });
+
+        let lines = recording.to_json_lines();
+        assert_eq!(lines.lines().count(), 3);
+        assert_eq!(lines.lines().nth(1), Some("{}"));
+        assert_eq!(InputRecording::from_json_lines(&lines).unwrap(), recording);
+
+        assert!(matches!(
+            InputRecording::from_json_lines(""),
+            Err(RecordingError::Empty)
+        ));
+        assert!(matches!(
+            InputRecording::from_json_lines("{\"version\":1,\"start_tick\":0}\nnope"),
+            Err(RecordingError::Json { line: 2, .. })
+        ));
+    }
+}
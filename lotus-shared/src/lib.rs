@@ -5,6 +5,7 @@ pub mod font;
 pub mod gizmos;
 pub mod graphics;
 pub mod input;
+pub mod log;
 pub mod math;
 pub mod message;
 pub mod pis;
@@ -0,0 +1,397 @@
+//! Log records sent from scripts to the engine, and filtering them by level and target.
+
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+/// The level of a log message, ordered from least to most severe.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr, Deserialize_repr,
+)]
+#[repr(i8)]
+pub enum Level {
+    /// Very detailed information, e.g. per-tick values.
+    Trace = -1,
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+    Error = 3,
+}
+
+impl Level {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Trace => "trace",
+            Self::Debug => "debug",
+            Self::Info => "info",
+            Self::Warn => "warn",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseLogFilterError {
+    #[error("unknown log level: {0}")]
+    UnknownLevel(String),
+}
+
+impl FromStr for Level {
+    type Err = ParseLogFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "trace" => Ok(Self::Trace),
+            "debug" => Ok(Self::Debug),
+            "info" => Ok(Self::Info),
+            "warn" | "warning" => Ok(Self::Warn),
+            "error" => Ok(Self::Error),
+            _ => Err(ParseLogFilterError::UnknownLevel(s.to_string())),
+        }
+    }
+}
+
+/// The least severe level that is logged, or [LevelFilter::Off].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelFilter {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Off,
+}
+
+impl LevelFilter {
+    /// Returns `true` if messages of the given level pass the filter.
+    pub fn allows(&self, level: Level) -> bool {
+        LevelFilter::from(level) >= *self
+    }
+}
+
+impl From<Level> for LevelFilter {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Trace => Self::Trace,
+            Level::Debug => Self::Debug,
+            Level::Info => Self::Info,
+            Level::Warn => Self::Warn,
+            Level::Error => Self::Error,
+        }
+    }
+}
+
+impl FromStr for LevelFilter {
+    type Err = ParseLogFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().eq_ignore_ascii_case("off") {
+            Ok(Self::Off)
+        } else {
+            s.parse::<Level>().map(Into::into)
+        }
+    }
+}
+
+impl fmt::Display for LevelFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Trace => f.write_str("trace"),
+            Self::Debug => f.write_str("debug"),
+            Self::Info => f.write_str("info"),
+            Self::Warn => f.write_str("warn"),
+            Self::Error => f.write_str("error"),
+            Self::Off => f.write_str("off"),
+        }
+    }
+}
+
+/// Filters log messages by level, with separate levels per target.
+///
+/// A target is usually the module path of the code that logs, like `my_script::doors`.
+/// A target level applies to the target and everything below it, the most specific one wins.
+///
+/// The textual form is a comma separated list of a default level and `target=level` pairs,
+/// e.g. `info,my_script::doors=trace,my_script::ibis=off`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "RawLogFilter")]
+pub struct LogFilter {
+    pub default: LevelFilter,
+    /// Sorted from the most to the least specific target.
+    targets: Vec<(String, LevelFilter)>,
+}
+
+/// A deserialized [LogFilter] whose targets may be in any order.
+#[derive(Deserialize)]
+struct RawLogFilter {
+    default: LevelFilter,
+    #[serde(default)]
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl From<RawLogFilter> for LogFilter {
+    fn from(raw: RawLogFilter) -> Self {
+        raw.targets
+            .into_iter()
+            .fold(Self::new(raw.default), |filter, (target, level)| {
+                filter.with_target(target, level)
+            })
+    }
+}
+
+impl Default for LogFilter {
+    /// Logs everything except [Level::Trace].
+    fn default() -> Self {
+        Self::new(LevelFilter::Debug)
+    }
+}
+
+impl LogFilter {
+    pub fn new(default: LevelFilter) -> Self {
+        Self {
+            default,
+            targets: Vec::new(),
+        }
+    }
+
+    pub fn with_target(mut self, target: impl Into<String>, level: LevelFilter) -> Self {
+        self.set_target(target, level);
+        self
+    }
+
+    /// Sets the level of a target and everything below it.
+    pub fn set_target(&mut self, target: impl Into<String>, level: LevelFilter) {
+        let target = target.into();
+        match self.targets.iter_mut().find(|(t, _)| *t == target) {
+            Some((_, l)) => *l = level,
+            None => {
+                self.targets.push((target, level));
+                self.targets
+                    .sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+            }
+        }
+    }
+
+    /// Removes the level of a target, so it uses the level of its parent again.
+    pub fn remove_target(&mut self, target: &str) {
+        self.targets.retain(|(t, _)| t != target);
+    }
+
+    /// Returns the target levels, from the most to the least specific target.
+    pub fn targets(&self) -> impl Iterator<Item = (&str, LevelFilter)> {
+        self.targets.iter().map(|(t, l)| (t.as_str(), *l))
+    }
+
+    /// Returns the level filter that applies to the target.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(t, _)| {
+                target
+                    .strip_prefix(t.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    /// Returns `true` if a message of the given level and target is logged.
+    pub fn enabled(&self, level: Level, target: &str) -> bool {
+        self.level_for(target).allows(level)
+    }
+
+    /// Returns the most verbose level of the default and all targets.
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, l)| *l)
+            .fold(self.default, LevelFilter::min)
+    }
+}
+
+impl FromStr for LogFilter {
+    type Err = ParseLogFilterError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut filter = Self::default();
+
+        for directive in s.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => filter.set_target(target.trim(), level.parse()?),
+                None => filter.default = directive.parse()?,
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for LogFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.default)?;
+        for (target, level) in self.targets.iter().rev() {
+            write!(f, ",{target}={level}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A span the logged event happened in, with its fields.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogSpan {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<(String, serde_json::Value)>,
+}
+
+/// A message logged by a script.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogRecord {
+    pub level: Level,
+    /// Usually the module path of the code that logged the message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub message: String,
+    /// Structured fields, in the order they were given.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<(String, serde_json::Value)>,
+    /// The spans the message was logged in, starting with the outermost one.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<LogSpan>,
+}
+
+impl LogRecord {
+    pub fn new(level: Level, message: impl Into<String>) -> Self {
+        Self {
+            level,
+            target: None,
+            message: message.into(),
+            fields: Vec::new(),
+            spans: Vec::new(),
+        }
+    }
+
+    pub fn with_target(mut self, target: impl Into<String>) -> Self {
+        self.target = Some(target.into());
+        self
+    }
+
+    pub fn with_field(
+        mut self,
+        name: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.fields.push((name.into(), value.into()));
+        self
+    }
+}
+
+fn write_fields(f: &mut fmt::Formatter<'_>, fields: &[(String, serde_json::Value)]) -> fmt::Result {
+    for (i, (name, value)) in fields.iter().enumerate() {
+        if i > 0 {
+            f.write_str(" ")?;
+        }
+        match value {
+            serde_json::Value::String(value) => write!(f, "{name}={value:?}")?,
+            value => write!(f, "{name}={value}")?,
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for LogRecord {
+    /// Formats the record as a single line, e.g.
+    /// `my_script::doors: door{index=1}: opening speed=0.5`. The level is not included.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(target) = &self.target {
+            write!(f, "{target}: ")?;
+        }
+
+        for span in &self.spans {
+            f.write_str(&span.name)?;
+            if !span.fields.is_empty() {
+                f.write_str("{")?;
+                write_fields(f, &span.fields)?;
+                f.write_str("}")?;
+            }
+            f.write_str(": ")?;
+        }
+
+        f.write_str(&self.message)?;
+        if !self.fields.is_empty() {
+            if !self.message.is_empty() {
+                f.write_str(" ")?;
+            }
+            write_fields(f, &self.fields)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_targets() {
+        let filter: LogFilter = "info, my_script::doors=trace, my_script::doors::motor=off"
+            .parse()
+            .unwrap();
+
+        assert!(filter.enabled(Level::Info, "my_script"));
+        assert!(!filter.enabled(Level::Debug, "my_script"));
+        assert!(filter.enabled(Level::Trace, "my_script::doors"));
+        assert!(filter.enabled(Level::Trace, "my_script::doors::sensor"));
+        assert!(!filter.enabled(Level::Error, "my_script::doors::motor"));
+        assert!(!filter.enabled(Level::Trace, "my_script::doorsx"));
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+        assert_eq!(
+            filter.to_string(),
+            "info,my_script::doors=trace,my_script::doors::motor=off"
+        );
+
+        assert!(matches!(
+            "info,foo=loud".parse::<LogFilter>(),
+            Err(ParseLogFilterError::UnknownLevel(_))
+        ));
+    }
+
+    #[test]
+    fn test_filter_deserialize_sorts_targets() {
+        let filter: LogFilter = serde_json::from_str(
+            r#"{"default":"info","targets":[["my_script","off"],["my_script::doors","trace"]]}"#,
+        )
+        .unwrap();
+
+        assert!(filter.enabled(Level::Trace, "my_script::doors::motor"));
+        assert!(!filter.enabled(Level::Error, "my_script::ibis"));
+        assert_eq!(
+            filter.targets().map(|(t, _)| t).collect::<Vec<_>>(),
+            ["my_script::doors", "my_script"]
+        );
+    }
+
+    #[test]
+    fn test_record_display() {
+        let mut record = LogRecord::new(Level::Info, "opening")
+            .with_target("my_script::doors")
+            .with_field("speed", 0.5)
+            .with_field("reason", "button");
+        record.spans.push(LogSpan {
+            name: "door".to_string(),
+            fields: vec![("index".to_string(), 1.into())],
+        });
+
+        assert_eq!(
+            record.to_string(),
+            "my_script::doors: door{index=1}: opening speed=0.5 reason=\"button\""
+        );
+    }
+}
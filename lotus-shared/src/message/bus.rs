@@ -0,0 +1,176 @@
+//! Manage buses on the couplings of a vehicle.
+//!
+//! A bus is a named channel across a [Coupling]. Messages with a [bus](super::MessageMeta::bus)
+//! are only delivered across a coupling if the bus is open on both vehicles.
+//! When opening a bus, a vehicle can attach [BusMetadata], e.g. the protocol versions it supports.
+//! The engine negotiates the highest common protocol version and notifies both vehicles with a
+//! [BusProtocolNegotiated] message.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::Coupling;
+use crate::message_type;
+
+/// Describes a bus on a coupling.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusInfo {
+    /// The name of the bus.
+    pub name: String,
+    /// The coupling the bus belongs to.
+    pub coupling: Coupling,
+    /// `true` if this vehicle has opened the bus.
+    pub open: bool,
+    /// `true` if the coupled vehicle has opened the bus.
+    pub peer_open: bool,
+    /// The metadata this vehicle attached to the bus.
+    pub metadata: BusMetadata,
+    /// The metadata the coupled vehicle attached to the bus, if it has opened it.
+    pub peer_metadata: Option<BusMetadata>,
+    /// The negotiated protocol version, if both vehicles support a common one.
+    pub protocol: Option<u32>,
+}
+
+impl BusInfo {
+    /// Returns `true` if the bus is open on both vehicles, so messages are delivered across the coupling.
+    pub fn is_connected(&self) -> bool {
+        self.open && self.peer_open
+    }
+}
+
+/// Metadata a vehicle attaches to a bus when opening it.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BusMetadata {
+    /// The protocol versions this vehicle supports on the bus.
+    pub protocol_versions: Vec<u32>,
+    /// Additional properties, e.g. the vendor of the protocol.
+    pub properties: BTreeMap<String, String>,
+}
+
+impl BusMetadata {
+    /// Creates empty metadata.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the protocol versions this vehicle supports on the bus.
+    pub fn with_protocol_versions(mut self, versions: impl IntoIterator<Item = u32>) -> Self {
+        self.protocol_versions = versions.into_iter().collect();
+        self
+    }
+
+    /// Adds a property.
+    pub fn with_property(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.properties.insert(key.into(), value.into());
+        self
+    }
+
+    /// Returns the highest protocol version supported by both sides.
+    pub fn negotiate(&self, peer: &BusMetadata) -> Option<u32> {
+        self.protocol_versions
+            .iter()
+            .filter(|version| peer.protocol_versions.contains(version))
+            .max()
+            .copied()
+    }
+}
+
+#[cfg(feature = "ffi")]
+impl Coupling {
+    /// Opens the given bus and attaches the given metadata to it.
+    pub fn open_bus_with(&self, bus: &str, metadata: &BusMetadata) {
+        let bus = lotus_script_sys::FfiObject::new(&bus);
+        let metadata = lotus_script_sys::FfiObject::new(metadata);
+        unsafe {
+            lotus_script_sys::vehicle::open_bus_with_metadata(
+                *self as u32,
+                bus.packed(),
+                metadata.packed(),
+            )
+        };
+    }
+
+    /// Returns all buses on this coupling that are open on either vehicle.
+    pub fn buses(&self) -> Vec<BusInfo> {
+        let buses = unsafe { lotus_script_sys::vehicle::buses(*self as u32) };
+        lotus_script_sys::FfiObject::from_packed(buses).deserialize()
+    }
+
+    /// Returns the given bus, if it is open on either vehicle.
+    pub fn bus(&self, bus: &str) -> Option<BusInfo> {
+        self.buses().into_iter().find(|b| b.name == bus)
+    }
+
+    /// Returns the negotiated protocol version of the given bus.
+    pub fn bus_protocol(&self, bus: &str) -> Option<u32> {
+        self.bus(bus).and_then(|b| b.protocol)
+    }
+}
+
+/// Sent when a bus on a coupling was opened by this or the coupled vehicle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusOpened {
+    pub coupling: Coupling,
+    pub bus: String,
+    /// `true` if the coupled vehicle opened the bus.
+    pub by_peer: bool,
+}
+
+message_type!(BusOpened, "builtin", "bus_opened");
+
+/// Sent when a bus on a coupling was closed by this or the coupled vehicle.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusClosed {
+    pub coupling: Coupling,
+    pub bus: String,
+    /// `true` if the coupled vehicle closed the bus.
+    pub by_peer: bool,
+}
+
+message_type!(BusClosed, "builtin", "bus_closed");
+
+/// Sent when the protocol of a bus was negotiated, after both vehicles opened it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BusProtocolNegotiated {
+    pub coupling: Coupling,
+    pub bus: String,
+    /// The highest protocol version supported by both vehicles, `None` if there is none.
+    pub protocol: Option<u32>,
+    /// The metadata the coupled vehicle attached to the bus.
+    pub peer_metadata: BusMetadata,
+}
+
+message_type!(BusProtocolNegotiated, "builtin", "bus_protocol_negotiated");
+
+/// Sent when a vehicle was coupled to the given coupling.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerCoupled {
+    pub coupling: Coupling,
+}
+
+message_type!(PeerCoupled, "builtin", "peer_coupled");
+
+/// Sent when the vehicle at the given coupling was uncoupled.
+/// All buses on the coupling are closed by the peer at this point.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerUncoupled {
+    pub coupling: Coupling,
+}
+
+message_type!(PeerUncoupled, "builtin", "peer_uncoupled");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() {
+        let local = BusMetadata::new().with_protocol_versions([1, 2, 3]);
+        let peer = BusMetadata::new().with_protocol_versions([2, 3, 4]);
+        assert_eq!(local.negotiate(&peer), Some(3));
+
+        let legacy = BusMetadata::new().with_protocol_versions([1]);
+        assert_eq!(peer.negotiate(&legacy), None);
+    }
+}
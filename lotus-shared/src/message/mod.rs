@@ -0,0 +1,811 @@
+//! Handle messages between scripts or from the engine.
+//! See [Message] and [MessageType] for more information.
+use std::borrow::Cow;
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::content::ContentId;
+
+pub mod bus;
+pub mod registry;
+pub mod schema;
+pub mod trace;
+
+/// Represents a message that can be sent between scripts or from the engine.
+///
+/// # Example
+/// ```no_run
+/// # use serde::{Deserialize, Serialize};
+/// # use lotus_shared::message::{Message, MessageType};
+/// # use lotus_shared::message_type;
+///
+/// // Define a message type, has to implement Serialize and Deserialize
+/// #[derive(Serialize, Deserialize)]
+/// struct TestMessage {
+///     value: i32,
+/// }
+///
+/// // Register the message type
+/// message_type!(TestMessage, "test", "message");
+/// ```
+///
+/// Registered message types are exported by the script together with their schema,
+/// see [registry] for more information.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Message {
+    meta: MessageMeta,
+    #[cfg_attr(feature = "engine", serde(default))]
+    source: MessageSource,
+    value: MessagePayload,
+}
+
+/// The payload of a [Message].
+///
+/// Messages created with [Message::new] carry their value as msgpack bytes, which are only
+/// decoded when the receiver asks for a concrete type. Messages created by the engine may still
+/// carry a JSON-shaped value, which is decoded in place.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MessagePayload {
+    /// The value as a JSON value.
+    Json(serde_json::Value),
+    /// The value encoded as msgpack.
+    Binary(#[serde(with = "serde_bytes")] Vec<u8>),
+}
+
+impl MessagePayload {
+    /// Encodes the given value as a binary payload.
+    pub fn encode<T: Serialize>(value: &T) -> Result<Self, SerializationError> {
+        rmp_serde::to_vec_named(value)
+            .map(Self::Binary)
+            .map_err(|e| SerializationError(e.to_string()))
+    }
+
+    /// Decodes the payload as the given type.
+    pub fn decode<T: DeserializeOwned>(&self) -> Result<T, SerializationError> {
+        match self {
+            Self::Json(value) => {
+                T::deserialize(value).map_err(|e| SerializationError(e.to_string()))
+            }
+            Self::Binary(bytes) => {
+                rmp_serde::from_slice(bytes).map_err(|e| SerializationError(e.to_string()))
+            }
+        }
+    }
+
+    /// Returns the payload as a JSON value, decoding it if necessary.
+    pub fn to_json(&self) -> Result<serde_json::Value, SerializationError> {
+        match self {
+            Self::Json(value) => Ok(value.clone()),
+            Self::Binary(_) => self.decode(),
+        }
+    }
+}
+
+/// Represents the metadata for a message type.
+///
+/// The combination of `namespace` and `identifier` should be globally unique for each message type.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct MessageMeta {
+    /// The namespace of the message type.
+    pub namespace: Cow<'static, str>,
+    /// The identifier of the message type.
+    pub identifier: Cow<'static, str>,
+    /// The bus the message should be sent on.
+    pub bus: Option<Cow<'static, str>>,
+}
+
+impl MessageMeta {
+    /// Creates a new message meta.
+    pub const fn new(
+        namespace: &'static str,
+        identifier: &'static str,
+        bus: Option<&'static str>,
+    ) -> Self {
+        Self {
+            namespace: Cow::Borrowed(namespace),
+            identifier: Cow::Borrowed(identifier),
+            bus: match bus {
+                Some(bus) => Some(Cow::Borrowed(bus)),
+                None => None,
+            },
+        }
+    }
+}
+
+/// Represents a message type that can be sent between scripts or from the engine.
+/// The [MessageType::MESSAGE_META] constant should return a globally unique message meta for the message type.
+pub trait MessageType: Serialize + DeserializeOwned {
+    /// The metadata for the message type.
+    const MESSAGE_META: MessageMeta;
+}
+
+/// Represents the source of a message.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MessageSource {
+    /// If the message is coming from another vehicle across couplings, this will be Some.
+    pub coupling: Option<Coupling>,
+    /// If the message is coming from a module, these will be Some.
+    pub module_slot_index: Option<u16>,
+    pub module_slot_cockpit_index: Option<u8>,
+    /// The kind of the sender.
+    #[serde(default)]
+    pub sender: MessageSender,
+    /// The content id of the sending script, if the message was sent by a script.
+    #[serde(default)]
+    pub script: Option<ContentId>,
+    /// The number of couplings the message has crossed. This is larger than `1` for
+    /// [MessageTarget::AcrossCoupling] messages with `cascade: true`.
+    #[serde(default)]
+    pub hops: u8,
+}
+
+/// The kind of sender of a message.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum MessageSender {
+    /// The sender is not known, e.g. because the engine does not report it.
+    #[default]
+    Unknown,
+    /// The message was sent by the engine itself.
+    Engine,
+    /// The message was sent by the main script of a vehicle.
+    Vehicle,
+    /// The message was sent by a child script, e.g. a module.
+    Child,
+}
+
+impl MessageSource {
+    /// Returns `true` if the message is coming from the vehicle in front.
+    pub fn is_front(&self) -> bool {
+        matches!(self.coupling, Some(Coupling::Front))
+    }
+
+    /// Returns `true` if the message is coming from the vehicle in rear.
+    pub fn is_rear(&self) -> bool {
+        matches!(self.coupling, Some(Coupling::Rear))
+    }
+
+    /// Returns `true` if the message was sent by the engine.
+    pub fn is_engine(&self) -> bool {
+        self.sender == MessageSender::Engine
+    }
+
+    /// Returns the number of couplings the message has crossed.
+    /// Returns `0` if the message is coming from the same vehicle.
+    pub fn coupling_hops(&self) -> u8 {
+        match self.coupling {
+            Some(_) => self.hops.max(1),
+            None => 0,
+        }
+    }
+
+    /// Returns the offset of the sending vehicle relative to this vehicle.
+    /// Negative values are vehicles in front, positive values are vehicles in rear
+    /// and `0` is this vehicle.
+    pub fn vehicle_offset(&self) -> i32 {
+        match self.coupling {
+            Some(Coupling::Front) => -(self.coupling_hops() as i32),
+            Some(Coupling::Rear) => self.coupling_hops() as i32,
+            None => 0,
+        }
+    }
+
+    /// Returns `true` if the message was sent from the vehicle with the given offset.
+    /// See [MessageSource::vehicle_offset].
+    pub fn from_vehicle_offset(&self, offset: i32) -> bool {
+        self.vehicle_offset() == offset
+    }
+
+    /// Returns `true` if the message was sent by the main script of this vehicle.
+    pub fn from_own_vehicle(&self) -> bool {
+        self.coupling.is_none() && self.sender == MessageSender::Vehicle
+    }
+
+    /// Returns `true` if the message was sent by the child script in the given module slot of this vehicle.
+    pub fn from_child(&self, module_slot_index: u16) -> bool {
+        self.coupling.is_none()
+            && self.sender != MessageSender::Engine
+            && self.module_slot_index == Some(module_slot_index)
+    }
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! message_type {
+    ($type:ty, $namespace:expr, $identifier:expr, $bus:expr) => {
+        impl $crate::message::MessageType for $type {
+            const MESSAGE_META: $crate::message::MessageMeta =
+                $crate::message::MessageMeta::new($namespace, $identifier, Some($bus));
+        }
+
+        $crate::__register_message_type!($type);
+    };
+    ($type:ty, $namespace:expr, $identifier:expr) => {
+        impl $crate::message::MessageType for $type {
+            const MESSAGE_META: $crate::message::MessageMeta =
+                $crate::message::MessageMeta::new($namespace, $identifier, None);
+        }
+
+        $crate::__register_message_type!($type);
+    };
+}
+
+#[doc(inline)]
+pub use message_type;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MessageValueError {
+    #[error("invalid message type")]
+    InvalidType,
+    #[error("{0}")]
+    Serialization(SerializationError),
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("serialization error: {0}")]
+pub struct SerializationError(String);
+
+#[derive(Debug, thiserror::Error)]
+pub enum MessageHandleError {
+    #[error("{0}")]
+    Serialization(SerializationError),
+    #[error("handler error: {0}")]
+    Handler(Box<dyn std::error::Error>),
+}
+
+impl Message {
+    /// Creates a new message with the given value.
+    pub fn new<T: MessageType>(value: &T) -> Self {
+        Self {
+            meta: T::MESSAGE_META.clone(),
+            source: MessageSource::default(),
+            value: MessagePayload::encode(value).unwrap(),
+        }
+    }
+
+    /// Creates a new message with a JSON-shaped value, as used by older engine messages.
+    pub fn from_json(meta: MessageMeta, value: serde_json::Value) -> Self {
+        Self {
+            meta,
+            source: MessageSource::default(),
+            value: MessagePayload::Json(value),
+        }
+    }
+
+    /// Returns the message type metadata.
+    pub fn meta(&self) -> &MessageMeta {
+        &self.meta
+    }
+
+    /// Returns the source of the message.
+    pub fn source(&self) -> &MessageSource {
+        &self.source
+    }
+
+    /// Returns `true` if the message was sent by the child script in the given module slot of this vehicle.
+    /// See [MessageSource::from_child].
+    pub fn from_child(&self, module_slot_index: u16) -> bool {
+        self.source.from_child(module_slot_index)
+    }
+
+    /// Returns `true` if the message was sent from the vehicle with the given offset.
+    /// See [MessageSource::vehicle_offset].
+    pub fn from_vehicle_offset(&self, offset: i32) -> bool {
+        self.source.from_vehicle_offset(offset)
+    }
+
+    /// Returns the raw payload of the message.
+    pub fn payload(&self) -> &MessagePayload {
+        &self.value
+    }
+
+    /// Returns the message value as the given type. Returns a [MessageValueError] if the message has a different type.
+    pub fn value<T: MessageType>(&self) -> Result<T, MessageValueError> {
+        if self.meta != T::MESSAGE_META {
+            return Err(MessageValueError::InvalidType);
+        }
+
+        self.value
+            .decode()
+            .map_err(MessageValueError::Serialization)
+    }
+
+    /// Returns `true` if the message has the given type.
+    pub fn has_type<T: MessageType>(&self) -> bool {
+        self.meta == T::MESSAGE_META
+    }
+
+    /// Handle the message with the given handler function.
+    /// Returns `Ok(true)` if the message was handled, `Ok(false)` if the message has a different type,
+    /// or `Err` if the message could not be deserialized or the handler function returned an error.
+    /// The handler function should return `Ok(())` if the message was handled successfully.
+    pub fn handle<T: MessageType>(
+        &self,
+        f: impl FnOnce(T) -> Result<(), Box<dyn std::error::Error>>,
+    ) -> Result<bool, MessageHandleError> {
+        match self.value::<T>() {
+            Ok(v) => f(v).map_err(MessageHandleError::Handler).map(|_| true),
+            Err(MessageValueError::InvalidType) => Ok(false),
+            Err(MessageValueError::Serialization(e)) => Err(MessageHandleError::Serialization(e)),
+        }
+    }
+
+    #[cfg(feature = "engine")]
+    pub fn with_source(&self, source: MessageSource) -> Self {
+        Self {
+            meta: self.meta.clone(),
+            source,
+            value: self.value.clone(),
+        }
+    }
+}
+
+pub trait IntoMessageTargets {
+    fn into_message_targets(self) -> impl IntoIterator<Item = MessageTarget>;
+}
+
+impl IntoMessageTargets for MessageTarget {
+    fn into_message_targets(self) -> impl IntoIterator<Item = MessageTarget> {
+        [self]
+    }
+}
+
+impl<T> IntoMessageTargets for T
+where
+    T: IntoIterator<Item = MessageTarget>,
+{
+    fn into_message_targets(self) -> impl IntoIterator<Item = MessageTarget> {
+        self
+    }
+}
+
+/// Where and when [send_message] delivers a message: targets, or targets with a schedule, see
+/// [MessageSchedule::to].
+#[cfg(feature = "ffi")]
+pub trait MessageDelivery {
+    /// What [send_message] returns for this delivery.
+    type Output;
+
+    #[doc(hidden)]
+    fn deliver(self, message: &Message) -> Self::Output;
+}
+
+#[cfg(feature = "ffi")]
+impl<T: IntoMessageTargets> MessageDelivery for T {
+    type Output = ();
+
+    fn deliver(self, message: &Message) {
+        let this = lotus_script_sys::FfiObject::new(message);
+        let targets = self.into_message_targets().into_iter().collect::<Vec<_>>();
+        trace::record_sent(message, &targets, None);
+        let targets = lotus_script_sys::FfiObject::new(&targets);
+
+        unsafe { lotus_script_sys::messages::send(targets.packed(), this.packed()) }
+    }
+}
+
+#[cfg(feature = "ffi")]
+impl<T: IntoMessageTargets> MessageDelivery for Scheduled<T> {
+    type Output = Result<ScheduledMessage, ScheduleError>;
+
+    fn deliver(self, message: &Message) -> Self::Output {
+        self.schedule.validate()?;
+
+        let this = lotus_script_sys::FfiObject::new(message);
+        let targets = self
+            .targets
+            .into_message_targets()
+            .into_iter()
+            .collect::<Vec<_>>();
+        trace::record_sent(message, &targets, Some(self.schedule));
+        let targets = lotus_script_sys::FfiObject::new(&targets);
+        let schedule = lotus_script_sys::FfiObject::new(&self.schedule);
+
+        Ok(ScheduledMessage(unsafe {
+            lotus_script_sys::messages::send_scheduled(
+                targets.packed(),
+                this.packed(),
+                schedule.packed(),
+            )
+        }))
+    }
+}
+
+/// Sends the message to the given targets.
+///
+/// Messages are delivered right away, unless the targets are given a [MessageSchedule] with
+/// [MessageSchedule::to]. Scheduled deliveries are executed by the engine in simulation time, so
+/// they respect pause and the time speed multiplier, and return a handle to cancel them.
+///
+/// # Example
+/// ```no_run
+/// # use lotus_shared::message::{Coupling, Message, MessageSchedule, MessageTarget, send_message};
+/// # use serde::{Deserialize, Serialize};
+/// # use lotus_shared::message_type;
+/// # #[derive(Serialize, Deserialize)]
+/// # struct TestMessage { value: i32 };
+/// # message_type!(TestMessage, "test", "message");
+/// # #[derive(Serialize, Deserialize)]
+/// # struct Heartbeat;
+/// # message_type!(Heartbeat, "test", "heartbeat");
+/// // Send a message with only a single target
+/// send_message(&TestMessage { value: 42 }, MessageTarget::Myself);
+/// // Send a message to multiple targets
+/// send_message(&TestMessage { value: 42 }, [MessageTarget::Myself, MessageTarget::ChildByIndex(0)]);
+///
+/// // Send a heartbeat to the vehicle in rear every 500 ms
+/// let rear = MessageTarget::AcrossCoupling {
+///     coupling: Coupling::Rear,
+///     cascade: false,
+/// };
+/// let heartbeat = send_message(&Heartbeat, MessageSchedule::every(0.5).to(rear)).unwrap();
+///
+/// // Stop sending it later on
+/// heartbeat.cancel();
+/// ```
+#[cfg(feature = "ffi")]
+pub fn send_message<T: MessageType, D: MessageDelivery>(message: &T, delivery: D) -> D::Output {
+    delivery.deliver(&Message::new(message))
+}
+
+/// Describes when a scheduled message is delivered. See [send_message].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MessageSchedule {
+    /// The delay in seconds of simulation time until the message is delivered for the first time.
+    pub delay: f32,
+    /// If set, the message is delivered again every `interval` seconds of simulation time until it is cancelled.
+    pub interval: Option<f32>,
+}
+
+/// An error returned by [MessageSchedule::validate].
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum ScheduleError {
+    #[error("delay must be finite and not negative, got {0}")]
+    InvalidDelay(f32),
+    #[error("interval must be finite and positive, got {0}")]
+    InvalidInterval(f32),
+}
+
+impl MessageSchedule {
+    /// Deliver the message once after the given number of seconds.
+    pub fn after(seconds: f32) -> Self {
+        Self {
+            delay: seconds,
+            interval: None,
+        }
+    }
+
+    /// Deliver the message every given number of seconds, starting after the first interval.
+    pub fn every(seconds: f32) -> Self {
+        Self::after(seconds).repeat_every(seconds)
+    }
+
+    /// Repeat the delivery every given number of seconds after the first delivery.
+    pub fn repeat_every(mut self, seconds: f32) -> Self {
+        self.interval = Some(seconds);
+        self
+    }
+
+    /// Returns `true` if the message is delivered more than once.
+    pub fn is_periodic(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// Checks that the delay is not negative and the interval is positive, and neither is NaN
+    /// or infinite. [send_message] doesn't send messages with an invalid schedule.
+    pub fn validate(&self) -> Result<(), ScheduleError> {
+        if !(self.delay.is_finite() && self.delay >= 0.0) {
+            return Err(ScheduleError::InvalidDelay(self.delay));
+        }
+
+        match self.interval {
+            Some(interval) if !(interval.is_finite() && interval > 0.0) => {
+                Err(ScheduleError::InvalidInterval(interval))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Delivers a message to the given targets with this schedule, see [send_message].
+    pub fn to<T: IntoMessageTargets>(self, targets: T) -> Scheduled<T> {
+        Scheduled {
+            targets,
+            schedule: self,
+        }
+    }
+}
+
+/// Targets with a [MessageSchedule], created with [MessageSchedule::to].
+#[derive(Debug, Clone, Copy)]
+pub struct Scheduled<T> {
+    /// The targets the message is delivered to, like the targets of an unscheduled message.
+    pub targets: T,
+    /// When the message is delivered. It is validated when sending the message.
+    pub schedule: MessageSchedule,
+}
+
+/// A handle to a message sent with a [MessageSchedule], see [send_message].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScheduledMessage(u64);
+
+impl ScheduledMessage {
+    #[cfg(feature = "engine")]
+    /// Create a new scheduled message handle.
+    pub fn new(id: u64) -> Self {
+        Self(id)
+    }
+
+    /// Returns the ID of the scheduled message.
+    pub fn id(&self) -> u64 {
+        self.0
+    }
+
+    #[cfg(feature = "ffi")]
+    /// Cancels all further deliveries of the message.
+    /// Returns `true` if the message was still pending.
+    pub fn cancel(self) -> bool {
+        unsafe { lotus_script_sys::messages::cancel_scheduled(self.0) == 1 }
+    }
+
+    #[cfg(feature = "ffi")]
+    /// Returns `true` if the message has further deliveries pending.
+    pub fn is_pending(&self) -> bool {
+        unsafe { lotus_script_sys::messages::is_scheduled(self.0) == 1 }
+    }
+}
+
+/// Represents a message target.
+#[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+pub enum MessageTarget {
+    /// The script itself.
+    Myself,
+    /// The child script at the given index.
+    ChildByIndex(usize),
+    /// To all modules in the cockpit with the given index.
+    Cockpit(u8),
+    /// Broadcast to scripts based on the specified scope.
+    Broadcast {
+        /// Whether to include coupled vehicles.
+        across_couplings: bool,
+        /// Whether to include the sending script.
+        include_self: bool,
+    },
+    /// Send to a specific coupling.
+    AcrossCoupling {
+        /// The coupling to send to.
+        coupling: Coupling,
+        /// Whether to cascade the message to the next coupling.
+        cascade: bool,
+    },
+    /// The parent script.
+    Parent,
+}
+
+impl MessageTarget {
+    /// Helper to create a broadcast target that excludes self
+    pub fn broadcast_except_self(across_couplings: bool) -> Self {
+        Self::Broadcast {
+            across_couplings,
+            include_self: false,
+        }
+    }
+
+    pub fn broadcast_all() -> Self {
+        Self::Broadcast {
+            across_couplings: true,
+            include_self: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Coupling {
+    /// The coupling to the front vehicle.
+    Front,
+    /// The coupling to the rear vehicle.
+    Rear,
+}
+
+impl Coupling {
+    #[cfg(feature = "ffi")]
+    /// Opens the given bus. Use [Coupling::open_bus_with] to attach metadata to the bus.
+    pub fn open_bus(&self, bus: &str) {
+        let bus = lotus_script_sys::FfiObject::new(&bus);
+        unsafe { lotus_script_sys::vehicle::open_bus(*self as u32, bus.packed()) };
+    }
+
+    #[cfg(feature = "ffi")]
+    /// Closes the given bus.
+    pub fn close_bus(&self, bus: &str) {
+        let bus = lotus_script_sys::FfiObject::new(&bus);
+        unsafe { lotus_script_sys::vehicle::close_bus(*self as u32, bus.packed()) };
+    }
+
+    #[cfg(feature = "ffi")]
+    /// Returns `true` if the given bus is open.
+    pub fn is_open(&self, bus: &str) -> bool {
+        let bus = lotus_script_sys::FfiObject::new(&bus);
+        unsafe { lotus_script_sys::vehicle::is_bus_open(*self as u32, bus.packed()) == 1 }
+    }
+
+    #[cfg(feature = "ffi")]
+    pub fn is_coupled(&self) -> bool {
+        unsafe { lotus_script_sys::vehicle::is_coupled(*self as u32) == 1 }
+    }
+}
+
+impl From<u32> for Coupling {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Front,
+            1 => Self::Rear,
+            _ => panic!("invalid coupling value: {}", value),
+        }
+    }
+}
+
+impl From<usize> for Coupling {
+    fn from(value: usize) -> Self {
+        match value {
+            0 => Self::Front,
+            1 => Self::Rear,
+            _ => panic!("invalid coupling value: {}", value),
+        }
+    }
+}
+
+impl From<Coupling> for usize {
+    fn from(value: Coupling) -> Self {
+        match value {
+            Coupling::Front => 0,
+            Coupling::Rear => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    struct TestMessage {
+        value: i32,
+    }
+
+    message_type!(TestMessage, "test", "message", "ibis");
+
+    #[test]
+    fn test_message() {
+        let message = Message::new(&TestMessage { value: 42 });
+        assert_eq!(message.meta(), &TestMessage::MESSAGE_META);
+
+        let value = message.value::<TestMessage>().unwrap();
+
+        assert_eq!(value, TestMessage { value: 42 });
+
+        message
+            .handle::<TestMessage>(|m| {
+                assert_eq!(m.value, 42);
+                Ok(())
+            })
+            .expect("message handle failed");
+    }
+
+    #[test]
+    fn test_message_source_filters() {
+        let child = MessageSource {
+            module_slot_index: Some(2),
+            sender: MessageSender::Child,
+            ..Default::default()
+        };
+        assert!(child.from_child(2));
+        assert!(!child.from_child(1));
+        assert!(child.from_vehicle_offset(0));
+
+        let cascaded = MessageSource {
+            coupling: Some(Coupling::Front),
+            sender: MessageSender::Vehicle,
+            hops: 2,
+            ..Default::default()
+        };
+        assert_eq!(cascaded.vehicle_offset(), -2);
+        assert!(!cascaded.from_own_vehicle());
+
+        // Engines that don't report hops yet still count a single coupling.
+        let legacy = MessageSource {
+            coupling: Some(Coupling::Rear),
+            ..Default::default()
+        };
+        assert!(legacy.from_vehicle_offset(1));
+    }
+
+    #[test]
+    fn test_schedule_validate() {
+        assert_eq!(MessageSchedule::after(0.0).validate(), Ok(()));
+        assert_eq!(MessageSchedule::every(0.5).validate(), Ok(()));
+        assert!(!MessageSchedule::after(3.0).is_periodic());
+
+        assert_eq!(
+            MessageSchedule::after(-1.0).validate(),
+            Err(ScheduleError::InvalidDelay(-1.0))
+        );
+        assert!(matches!(
+            MessageSchedule::after(f32::NAN).validate(),
+            Err(ScheduleError::InvalidDelay(_))
+        ));
+        assert_eq!(
+            MessageSchedule::after(1.0).repeat_every(0.0).validate(),
+            Err(ScheduleError::InvalidInterval(0.0))
+        );
+        assert!(matches!(
+            MessageSchedule::every(f32::NAN).validate(),
+            Err(ScheduleError::InvalidDelay(_))
+        ));
+        assert!(matches!(
+            MessageSchedule {
+                delay: 1.0,
+                interval: Some(f32::INFINITY),
+            }
+            .validate(),
+            Err(ScheduleError::InvalidInterval(_))
+        ));
+    }
+
+    #[test]
+    fn test_schedule_to() {
+        let scheduled = MessageSchedule::every(0.5).to(MessageTarget::Myself);
+        assert_eq!(scheduled.schedule.interval, Some(0.5));
+
+        let json = serde_json::to_string(&scheduled.schedule).unwrap();
+        let schedule: MessageSchedule = serde_json::from_str(&json).unwrap();
+        assert_eq!(schedule, scheduled.schedule);
+    }
+
+    #[test]
+    fn test_message_binary_roundtrip() {
+        let message = Message::new(&TestMessage { value: 42 });
+        assert!(matches!(message.payload(), MessagePayload::Binary(_)));
+
+        let bytes = rmp_serde::to_vec_named(&message).unwrap();
+        let message: Message = rmp_serde::from_slice(&bytes).unwrap();
+
+        assert!(matches!(message.payload(), MessagePayload::Binary(_)));
+        assert_eq!(
+            message.value::<TestMessage>().unwrap(),
+            TestMessage { value: 42 }
+        );
+    }
+
+    #[test]
+    fn test_message_json_compat() {
+        #[derive(Serialize)]
+        struct EngineMessage {
+            meta: MessageMeta,
+            source: MessageSource,
+            value: serde_json::Value,
+        }
+
+        let bytes = rmp_serde::to_vec_named(&EngineMessage {
+            meta: TestMessage::MESSAGE_META,
+            source: MessageSource::default(),
+            value: serde_json::json!({ "value": 42 }),
+        })
+        .unwrap();
+        let message: Message = rmp_serde::from_slice(&bytes).unwrap();
+
+        assert!(matches!(message.payload(), MessagePayload::Json(_)));
+        assert_eq!(
+            message.value::<TestMessage>().unwrap(),
+            TestMessage { value: 42 }
+        );
+        assert_eq!(
+            message.payload().to_json().unwrap(),
+            serde_json::json!({ "value": 42 })
+        );
+    }
+}
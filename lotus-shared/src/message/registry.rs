@@ -0,0 +1,293 @@
+//! A registry of the message types a script understands.
+//!
+//! Every type registered with [message_type!](crate::message_type) is collected automatically
+//! and can be listed with [registered]. Scripts export the list, so the engine and tooling can
+//! list the messages a script understands and validate messages flowing between scripts of
+//! different authors. See [MessageRegistry].
+
+use std::borrow::Cow;
+
+use serde::{Deserialize, Serialize};
+
+use super::{schema, Message, MessageMeta, MessageType, SerializationError};
+
+/// Describes a registered message type.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageTypeInfo {
+    /// The metadata of the message type.
+    pub meta: MessageMeta,
+    /// The Rust type name of the message type. Only meant for display purposes.
+    pub type_name: Cow<'static, str>,
+    /// The JSON schema of the message value.
+    pub schema: serde_json::Value,
+}
+
+impl MessageTypeInfo {
+    /// Creates the info for the given message type.
+    pub fn of<T: MessageType>() -> Self {
+        Self {
+            meta: T::MESSAGE_META,
+            type_name: Cow::Borrowed(std::any::type_name::<T>()),
+            schema: schema::schema_for::<T>(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MessageValidationError {
+    #[error("unknown message type {namespace}:{identifier}")]
+    UnknownType {
+        namespace: Cow<'static, str>,
+        identifier: Cow<'static, str>,
+    },
+    #[error("{0}")]
+    Serialization(SerializationError),
+    #[error("invalid message value: {0}")]
+    Schema(schema::SchemaError),
+}
+
+/// A collection of message types.
+///
+/// # Example
+/// ```
+/// # use serde::{Deserialize, Serialize};
+/// # use lotus_shared::message::{Message, registry::MessageRegistry};
+/// # use lotus_shared::message_type;
+/// #[derive(Serialize, Deserialize)]
+/// struct DoorsClosed {
+///     side: u8,
+/// }
+///
+/// message_type!(DoorsClosed, "example", "doors_closed");
+///
+/// let mut registry = MessageRegistry::new();
+/// registry.register::<DoorsClosed>();
+///
+/// let message = Message::new(&DoorsClosed { side: 1 });
+/// assert!(registry.validate(&message).is_ok());
+/// ```
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MessageRegistry {
+    types: Vec<MessageTypeInfo>,
+}
+
+impl MessageRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the given message type.
+    pub fn register<T: MessageType>(&mut self) {
+        self.insert(MessageTypeInfo::of::<T>());
+    }
+
+    /// Inserts the given message type info, replacing a previous entry with the same namespace,
+    /// identifier and bus.
+    pub fn insert(&mut self, info: MessageTypeInfo) {
+        match self
+            .types
+            .iter_mut()
+            .find(|t| is_same_type(&t.meta, &info.meta))
+        {
+            Some(existing) => *existing = info,
+            None => self.types.push(info),
+        }
+    }
+
+    /// Returns the info for the message type with the given namespace and identifier.
+    pub fn get(&self, namespace: &str, identifier: &str) -> Option<&MessageTypeInfo> {
+        self.types
+            .iter()
+            .find(|t| t.meta.namespace == namespace && t.meta.identifier == identifier)
+    }
+
+    /// Returns the info for the message type with the given metadata. Falls back to a type with
+    /// the same namespace and identifier on another bus.
+    pub fn find(&self, meta: &MessageMeta) -> Option<&MessageTypeInfo> {
+        self.types
+            .iter()
+            .find(|t| is_same_type(&t.meta, meta))
+            .or_else(|| self.get(&meta.namespace, &meta.identifier))
+    }
+
+    /// Returns an iterator over all registered message types.
+    pub fn iter(&self) -> impl Iterator<Item = &MessageTypeInfo> {
+        self.types.iter()
+    }
+
+    /// Returns the number of registered message types.
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// Returns `true` if no message types are registered.
+    pub fn is_empty(&self) -> bool {
+        self.types.is_empty()
+    }
+
+    /// Checks that the message has a registered type and its value matches the schema of that type.
+    pub fn validate(&self, message: &Message) -> Result<(), MessageValidationError> {
+        let meta = message.meta();
+        let info = self
+            .find(meta)
+            .ok_or_else(|| MessageValidationError::UnknownType {
+                namespace: meta.namespace.clone(),
+                identifier: meta.identifier.clone(),
+            })?;
+
+        let value = message
+            .payload()
+            .to_json()
+            .map_err(MessageValidationError::Serialization)?;
+
+        schema::validate(&info.schema, &value).map_err(MessageValidationError::Schema)
+    }
+}
+
+impl<'a> IntoIterator for &'a MessageRegistry {
+    type Item = &'a MessageTypeInfo;
+    type IntoIter = std::slice::Iter<'a, MessageTypeInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.types.iter()
+    }
+}
+
+impl FromIterator<MessageTypeInfo> for MessageRegistry {
+    fn from_iter<I: IntoIterator<Item = MessageTypeInfo>>(iter: I) -> Self {
+        let mut registry = Self::new();
+        for info in iter {
+            registry.insert(info);
+        }
+        registry
+    }
+}
+
+fn is_same_type(a: &MessageMeta, b: &MessageMeta) -> bool {
+    a.namespace == b.namespace && a.identifier == b.identifier && a.bus == b.bus
+}
+
+#[doc(hidden)]
+pub use inventory;
+
+/// A message type collected by [message_type!](crate::message_type).
+#[doc(hidden)]
+pub struct RegisteredType {
+    info: fn() -> MessageTypeInfo,
+}
+
+impl RegisteredType {
+    pub const fn new<T: MessageType>() -> Self {
+        Self {
+            info: MessageTypeInfo::of::<T>,
+        }
+    }
+}
+
+inventory::collect!(RegisteredType);
+
+/// Returns all message types registered with [message_type!](crate::message_type) in this
+/// script or engine, including the built-in ones.
+pub fn registered() -> MessageRegistry {
+    run_constructors();
+    inventory::iter::<RegisteredType>
+        .into_iter()
+        .map(|registered| (registered.info)())
+        .collect()
+}
+
+/// Registers the collected message types on WebAssembly, where the static constructors only run
+/// when `__wasm_call_ctors` is called. This happens once, in the `init` export of
+/// `script!` or in the first call of [registered], whatever comes first. Does nothing on
+/// other targets.
+#[doc(hidden)]
+pub fn run_constructors() {
+    #[cfg(target_family = "wasm")]
+    {
+        extern "C" {
+            fn __wasm_call_ctors();
+        }
+
+        static CONSTRUCTORS: std::sync::Once = std::sync::Once::new();
+        CONSTRUCTORS.call_once(|| unsafe { __wasm_call_ctors() });
+    }
+}
+
+/// The name of the function a script exports to return the [MessageRegistry] of its
+/// [registered] message types. It can be called right after instantiating the script, before
+/// `init`.
+pub const EXPORT_NAME: &str = "message_types";
+
+#[doc(hidden)]
+#[cfg(feature = "ffi")]
+#[export_name = "message_types"]
+pub extern "C" fn export_message_types() -> u64 {
+    lotus_script_sys::FfiObject::new(&registered()).packed_forget()
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __register_message_type {
+    ($type:ty) => {
+        $crate::message::registry::inventory::submit! {
+            $crate::message::registry::RegisteredType::new::<$type>()
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::message_type;
+
+    const NAMESPACE: &str = "registry_test";
+
+    #[derive(Serialize, Deserialize)]
+    struct Announcement {
+        text: String,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct IbisAnnouncement {
+        text: String,
+    }
+
+    message_type!(Announcement, NAMESPACE, "announcement");
+    message_type!(IbisAnnouncement, NAMESPACE, "announcement", "ibis");
+
+    #[test]
+    fn test_registered_automatically() {
+        let registry = registered();
+
+        let types = registry
+            .iter()
+            .filter(|t| t.meta.namespace == NAMESPACE)
+            .collect::<Vec<_>>();
+        assert_eq!(types.len(), 2);
+
+        let ibis = registry.find(&IbisAnnouncement::MESSAGE_META).unwrap();
+        assert_eq!(ibis.meta.bus.as_deref(), Some("ibis"));
+        assert!(registry
+            .validate(&Message::new(&Announcement {
+                text: "Next stop".into()
+            }))
+            .is_ok());
+
+        // Built-in types are registered too.
+        assert!(registry.get("builtin", "action_event").is_some());
+    }
+
+    #[test]
+    fn test_registered_twice() {
+        run_constructors();
+        let first = registered();
+        let second = registered();
+
+        assert_eq!(first.iter().count(), second.iter().count());
+        assert!(second.find(&Announcement::MESSAGE_META).is_some());
+    }
+}
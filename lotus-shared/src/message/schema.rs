@@ -0,0 +1,1112 @@
+//! Describes the shape of message types as JSON schema.
+//!
+//! The schema is derived from the [Deserialize](serde::Deserialize) implementation of a type,
+//! so every [MessageType](super::MessageType) has one without additional derives.
+//! Types that call `deserialize_any`, like untagged enums, are described as accepting any value,
+//! and so are structs with flattened fields.
+//! Recursive types are only described up to the first recursive reference.
+//! Struct fields are required if deserializing the struct without them fails because they are
+//! missing, i.e. unless they are `Option`s or have `#[serde(default)]`. Fields declared after a
+//! required field that can't be traced, e.g. an untagged enum, are described as optional.
+//! Named types are stored in `$defs` by their full type path.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{self, Display};
+
+use serde::de::{self, DeserializeOwned, DeserializeSeed, IntoDeserializer, Visitor};
+use serde_json::{json, Map, Value};
+
+/// The maximum number of passes used to discover all enum variants.
+const MAX_PASSES: usize = 256;
+
+/// Returns the JSON schema of the given type.
+pub fn schema_for<T: DeserializeOwned>() -> Value {
+    let mut state = State::default();
+    let mut root = None;
+
+    for _ in 0..MAX_PASSES {
+        let mut schema = Value::Bool(true);
+        let _ = T::deserialize(Tracer {
+            state: &mut state,
+            out: &mut schema,
+        });
+
+        // Trace the same variants again with the failing fields last, to reach the others.
+        if std::mem::take(&mut state.retry) {
+            continue;
+        }
+
+        root.get_or_insert(schema);
+        if !state.next_choice() {
+            break;
+        }
+    }
+
+    // Leave out every required field once, to find the ones that have a default.
+    state.probing = true;
+    for (name, field) in state.probes() {
+        state.probe = Some((name, field));
+        state.choices = state.structs[name].context.clone();
+        let _ = T::deserialize(Tracer {
+            state: &mut state,
+            out: &mut Value::Null,
+        });
+    }
+
+    state.finish(root.unwrap_or(Value::Bool(true)))
+}
+
+/// An error returned by [validate].
+#[derive(Debug, thiserror::Error)]
+#[error("{path}: {message}")]
+pub struct SchemaError {
+    /// The JSON pointer to the invalid value.
+    pub path: String,
+    /// What is wrong with the value.
+    pub message: String,
+}
+
+/// Validates a JSON value against a schema returned by [schema_for].
+pub fn validate(schema: &Value, value: &Value) -> Result<(), SchemaError> {
+    check(schema, schema, value, "")
+}
+
+fn check(root: &Value, schema: &Value, value: &Value, path: &str) -> Result<(), SchemaError> {
+    let error = |message: String| SchemaError {
+        path: if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        },
+        message,
+    };
+
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        Value::Bool(false) => return Err(error("no value is allowed".to_string())),
+        _ => return Ok(()),
+    };
+
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        let target = reference
+            .strip_prefix("#/$defs/")
+            .and_then(|name| root.get("$defs")?.get(name))
+            .ok_or_else(|| error(format!("unresolved reference {reference}")))?;
+        check(root, target, value, path)?;
+    }
+
+    if let Some(ty) = schema.get("type").and_then(Value::as_str) {
+        let matches = match ty {
+            "null" => value.is_null(),
+            "boolean" => value.is_boolean(),
+            "integer" => value.is_i64() || value.is_u64(),
+            "number" => value.is_number(),
+            "string" => value.is_string(),
+            "array" => value.is_array(),
+            "object" => value.is_object(),
+            _ => true,
+        };
+
+        if !matches {
+            return Err(error(format!("expected {ty}, got {value}")));
+        }
+    }
+
+    if let Some(expected) = schema.get("const") {
+        if value != expected {
+            return Err(error(format!("expected {expected}, got {value}")));
+        }
+    }
+
+    if let (Some(minimum), Some(value)) = (
+        schema.get("minimum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) {
+        if value < minimum {
+            return Err(error(format!("{value} is less than {minimum}")));
+        }
+    }
+
+    if let (Some(maximum), Some(value)) = (
+        schema.get("maximum").and_then(Value::as_f64),
+        value.as_f64(),
+    ) {
+        if value > maximum {
+            return Err(error(format!("{value} is greater than {maximum}")));
+        }
+    }
+
+    if let Some(value) = value.as_str() {
+        let len = value.chars().count() as u64;
+        if schema
+            .get("minLength")
+            .and_then(Value::as_u64)
+            .is_some_and(|min| len < min)
+            || schema
+                .get("maxLength")
+                .and_then(Value::as_u64)
+                .is_some_and(|max| len > max)
+        {
+            return Err(error(format!("invalid length of {value:?}")));
+        }
+    }
+
+    if let Some(options) = schema
+        .get("anyOf")
+        .or_else(|| schema.get("oneOf"))
+        .and_then(Value::as_array)
+    {
+        if !options
+            .iter()
+            .any(|option| check(root, option, value, path).is_ok())
+        {
+            return Err(error(format!("{value} does not match any variant")));
+        }
+    }
+
+    if let Value::Array(items) = value {
+        let len = items.len() as u64;
+        if schema
+            .get("minItems")
+            .and_then(Value::as_u64)
+            .is_some_and(|min| len < min)
+            || schema
+                .get("maxItems")
+                .and_then(Value::as_u64)
+                .is_some_and(|max| len > max)
+        {
+            return Err(error(format!("invalid number of items: {len}")));
+        }
+
+        let prefix = schema
+            .get("prefixItems")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+
+        for (index, item) in items.iter().enumerate() {
+            let item_schema = prefix.get(index).or_else(|| schema.get("items"));
+            if let Some(item_schema) = item_schema {
+                check(root, item_schema, item, &format!("{path}/{index}"))?;
+            }
+        }
+    }
+
+    if let Value::Object(map) = value {
+        let properties = schema.get("properties").and_then(Value::as_object);
+
+        for required in schema
+            .get("required")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Value::as_str)
+        {
+            if !map.contains_key(required) {
+                return Err(error(format!("missing property {required:?}")));
+            }
+        }
+
+        for (key, item) in map {
+            let item_path = format!("{path}/{key}");
+            match properties.and_then(|properties| properties.get(key)) {
+                Some(item_schema) => check(root, item_schema, item, &item_path)?,
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        return Err(error(format!("unknown property {key:?}")))
+                    }
+                    Some(item_schema) => check(root, item_schema, item, &item_path)?,
+                    None => {}
+                },
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The error of a traced type. Missing fields are kept apart from other errors, so probing
+/// doesn't depend on error messages.
+#[derive(Debug)]
+enum TraceError {
+    /// A field of the struct being deserialized is missing.
+    MissingField(&'static str),
+    Custom(String),
+}
+
+impl Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::MissingField(field) => write!(f, "missing field `{field}`"),
+            TraceError::Custom(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+impl de::Error for TraceError {
+    fn custom<T: Display>(message: T) -> Self {
+        TraceError::Custom(message.to_string())
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        TraceError::MissingField(field)
+    }
+}
+
+/// Types are identified by their full path, see [std::any::type_name].
+#[derive(Default)]
+struct State {
+    /// Schemas of named structs.
+    defs: BTreeMap<&'static str, Value>,
+    /// Variants seen so far for every named enum.
+    enums: HashMap<&'static str, EnumTrace>,
+    /// The variant to pick for each enum in the current pass.
+    choices: HashMap<&'static str, usize>,
+    /// Paths of the structs currently being traced.
+    stack: Vec<&'static str>,
+    /// Set when tracing stopped at a recursive type.
+    recursion: bool,
+    /// Every named struct seen, with the fields found to be optional.
+    structs: HashMap<&'static str, StructProbe>,
+    /// Set after all variants have been visited. Passes then only probe fields and don't
+    /// change the schemas.
+    probing: bool,
+    /// The struct and field that is left out in the current pass.
+    probe: Option<(&'static str, &'static str)>,
+    /// Struct fields that failed to deserialize, e.g. untagged enums. They are traced after
+    /// the other fields of the struct and left out while probing.
+    failing: HashSet<(&'static str, &'static str)>,
+    /// Set when a field failed for the first time, to trace the pass again.
+    retry: bool,
+}
+
+struct StructProbe {
+    /// The choices that led to this struct being reached for the first time.
+    context: HashMap<&'static str, usize>,
+    /// Fields that are not `Option`s, in the order they were traced.
+    required: Vec<&'static str>,
+    /// Required fields that can be left out anyway.
+    optional: Vec<&'static str>,
+}
+
+struct EnumTrace {
+    title: &'static str,
+    schemas: Vec<Option<Value>>,
+    visited: Vec<bool>,
+    /// The choices that led to this enum being reached for the first time.
+    context: HashMap<&'static str, usize>,
+}
+
+impl State {
+    fn enter_enum(
+        &mut self,
+        path: &'static str,
+        name: &'static str,
+        variants: &'static [&'static str],
+    ) -> usize {
+        let choice = self.choices.get(path).copied().unwrap_or_default();
+        if self.probing {
+            return choice;
+        }
+
+        let trace = self.enums.entry(path).or_insert_with(|| EnumTrace {
+            title: name,
+            schemas: vec![None; variants.len()],
+            visited: vec![false; variants.len()],
+            context: self.choices.clone(),
+        });
+        trace.visited[choice] = true;
+        choice
+    }
+
+    /// Picks an unvisited enum variant for the next pass. Returns `false` if all variants have been visited.
+    fn next_choice(&mut self) -> bool {
+        let mut names = self.enums.keys().copied().collect::<Vec<_>>();
+        names.sort_unstable();
+
+        for name in names {
+            let trace = self.enums.get_mut(name).unwrap();
+            if let Some(index) = trace.visited.iter().position(|visited| !visited) {
+                // Mark it right away, so a variant that can't be reached doesn't loop forever.
+                trace.visited[index] = true;
+                self.choices = trace.context.clone();
+                self.choices.insert(name, index);
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// Returns the required fields of all structs, to be left out one at a time.
+    fn probes(&self) -> Vec<(&'static str, &'static str)> {
+        let mut probes = self
+            .structs
+            .iter()
+            .flat_map(|(name, probe)| probe.required.iter().map(|field| (*name, *field)))
+            .collect::<Vec<_>>();
+        probes.sort_unstable();
+        probes
+    }
+
+    fn take_recursion(&mut self) -> bool {
+        std::mem::take(&mut self.recursion)
+    }
+
+    fn finish(mut self, root: Value) -> Value {
+        for (path, trace) in self.enums {
+            let variants = trace.schemas.into_iter().flatten().collect::<Vec<_>>();
+            self.defs
+                .insert(path, json!({ "title": trace.title, "oneOf": variants }));
+        }
+
+        for (path, probe) in self.structs {
+            let required = probe
+                .required
+                .iter()
+                .filter(|field| !probe.optional.contains(field))
+                .collect::<Vec<_>>();
+            if let Some(schema) = self.defs.get_mut(path) {
+                schema["required"] = json!(required);
+            }
+        }
+
+        let mut schema = Map::new();
+        schema.insert(
+            "$schema".to_string(),
+            "https://json-schema.org/draft/2020-12/schema".into(),
+        );
+        if let Value::Object(root) = root {
+            schema.extend(root);
+        }
+        if !self.defs.is_empty() {
+            schema.insert(
+                "$defs".to_string(),
+                Value::Object(
+                    self.defs
+                        .into_iter()
+                        .map(|(path, schema)| (def_name(path), schema))
+                        .collect(),
+                ),
+            );
+        }
+
+        Value::Object(schema)
+    }
+}
+
+fn integer(format: &str, bounds: Option<(i64, i64)>) -> Value {
+    let mut schema = json!({ "type": "integer", "format": format });
+    if let Some((minimum, maximum)) = bounds {
+        schema["minimum"] = minimum.into();
+        schema["maximum"] = maximum.into();
+    }
+    schema
+}
+
+fn is_nullable(schema: &Value) -> bool {
+    schema
+        .get("anyOf")
+        .and_then(Value::as_array)
+        .is_some_and(|options| options.iter().any(|o| o["type"] == "null"))
+}
+
+/// Returns the `$defs` key of a type path, e.g. `lotus_shared.message.MessageSource`.
+fn def_name(path: &str) -> String {
+    path.replace("::", ".").replace(' ', "")
+}
+
+fn reference(path: &str) -> Value {
+    json!({ "$ref": format!("#/$defs/{}", def_name(path)) })
+}
+
+fn object(properties: Map<String, Value>, required: Vec<&'static str>) -> Value {
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// A deserializer that records the schema of whatever is deserialized from it.
+struct Tracer<'a> {
+    state: &'a mut State,
+    out: &'a mut Value,
+}
+
+macro_rules! trace_primitive {
+    ($($method:ident => $visit:ident($($value:expr)?), $schema:expr;)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                *self.out = $schema;
+                visitor.$visit($($value)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = TraceError;
+
+    trace_primitive! {
+        deserialize_bool => visit_bool(false), json!({ "type": "boolean" });
+        deserialize_i8 => visit_i8(0), integer("int8", Some((i8::MIN as i64, i8::MAX as i64)));
+        deserialize_i16 => visit_i16(0), integer("int16", Some((i16::MIN as i64, i16::MAX as i64)));
+        deserialize_i32 => visit_i32(0), integer("int32", Some((i32::MIN as i64, i32::MAX as i64)));
+        deserialize_i64 => visit_i64(0), integer("int64", None);
+        deserialize_i128 => visit_i128(0), integer("int128", None);
+        deserialize_u8 => visit_u8(0), integer("uint8", Some((0, u8::MAX as i64)));
+        deserialize_u16 => visit_u16(0), integer("uint16", Some((0, u16::MAX as i64)));
+        deserialize_u32 => visit_u32(0), integer("uint32", Some((0, u32::MAX as i64)));
+        deserialize_u64 => visit_u64(0), json!({ "type": "integer", "format": "uint64", "minimum": 0 });
+        deserialize_u128 => visit_u128(0), json!({ "type": "integer", "format": "uint128", "minimum": 0 });
+        deserialize_f32 => visit_f32(0.0), json!({ "type": "number", "format": "float" });
+        deserialize_f64 => visit_f64(0.0), json!({ "type": "number", "format": "double" });
+        deserialize_char => visit_char(' '), json!({ "type": "string", "minLength": 1, "maxLength": 1 });
+        deserialize_str => visit_str(""), json!({ "type": "string" });
+        deserialize_string => visit_string(String::new()), json!({ "type": "string" });
+        deserialize_identifier => visit_str(""), json!({ "type": "string" });
+        deserialize_bytes => visit_bytes(&[]), json!({ "type": "array", "items": integer("uint8", Some((0, 255))) });
+        deserialize_byte_buf => visit_byte_buf(Vec::new()), json!({ "type": "array", "items": integer("uint8", Some((0, 255))) });
+        deserialize_unit => visit_unit(), json!({ "type": "null" });
+        deserialize_any => visit_unit(), Value::Bool(true);
+        deserialize_ignored_any => visit_unit(), Value::Bool(true);
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let mut inner = Value::Bool(true);
+        let result = visitor.visit_some(Tracer {
+            state: self.state,
+            out: &mut inner,
+        });
+        *self.out = json!({ "anyOf": [inner, { "type": "null" }] });
+        result
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let mut access = SeqTrace {
+            state: self.state,
+            len: 1,
+            items: Vec::new(),
+        };
+        let result = visitor.visit_seq(&mut access);
+        *self.out = json!({
+            "type": "array",
+            "items": access.items.pop().unwrap_or(Value::Bool(true)),
+        });
+        result
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let mut access = SeqTrace {
+            state: self.state,
+            len,
+            items: Vec::new(),
+        };
+        let result = visitor.visit_seq(&mut access);
+        *self.out = json!({
+            "type": "array",
+            "prefixItems": access.items,
+            "minItems": len,
+            "maxItems": len,
+        });
+        result
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let mut access = MapTrace {
+            state: self.state,
+            done: false,
+            value: Value::Bool(true),
+        };
+        let result = visitor.visit_map(&mut access);
+        *self.out = json!({ "type": "object", "additionalProperties": access.value });
+        result
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let path = std::any::type_name::<V::Value>();
+        *self.out = reference(path);
+
+        if self.state.stack.contains(&path) {
+            self.state.recursion = true;
+            return Err(de::Error::custom("recursive type"));
+        }
+
+        let skip = match self.state.probe {
+            Some((probe, field)) if probe == path => Some(field),
+            _ => None,
+        };
+
+        let failing = |field: &&str| self.state.failing.contains(&(path, *field));
+        let mut order = fields
+            .iter()
+            .copied()
+            .filter(|field| !failing(field))
+            .collect::<Vec<_>>();
+        if !self.state.probing {
+            order.extend(fields.iter().copied().filter(failing));
+        }
+        order.retain(|field| Some(*field) != skip);
+
+        self.state.stack.push(path);
+        let mut access = StructTrace {
+            state: self.state,
+            path: Some(path),
+            fields: order,
+            next: 0,
+            properties: Map::new(),
+            required: Vec::new(),
+        };
+        let result = visitor.visit_map(&mut access);
+        let state = access.state;
+        state.stack.pop();
+
+        if let Some(field) = skip {
+            // Nested missing fields are renamed by the struct trace, so this is the probed struct.
+            let required =
+                matches!(result, Err(TraceError::MissingField(missing)) if missing == field);
+            if !required {
+                let probe = state.structs.get_mut(path).unwrap();
+                if !probe.optional.contains(&field) {
+                    probe.optional.push(field);
+                }
+            }
+            return Err(de::Error::custom("field probed"));
+        }
+
+        if !state.probing {
+            state
+                .structs
+                .entry(path)
+                .and_modify(|probe| probe.required.clone_from(&access.required))
+                .or_insert_with(|| StructProbe {
+                    context: state.choices.clone(),
+                    required: access.required.clone(),
+                    optional: Vec::new(),
+                });
+
+            let mut schema = object(access.properties, access.required);
+            schema["title"] = name.into();
+            state.defs.insert(path, schema);
+        }
+
+        result
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let path = std::any::type_name::<V::Value>();
+        *self.out = reference(path);
+
+        if variants.is_empty() {
+            return Err(de::Error::custom("enum without variants"));
+        }
+
+        let choice = self.state.enter_enum(path, name, variants);
+        let mut schema = Value::Null;
+        let result = visitor.visit_enum(VariantTrace {
+            state: &mut *self.state,
+            variant: variants[choice],
+            out: &mut schema,
+        });
+
+        if !schema.is_null() && !self.state.probing {
+            self.state.enums.get_mut(path).unwrap().schemas[choice] = Some(schema);
+        }
+
+        result
+    }
+}
+
+struct SeqTrace<'a> {
+    state: &'a mut State,
+    len: usize,
+    items: Vec<Value>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqTrace<'_> {
+    type Error = TraceError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        if self.items.len() == self.len {
+            return Ok(None);
+        }
+
+        let mut schema = Value::Bool(true);
+        let result = seed.deserialize(Tracer {
+            state: &mut *self.state,
+            out: &mut schema,
+        });
+        self.items.push(schema);
+
+        match result {
+            Ok(value) => Ok(Some(value)),
+            // A recursive element ends the sequence early, the element schema is already a reference.
+            Err(_) if self.len == 1 && self.state.take_recursion() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.items.len())
+    }
+}
+
+struct MapTrace<'a> {
+    state: &'a mut State,
+    done: bool,
+    value: Value,
+}
+
+impl<'de> de::MapAccess<'de> for MapTrace<'_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        if self.done {
+            return Ok(None);
+        }
+
+        self.done = true;
+        seed.deserialize(Tracer {
+            state: &mut *self.state,
+            out: &mut Value::Null,
+        })
+        .map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        seed.deserialize(Tracer {
+            state: &mut *self.state,
+            out: &mut self.value,
+        })
+    }
+}
+
+struct StructTrace<'a> {
+    state: &'a mut State,
+    /// The path of the struct, `None` for struct variants.
+    path: Option<&'static str>,
+    /// The fields to pass to the visitor, in order.
+    fields: Vec<&'static str>,
+    /// The index of the next field.
+    next: usize,
+    properties: Map<String, Value>,
+    required: Vec<&'static str>,
+}
+
+impl<'de> de::MapAccess<'de> for StructTrace<'_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        match self.fields.get(self.next) {
+            Some(field) => seed.deserialize(field.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let field = self.fields[self.next];
+        self.next += 1;
+        let mut schema = Value::Bool(true);
+        let result = seed.deserialize(Tracer {
+            state: &mut *self.state,
+            out: &mut schema,
+        });
+
+        if !is_nullable(&schema) {
+            self.required.push(field);
+        }
+        self.properties.insert(field.to_string(), schema);
+
+        result.map_err(|error| {
+            if let Some(path) = self.path {
+                if !self.state.probing && self.state.failing.insert((path, field)) {
+                    self.state.retry = true;
+                }
+            }
+
+            // Missing fields of the value must not be mistaken for missing fields of this struct.
+            match error {
+                TraceError::MissingField(missing) => {
+                    de::Error::custom(format!("{field}: missing field `{missing}`"))
+                }
+                error => error,
+            }
+        })
+    }
+}
+
+struct VariantTrace<'a> {
+    state: &'a mut State,
+    variant: &'static str,
+    out: &'a mut Value,
+}
+
+impl VariantTrace<'_> {
+    fn tagged(&mut self, inner: Value) {
+        *self.out = json!({
+            "type": "object",
+            "properties": { self.variant: inner },
+            "required": [self.variant],
+            "additionalProperties": false,
+        });
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for VariantTrace<'_> {
+    type Error = TraceError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for VariantTrace<'_> {
+    type Error = TraceError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        *self.out = json!({ "const": self.variant });
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        mut self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        let mut inner = Value::Bool(true);
+        let result = seed.deserialize(Tracer {
+            state: &mut *self.state,
+            out: &mut inner,
+        });
+        self.tagged(inner);
+        result
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        mut self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let mut inner = Value::Bool(true);
+        let result = de::Deserializer::deserialize_tuple(
+            Tracer {
+                state: &mut *self.state,
+                out: &mut inner,
+            },
+            len,
+            visitor,
+        );
+        self.tagged(inner);
+        result
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        mut self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let mut access = StructTrace {
+            state: &mut *self.state,
+            path: None,
+            fields: fields.to_vec(),
+            next: 0,
+            properties: Map::new(),
+            required: Vec::new(),
+        };
+        let result = visitor.visit_map(&mut access);
+        let inner = object(access.properties, access.required);
+        self.tagged(inner);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Departure {
+        line: u32,
+        destination: String,
+        delay: Option<f32>,
+        doors: Vec<DoorState>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    enum DoorState {
+        Closed,
+        Open { side: Side },
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    enum Side {
+        Left,
+        Right,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Announcement {
+        text: String,
+        #[serde(default)]
+        repeat: u8,
+        side: other::Side,
+        sides: Vec<Side>,
+    }
+
+    mod other {
+        #[allow(dead_code)]
+        #[derive(serde::Deserialize)]
+        pub struct Side {
+            pub left: bool,
+        }
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Stop {
+        name: String,
+        #[serde(flatten)]
+        position: Position,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Content {
+        Text(String),
+        Lines(Vec<String>),
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Sign {
+        #[serde(default)]
+        brightness: u8,
+        content: Content,
+        #[serde(default)]
+        blink: bool,
+        text: String,
+        #[serde(skip_deserializing)]
+        cache: Vec<u8>,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize, Default)]
+    #[serde(default)]
+    struct Lights {
+        interior: bool,
+        reading: Option<bool>,
+        level: u8,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Deserialize)]
+    struct Car {
+        #[serde(rename = "car_number")]
+        number: u16,
+        lights: Lights,
+        #[serde(default = "Car::default_doors")]
+        doors: u8,
+        #[serde(default)]
+        info: Option<Box<Car>>,
+    }
+
+    impl Car {
+        fn default_doors() -> u8 {
+            4
+        }
+    }
+
+    const PREFIX: &str = "lotus_shared.message.schema.tests";
+
+    #[test]
+    fn test_schema_for_struct() {
+        let schema = schema_for::<Departure>();
+
+        assert_eq!(schema["$ref"], format!("#/$defs/{PREFIX}.Departure"));
+        let departure = &schema["$defs"][format!("{PREFIX}.Departure")];
+        assert_eq!(departure["title"], "Departure");
+        assert_eq!(departure["properties"]["line"]["type"], "integer");
+        assert_eq!(departure["properties"]["destination"]["type"], "string");
+        assert_eq!(
+            departure["required"],
+            json!(["line", "destination", "doors"])
+        );
+        assert_eq!(
+            schema["$defs"][format!("{PREFIX}.DoorState")]["oneOf"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            schema["$defs"][format!("{PREFIX}.Side")]["oneOf"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_schema_for_defaults_and_same_names() {
+        let schema = schema_for::<Announcement>();
+
+        let announcement = &schema["$defs"][format!("{PREFIX}.Announcement")];
+        assert_eq!(announcement["required"], json!(["text", "side", "sides"]));
+        assert_eq!(
+            schema["$defs"][format!("{PREFIX}.other.Side")]["required"],
+            json!(["left"])
+        );
+        assert!(schema["$defs"][format!("{PREFIX}.Side")]["oneOf"].is_array());
+
+        let valid = json!({ "text": "Next stop", "side": { "left": true }, "sides": ["Left"] });
+        validate(&schema, &valid).unwrap();
+    }
+
+    #[test]
+    fn test_message_source_defaults() {
+        let schema = schema_for::<super::super::MessageSource>();
+
+        let source = &schema["$defs"]["lotus_shared.message.MessageSource"];
+        assert_eq!(source["required"], json!([]));
+        validate(&schema, &json!({})).unwrap();
+    }
+
+    #[test]
+    fn test_validate() {
+        let schema = schema_for::<Departure>();
+
+        let valid = json!({
+            "line": 7,
+            "destination": "Hauptbahnhof",
+            "delay": null,
+            "doors": ["Closed", { "Open": { "side": "Left" } }],
+        });
+        validate(&schema, &valid).unwrap();
+
+        let invalid = json!({
+            "line": -1,
+            "destination": "Hauptbahnhof",
+            "doors": [{ "Open": { "side": "Up" } }],
+        });
+        assert!(validate(&schema, &invalid).is_err());
+    }
+
+    #[test]
+    fn test_schema_for_flatten() {
+        let schema = schema_for::<Stop>();
+
+        assert_eq!(schema["type"], "object");
+        validate(&schema, &json!({ "name": "Markt", "x": 1.0, "y": 2.0 })).unwrap();
+    }
+
+    #[test]
+    fn test_schema_for_untagged() {
+        let schema = schema_for::<Sign>();
+
+        let sign = &schema["$defs"][format!("{PREFIX}.Sign")];
+        assert_eq!(sign["properties"]["content"], json!(true));
+        assert_eq!(sign["properties"]["text"]["type"], "string");
+        assert!(sign["properties"].get("cache").is_none());
+        // Fields after the untagged enum can't be probed and are optional.
+        assert_eq!(sign["required"], json!(["content"]));
+
+        validate(&schema, &json!({ "content": "Hauptbahnhof", "text": "" })).unwrap();
+        validate(&schema, &json!({ "content": ["Line 5", "Hauptbahnhof"] })).unwrap();
+        assert!(validate(&schema, &json!({ "text": "" })).is_err());
+    }
+
+    #[test]
+    fn test_schema_for_struct_defaults() {
+        let schema = schema_for::<Car>();
+
+        let car = &schema["$defs"][format!("{PREFIX}.Car")];
+        assert_eq!(car["required"], json!(["car_number", "lights"]));
+        assert_eq!(
+            schema["$defs"][format!("{PREFIX}.Lights")]["required"],
+            json!([])
+        );
+        assert_eq!(
+            car["properties"]["info"]["anyOf"][0]["$ref"],
+            format!("#/$defs/{PREFIX}.Car")
+        );
+
+        validate(&schema, &json!({ "car_number": 2, "lights": {} })).unwrap();
+        assert!(validate(&schema, &json!({ "number": 2, "lights": {} })).is_err());
+    }
+}
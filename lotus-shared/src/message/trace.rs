@@ -0,0 +1,353 @@
+//! Opt-in tracing of sent and received messages.
+//!
+//! When enabled with [enable], every message sent with [send_message](super::send_message) and
+//! every message received by the script is recorded into a ring buffer. The buffer can be dumped as JSON Lines, either by the
+//! script itself with [dump_json_lines] or by sending it a [DumpMessageTrace] message, which
+//! writes the trace to the log.
+//!
+//! Dumps can be inspected and filtered offline with the `lotus-message-trace` tool.
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use super::{Coupling, Message, MessageMeta, MessageSchedule, MessageSource, MessageTarget};
+use crate::message_type;
+
+/// Whether a traced message was sent or received by the script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TraceDirection {
+    Sent,
+    Received,
+}
+
+/// A traced message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraceEntry {
+    /// The tick of the script in which the message was sent or received.
+    pub tick: u64,
+    /// The in-game time when the message was sent or received, in microseconds since the Unix
+    /// epoch. Unlike ticks, it is the same for all scripts, so traces of different scripts can be
+    /// merged on it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub game_time_micros: Option<i64>,
+    pub direction: TraceDirection,
+    pub meta: MessageMeta,
+    /// The source of a received message.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<MessageSource>,
+    /// The targets of a sent message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<MessageTarget>,
+    /// The schedule of a message sent with a [MessageSchedule].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<MessageSchedule>,
+    /// The message value, `null` if it could not be decoded.
+    pub payload: serde_json::Value,
+}
+
+impl TraceEntry {
+    /// Creates an entry for a sent message.
+    pub fn sent(
+        tick: u64,
+        message: &Message,
+        targets: Vec<MessageTarget>,
+        schedule: Option<MessageSchedule>,
+    ) -> Self {
+        Self {
+            tick,
+            game_time_micros: None,
+            direction: TraceDirection::Sent,
+            meta: message.meta().clone(),
+            source: None,
+            targets,
+            schedule,
+            payload: message.payload().to_json().unwrap_or_default(),
+        }
+    }
+
+    /// Creates an entry for a received message.
+    pub fn received(tick: u64, message: &Message) -> Self {
+        Self {
+            tick,
+            game_time_micros: None,
+            direction: TraceDirection::Received,
+            meta: message.meta().clone(),
+            source: Some(*message.source()),
+            targets: Vec::new(),
+            schedule: None,
+            payload: message.payload().to_json().unwrap_or_default(),
+        }
+    }
+
+    /// Sets the in-game time of the entry.
+    pub fn with_game_time(mut self, unix_micros: i64) -> Self {
+        self.game_time_micros = Some(unix_micros);
+        self
+    }
+}
+
+/// A ring buffer of traced messages. Once full, the oldest entries are dropped.
+#[derive(Debug, Clone)]
+pub struct MessageTrace {
+    entries: VecDeque<TraceEntry>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl MessageTrace {
+    /// Creates an empty trace keeping at most `capacity` entries.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+
+        Self {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            dropped: 0,
+        }
+    }
+
+    /// Adds an entry, dropping the oldest one if the trace is full.
+    pub fn push(&mut self, entry: TraceEntry) {
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+            self.dropped += 1;
+        }
+
+        self.entries.push_back(entry);
+    }
+
+    /// Returns the entries from oldest to newest.
+    pub fn iter(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the number of entries.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the number of entries dropped because the trace was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Removes all entries.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.dropped = 0;
+    }
+
+    /// Returns the entries as JSON Lines, one entry per line.
+    pub fn to_json_lines(&self) -> String {
+        to_json_lines(self.iter())
+    }
+}
+
+/// Returns the given entries as JSON Lines, one entry per line.
+pub fn to_json_lines<'a>(entries: impl IntoIterator<Item = &'a TraceEntry>) -> String {
+    let mut lines = String::new();
+
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry).unwrap());
+        lines.push('\n');
+    }
+
+    lines
+}
+
+/// Parses entries from JSON Lines. Empty lines are skipped.
+pub fn from_json_lines(lines: &str) -> Result<Vec<TraceEntry>, serde_json::Error> {
+    lines
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+/// Selects trace entries, every field that is set has to match.
+#[derive(Debug, Default, Clone)]
+pub struct TraceFilter {
+    pub namespace: Option<String>,
+    pub identifier: Option<String>,
+    pub bus: Option<String>,
+    pub direction: Option<TraceDirection>,
+    /// Matches received messages coming from this coupling and sent messages targeting it.
+    pub coupling: Option<Coupling>,
+    pub from_tick: Option<u64>,
+    pub to_tick: Option<u64>,
+}
+
+impl TraceFilter {
+    /// Returns `true` if the entry matches the filter.
+    pub fn matches(&self, entry: &TraceEntry) -> bool {
+        self.namespace
+            .as_ref()
+            .is_none_or(|namespace| entry.meta.namespace == namespace.as_str())
+            && self
+                .identifier
+                .as_ref()
+                .is_none_or(|identifier| entry.meta.identifier == identifier.as_str())
+            && self
+                .bus
+                .as_ref()
+                .is_none_or(|bus| entry.meta.bus.as_deref() == Some(bus.as_str()))
+            && self
+                .direction
+                .is_none_or(|direction| entry.direction == direction)
+            && self
+                .coupling
+                .is_none_or(|coupling| involves_coupling(entry, coupling))
+            && self.from_tick.is_none_or(|tick| entry.tick >= tick)
+            && self.to_tick.is_none_or(|tick| entry.tick <= tick)
+    }
+}
+
+fn involves_coupling(entry: &TraceEntry, coupling: Coupling) -> bool {
+    match entry.direction {
+        TraceDirection::Received => entry
+            .source
+            .is_some_and(|source| source.coupling == Some(coupling)),
+        TraceDirection::Sent => entry.targets.iter().any(|target| match target {
+            MessageTarget::AcrossCoupling { coupling: c, .. } => *c == coupling,
+            MessageTarget::Broadcast {
+                across_couplings, ..
+            } => *across_couplings,
+            _ => false,
+        }),
+    }
+}
+
+/// When received by a script that has tracing enabled, the script writes its trace to the log as JSON Lines.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DumpMessageTrace {
+    /// Whether to clear the trace after dumping it.
+    pub clear: bool,
+}
+
+message_type!(DumpMessageTrace, "builtin", "dump_message_trace");
+
+#[cfg(feature = "ffi")]
+mod ffi {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    thread_local! {
+        static TRACE: RefCell<Option<MessageTrace>> = const { RefCell::new(None) };
+    }
+
+    fn tick() -> u64 {
+        unsafe { lotus_script_sys::time::ticks_alive() }
+    }
+
+    fn game_time() -> i64 {
+        unsafe { lotus_script_sys::time::game_time() }
+    }
+
+    /// Enables tracing, keeping at most `capacity` entries. Existing entries are kept if tracing is already enabled.
+    pub fn enable(capacity: usize) {
+        TRACE.with_borrow_mut(|trace| match trace {
+            Some(trace) if trace.capacity == capacity => {}
+            Some(trace) => {
+                let mut resized = MessageTrace::new(capacity);
+                for entry in trace.entries.drain(..) {
+                    resized.push(entry);
+                }
+                *trace = resized;
+            }
+            None => *trace = Some(MessageTrace::new(capacity)),
+        });
+    }
+
+    /// Disables tracing and discards all entries.
+    pub fn disable() {
+        TRACE.with_borrow_mut(|trace| *trace = None);
+    }
+
+    /// Returns `true` if tracing is enabled.
+    pub fn is_enabled() -> bool {
+        TRACE.with_borrow(Option::is_some)
+    }
+
+    /// Calls the given function with the trace, if tracing is enabled.
+    pub fn with_trace<R>(f: impl FnOnce(&mut MessageTrace) -> R) -> Option<R> {
+        TRACE.with_borrow_mut(|trace| trace.as_mut().map(f))
+    }
+
+    /// Returns the trace as JSON Lines, `None` if tracing is disabled.
+    pub fn dump_json_lines() -> Option<String> {
+        with_trace(|trace| trace.to_json_lines())
+    }
+
+    #[doc(hidden)]
+    pub fn record_sent(
+        message: &Message,
+        targets: &[MessageTarget],
+        schedule: Option<MessageSchedule>,
+    ) {
+        with_trace(|trace| {
+            trace.push(
+                TraceEntry::sent(tick(), message, targets.to_vec(), schedule)
+                    .with_game_time(game_time()),
+            )
+        });
+    }
+
+    #[doc(hidden)]
+    pub fn record_received(message: &Message) {
+        with_trace(|trace| {
+            trace.push(TraceEntry::received(tick(), message).with_game_time(game_time()))
+        });
+    }
+}
+
+#[cfg(feature = "ffi")]
+pub use ffi::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize, Deserialize)]
+    struct Ping(u32);
+
+    message_type!(Ping, "test", "ping", "ibis");
+
+    #[test]
+    fn test_ring_buffer_and_json_lines() {
+        let mut trace = MessageTrace::new(2);
+        let message = Message::new(&Ping(1));
+        let targets = vec![MessageTarget::AcrossCoupling {
+            coupling: Coupling::Rear,
+            cascade: true,
+        }];
+
+        trace.push(TraceEntry::sent(1, &message, targets.clone(), None));
+        trace.push(TraceEntry::received(2, &message).with_game_time(1_000_000));
+        trace.push(TraceEntry::sent(3, &message, targets, None));
+
+        assert_eq!(trace.len(), 2);
+        assert_eq!(trace.dropped(), 1);
+
+        let entries = from_json_lines(&trace.to_json_lines()).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].tick, 2);
+        assert_eq!(entries[0].game_time_micros, Some(1_000_000));
+        assert_eq!(entries[1].game_time_micros, None);
+        assert_eq!(entries[1].payload, serde_json::json!(1));
+
+        let filter = TraceFilter {
+            coupling: Some(Coupling::Rear),
+            bus: Some("ibis".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(entries.iter().filter(|e| filter.matches(e)).count(), 1);
+    }
+}
@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
-use crate::message::{Coupling, MessageMeta, MessageType};
+use crate::{message::Coupling, message_type};
 
 #[derive(Debug, thiserror::Error)]
 pub enum VehicleError {
@@ -57,9 +57,11 @@ pub struct TrainConfigurationChanged {
     pub train_vehicle_count: usize,
 }
 
-impl MessageType for TrainConfigurationChanged {
-    const MESSAGE_META: MessageMeta = MessageMeta::new("builtin", "vehicle_in_train_changed", None);
-}
+message_type!(
+    TrainConfigurationChanged,
+    "builtin",
+    "vehicle_in_train_changed"
+);
 
 /// Calculation of the vehicle count in front or behind the vehicle
 /// relative to the vehicle.